@@ -13,7 +13,6 @@ extern crate zinc;
 #[macro_use] #[no_link] extern crate macro_platformtree;
 
 use zinc::hal::timer::Timer;
-use zinc::hal::lpc17xx::pwm;
 use zinc::hal::pwm::PWMOutput;
 
 // This example shows use of the RGB LED that is availble on the MBED
@@ -58,6 +57,12 @@ platformtree!(
         }
       }
     }
+
+    pwm {
+      pwm_blue@2  { period_us = 20_000; }
+      pwm_green@3 { period_us = 20_000; }
+      pwm_red@4   { period_us = 20_000; }
+    }
   }
 
   os {
@@ -65,15 +70,19 @@ platformtree!(
       loop = "run";
       args {
         timer = &timer;
+        pwm_red = &pwm_red;
+        pwm_green = &pwm_green;
+        pwm_blue = &pwm_blue;
       }
     }
   }
 );
 
 fn run(args: &pt::run_args) {
-  let mut pwm_red = pwm::PWM::new(pwm::PWMChannel::Channel4, 20_000);
-  let mut pwm_green = pwm::PWM::new(pwm::PWMChannel::Channel3, 20_000);
-  let mut pwm_blue = pwm::PWM::new(pwm::PWMChannel::Channel2, 20_000);
+  // PWM outputs are cheap handles, copy them to change their duty cycle.
+  let mut pwm_red = *args.pwm_red;
+  let mut pwm_green = *args.pwm_green;
+  let mut pwm_blue = *args.pwm_blue;
 
   // turn all off
   pwm_red.write(0.0);
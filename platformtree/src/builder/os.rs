@@ -51,14 +51,24 @@ pub fn attach(builder: &mut Builder, _: &mut ExtCtxt, node: Rc<node::Node>) {
       }
     }
   }
+
+  let maybe_tasks_node = node.get_by_path("tasks");
+  if maybe_tasks_node.is_some() {
+    let tasks_node = maybe_tasks_node.unwrap();
+    tasks_node.materializer.set(Some(build_tasks as fn(&mut Builder, &mut ExtCtxt, Rc<node::Node>)));
+    add_node_dependency(&node, &tasks_node);
+    add_node_dependency(&tasks_node, &mcu_node);
+  }
 }
 
 pub fn verify(_: &mut Builder, cx: &mut ExtCtxt, node: Rc<node::Node>) {
   node.expect_no_attributes(cx);
-  node.expect_subnodes(cx, &["single_task"]);
-  if node.get_by_path("single_task").is_none() {
+  node.expect_subnodes(cx, &["single_task", "tasks"]);
+  let single_task = node.get_by_path("single_task").is_some();
+  let tasks = node.get_by_path("tasks").is_some();
+  if single_task == tasks {
     cx.parse_sess().span_diagnostic.span_err(node.name_span,
-        "subnode `single_task` must be present");
+        "exactly one of subnodes `single_task` or `tasks` must be present");
   }
 }
 
@@ -86,6 +96,70 @@ fn build_single_task(builder: &mut Builder, cx: &mut ExtCtxt,
   }
 }
 
+/// Defines every subnode of `tasks` with `zinc::os::task` and starts the
+/// scheduler.
+///
+/// The task table gets a slot per task and one for the idle task, unless
+/// `max_tasks` asks for more to spawn tasks at runtime.
+fn build_tasks(builder: &mut Builder, cx: &mut ExtCtxt,
+    node: Rc<node::Node>) {
+  let tasks = node.subnodes();
+  let max_tasks = match node.get_int_attr("max_tasks") {
+    Some(max) if max < tasks.len() + 1 => {
+      cx.parse_sess().span_diagnostic.span_err(
+          node.get_attr("max_tasks").value_span,
+          format!("`max_tasks` must be at least {}, one slot is taken by \
+              the idle task", tasks.len() + 1).as_str());
+      return;
+    },
+    Some(max) => max,
+    None => tasks.len() + 1,
+  };
+
+  let table = quote_item!(&*cx,
+      pub static mut TASKS: [zinc::os::task::TaskDescriptor; $max_tasks] =
+          [zinc::os::task::TASK_DESCRIPTOR_INIT; $max_tasks];
+  ).unwrap();
+  builder.add_type_item((*table).clone());
+  builder.add_main_statement(quote_stmt!(&*cx,
+      unsafe { zinc::os::task::init(&mut pt::TASKS) }
+  ).unwrap());
+
+  for task in tasks.iter() {
+    if !task.expect_attributes(cx, &[
+        ("entry", node::StrAttribute),
+        ("stack_size", node::IntAttribute)]) {
+      continue;
+    }
+    task.expect_no_subnodes(cx);
+
+    let entry = TokenString(task.get_string_attr("entry").unwrap());
+    let stack_size = task.get_int_attr("stack_size").unwrap() as u32;
+    let arg = task.get_int_attr("arg").unwrap_or(0) as u32;
+    let priority = match task.get_int_attr("priority") {
+      Some(p) if p > 255 => {
+        cx.parse_sess().span_diagnostic.span_err(
+            task.get_attr("priority").value_span,
+            "`priority` must be at most 255");
+        continue;
+      },
+      Some(p) => {
+        let p = p as u8;
+        quote_expr!(&*cx, $p)
+      },
+      None => quote_expr!(&*cx, zinc::os::task::DEFAULT_PRIORITY),
+    };
+
+    builder.add_main_statement(quote_stmt!(&*cx,
+        zinc::os::task::define_task($entry, $arg, $stack_size, $priority)
+    ).unwrap());
+  }
+
+  builder.add_main_statement(quote_stmt!(&*cx,
+      zinc::os::task::start()
+  ).unwrap());
+}
+
 fn build_args(builder: &mut Builder, cx: &mut ExtCtxt,
     struct_name: &String, node: Rc<node::Node>) -> P<ast::Expr> {
   let mut fields = vec!();
@@ -219,8 +293,8 @@ mod test {
   use syntax::ext::build::AstBuilder;
 
   use builder::Builder;
-  use super::build_single_task;
-  use test_helpers::{assert_equal_source, with_parsed};
+  use super::{build_single_task, build_tasks};
+  use test_helpers::{assert_equal_items, assert_equal_source, with_parsed};
 
   #[test]
   fn builds_single_task_os_loop() {
@@ -280,4 +354,76 @@ mod test {
           }");
     });
   }
+
+  #[test]
+  fn builds_tasks() {
+    with_parsed("
+      tasks {
+        blink {
+          entry = \"blink\";
+          stack_size = 256;
+          priority = 2;
+        }
+        report {
+          entry = \"report\";
+          stack_size = 512;
+          arg = 7;
+        }
+      }", |cx, failed, pt| {
+      let mut builder = Builder::new(pt.clone(), cx);
+      build_tasks(&mut builder, cx, pt.get_by_path("tasks").unwrap().clone());
+      assert!(unsafe{*failed} == false);
+      assert!(builder.main_stmts.len() == 4);
+      assert!(builder.type_items.len() == 2);
+
+      assert_equal_items(&builder.type_items[1],
+          "pub static mut TASKS: [zinc::os::task::TaskDescriptor; 3usize] =
+              [zinc::os::task::TASK_DESCRIPTOR_INIT; 3usize];");
+      assert_equal_source(&builder.main_stmts[0],
+          "unsafe { zinc::os::task::init(&mut pt::TASKS) }");
+      assert_equal_source(&builder.main_stmts[1],
+          "zinc::os::task::define_task(blink, 0u32, 256u32, 2u8)");
+      assert_equal_source(&builder.main_stmts[2],
+          "zinc::os::task::define_task(report, 7u32, 512u32,
+              zinc::os::task::DEFAULT_PRIORITY)");
+      assert_equal_source(&builder.main_stmts[3],
+          "zinc::os::task::start()");
+    });
+  }
+
+  #[test]
+  fn builds_tasks_with_spare_slots() {
+    with_parsed("
+      tasks {
+        max_tasks = 6;
+        blink {
+          entry = \"blink\";
+          stack_size = 256;
+        }
+      }", |cx, failed, pt| {
+      let mut builder = Builder::new(pt.clone(), cx);
+      build_tasks(&mut builder, cx, pt.get_by_path("tasks").unwrap().clone());
+      assert!(unsafe{*failed} == false);
+
+      assert_equal_items(&builder.type_items[1],
+          "pub static mut TASKS: [zinc::os::task::TaskDescriptor; 6usize] =
+              [zinc::os::task::TASK_DESCRIPTOR_INIT; 6usize];");
+    });
+  }
+
+  #[test]
+  fn fails_to_build_task_with_out_of_range_priority() {
+    with_parsed("
+      tasks {
+        blink {
+          entry = \"blink\";
+          stack_size = 256;
+          priority = 300;
+        }
+      }", |cx, failed, pt| {
+      let mut builder = Builder::new(pt.clone(), cx);
+      build_tasks(&mut builder, cx, pt.get_by_path("tasks").unwrap().clone());
+      assert!(unsafe{*failed} == true);
+    });
+  }
 }
@@ -116,9 +116,7 @@ impl<G: Gpio, S: Spi> BlueNrg<G, S> {
       self.active.set_high();
       Err(Error::BufferSize(size))
     }else {
-      for b in buf.iter_mut() {
-        *b = self.spi.transfer(0);
-      }
+      self.spi.read_into(buf);
       self.active.set_high();
       Ok(())
     }
@@ -140,9 +138,7 @@ impl<G: Gpio, S: Spi> BlueNrg<G, S> {
       self.active.set_high();
       Err(Error::BufferSize(size))
     }else {
-      for b in buf.iter() {
-        self.spi.transfer(*b);
-      }
+      self.spi.write_all(buf);
       self.active.set_high();
       Ok(())
     }
@@ -13,10 +13,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-//! Generic char output trait.
+//! Generic char input and output traits.
 
 use core::slice::SliceExt;
 use core::convert::AsRef;
+use core::option::Option::{self, Some, None};
+use core::result::Result::{self, Ok, Err};
 
 use core::mem::zeroed;
 
@@ -64,11 +66,66 @@ pub trait CharIO {
   }
 }
 
+/// Errors reported by the receiver of a character device.
+#[derive(PartialEq, Clone, Copy)]
+pub enum ReadError {
+  /// A character was received without a valid stop bit.
+  Framing,
+  /// A character was received with a wrong parity bit.
+  Parity,
+  /// A character arrived while the receive buffer was full and was lost.
+  Overrun,
+}
+
+/// CharInput provides interface for reading characters.
+///
+/// This is the receiving companion to `CharIO`. It implements blocking and
+/// line-based reads on top of a single method: `try_getc`.
+pub trait CharInput {
+  /// Returns a received character, or `None` if there is nothing to read.
+  ///
+  /// This function never blocks.
+  fn try_getc(&self) -> Result<Option<char>, ReadError>;
+
+  /// Waits for a character and returns it.
+  fn getc(&self) -> Result<char, ReadError> {
+    loop {
+      match self.try_getc() {
+        Ok(Some(c)) => return Ok(c),
+        Ok(None) => {},
+        Err(e) => return Err(e),
+      }
+    }
+  }
+
+  /// Reads a line into `buf`, returning the number of bytes stored.
+  ///
+  /// The line terminator (`\r` or `\n`) is not stored. Empty lines are
+  /// skipped, so `\r\n` line endings produce a single line. If `buf` fills up
+  /// before the end of line the rest of it is left for the next read.
+  fn read_line(&self, buf: &mut [u8]) -> Result<usize, ReadError> {
+    let mut len = 0;
+    while len < buf.len() {
+      match try!(self.getc()) {
+        '\r' | '\n' if len == 0 => {},
+        '\r' | '\n' => break,
+        c => {
+          buf[len] = c as u8;
+          len += 1;
+        },
+      }
+    }
+    Ok(len)
+  }
+}
+
 #[cfg(test)]
 pub mod test {
   use core::cell::RefCell;
+  use core::option::Option::{Some, None};
+  use core::result::Result::{self, Ok, Err};
 
-  use drivers::chario::CharIO;
+  use drivers::chario::{CharIO, CharInput, ReadError};
 
   #[derive(Clone, Copy)]
   pub struct TestCharIOData {
@@ -109,6 +166,30 @@ pub mod test {
     }
   }
 
+  pub struct TestCharInput<'a> {
+    input: RefCell<&'a [Result<char, ReadError>]>,
+  }
+
+  impl<'a> CharInput for TestCharInput<'a> {
+    fn try_getc(&self) -> Result<Option<char>, ReadError> {
+      let mut input = self.input.borrow_mut();
+      if input.len() == 0 {
+        return Ok(None);
+      }
+      let c = input[0];
+      *input = &input[1..];
+      c.map(|c| Some(c))
+    }
+  }
+
+  impl<'a> TestCharInput<'a> {
+    pub fn new(input: &'a [Result<char, ReadError>]) -> TestCharInput<'a> {
+      TestCharInput {
+        input: RefCell::new(input),
+      }
+    }
+  }
+
   #[test]
   fn putc_should_store_a_char() {
     let io = TestCharIO::new();
@@ -179,4 +260,40 @@ pub mod test {
     assert!(io.get_last_char() == '\t');
     assert!(io.get_and_reset_putc_calls() == 2);
   }
+
+  #[test]
+  fn getc_should_return_chars_in_order() {
+    let io = TestCharInput::new(&[Ok('a'), Ok('b')]);
+    assert!(io.getc() == Ok('a'));
+    assert!(io.getc() == Ok('b'));
+    assert!(io.try_getc() == Ok(None));
+  }
+
+  #[test]
+  fn getc_should_report_errors() {
+    let io = TestCharInput::new(&[Err(ReadError::Framing), Ok('a')]);
+    assert!(io.getc() == Err(ReadError::Framing));
+    assert!(io.getc() == Ok('a'));
+  }
+
+  #[test]
+  fn read_line_should_stop_at_line_endings() {
+    let io = TestCharInput::new(&[Ok('a'), Ok('b'), Ok('\r'), Ok('\n'),
+                                  Ok('c'), Ok('\n')]);
+    let mut buf = [0u8; 8];
+    assert!(io.read_line(&mut buf) == Ok(2));
+    assert!(&buf[..2] == b"ab");
+    assert!(io.read_line(&mut buf) == Ok(1));
+    assert!(&buf[..1] == b"c");
+  }
+
+  #[test]
+  fn read_line_should_stop_when_buffer_is_full() {
+    let io = TestCharInput::new(&[Ok('a'), Ok('b'), Ok('c'), Ok('\n')]);
+    let mut buf = [0u8; 2];
+    assert!(io.read_line(&mut buf) == Ok(2));
+    assert!(&buf == b"ab");
+    assert!(io.read_line(&mut buf) == Ok(1));
+    assert!(buf[0] == 'c' as u8);
+  }
 }
@@ -14,12 +14,24 @@
 // limitations under the License.
 
 //! Driver for the ILI9341 LCD.
+//!
+//! When the SPI peripheral implements `SpiDma`, whole rectangles of pixels can
+//! be streamed with `blit_dma` and `clear_dma` instead of byte by byte.
+
+use core::intrinsics::abort;
+use core::result::Result::{self, Ok};
 
 use super::LCD;
 use drivers::chario::CharIO;
+use hal::dma;
 use hal::timer::Timer;
 use hal::pin::Gpio;
-use hal::spi::Spi;
+use hal::spi::{Spi, SpiDma};
+
+/// Screen width, in pixels.
+const WIDTH: u16 = 240;
+/// Screen height, in pixels.
+const HEIGHT: u16 = 320;
 
 /// ILI9341 driver.
 pub struct ILI9341<'a, S:'a, T:'a, P:'a> {
@@ -270,17 +282,16 @@ impl<'a, S: Spi, T: Timer, P: Gpio> ILI9341<'a, S, T, P> {
   }
 
   fn do_clear(&self) {
-    self.set_col(0, 239);
-    self.set_page(0, 319);
+    self.set_col(0, WIDTH - 1);
+    self.set_page(0, HEIGHT - 1);
     self.send_cmd(0x2c);
 
     self.dc.set_high();
     self.cs.set_low();
-    for _ in 0..38400 {
-      self.spi.transfer(0);
-      self.spi.transfer(0);
-      self.spi.transfer(0);
-      self.spi.transfer(0);
+    // 2 bytes per pixel
+    let zeroes = [0u8; 64];
+    for _ in 0..(WIDTH as usize * HEIGHT as usize * 2 / 64) {
+      self.spi.write_all(&zeroes);
     }
     self.cs.set_high();
   }
@@ -293,6 +304,50 @@ impl<'a, S: Spi, T: Timer, P: Gpio> ILI9341<'a, S, T, P> {
   }
 }
 
+impl<'a, S: SpiDma, T: Timer, P: Gpio> ILI9341<'a, S, T, P> {
+  /// Draws a `width` by `height` rectangle at `x`, `y`, writing `pixels`
+  /// through a DMA channel.
+  ///
+  /// `pixels` holds the rectangle row by row, two big-endian RGB565 bytes per
+  /// pixel.
+  pub fn blit_dma(&self, channel: &S::Channel, x: u16, y: u16, width: u16,
+      height: u16, pixels: &[u8]) -> Result<(), dma::Error> {
+    if width == 0 || height == 0 ||
+        pixels.len() != width as usize * height as usize * 2 {
+      unsafe { abort() };
+    }
+    self.set_col(x, x + width - 1);
+    self.set_page(y, y + height - 1);
+    self.send_cmd(0x2c);
+
+    self.dc.set_high();
+    self.cs.set_low();
+    let result = self.spi.write_dma(channel, pixels);
+    self.cs.set_high();
+    result
+  }
+
+  /// Clears the screen, writing the pixels through a DMA channel.
+  pub fn clear_dma(&self, channel: &S::Channel) -> Result<(), dma::Error> {
+    self.set_col(0, WIDTH - 1);
+    self.set_page(0, HEIGHT - 1);
+    self.send_cmd(0x2c);
+
+    self.dc.set_high();
+    self.cs.set_low();
+    let row = [0u8; WIDTH as usize * 2];
+    let mut result = Ok(());
+    for _ in 0..HEIGHT {
+      result = self.spi.write_dma(channel, &row);
+      if result.is_err() {
+        break;
+      }
+    }
+    self.cs.set_high();
+    result
+  }
+}
+
 impl<'a, S: Spi, T: Timer, P: Gpio> LCD for ILI9341<'a, S, T, P> {
   fn clear(&self) {
     self.do_clear();
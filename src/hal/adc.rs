@@ -0,0 +1,124 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/*!
+Analog to digital converter interface.
+
+ADCs are MCU-specific structs that implement the `Adc` trait. Conversions can
+be run one at a time with `read` and `scan`, or left running in the background
+with `start_continuous`, the last result being available from `latest`.
+
+Drivers also provide a `start_triggered` method that starts a conversion on
+each event of a hardware timer. As the available triggers differ between
+parts, it takes an MCU-specific `Trigger`.
+
+Results are right-aligned raw values, use `to_millivolts` to scale them.
+*/
+
+use core::result::Result::{self, Ok};
+
+/// ADC errors.
+#[derive(PartialEq, Clone, Copy)]
+pub enum Error {
+  /// The requested resolution is not available on this ADC.
+  UnsupportedResolution,
+  /// No sample time at least as long as the requested one is available.
+  UnsupportedSampleTime,
+  /// The channel does not exist on this ADC.
+  InvalidChannel,
+}
+
+/// Conversion resolution.
+#[derive(PartialEq, Clone, Copy)]
+pub enum Resolution {
+  /// 6-bit results.
+  Bits6,
+  /// 8-bit results.
+  Bits8,
+  /// 10-bit results.
+  Bits10,
+  /// 12-bit results.
+  Bits12,
+  /// 16-bit results.
+  Bits16,
+}
+
+impl Resolution {
+  /// Returns the number of bits in a result.
+  pub fn bits(self) -> u32 {
+    match self {
+      Resolution::Bits6  => 6,
+      Resolution::Bits8  => 8,
+      Resolution::Bits10 => 10,
+      Resolution::Bits12 => 12,
+      Resolution::Bits16 => 16,
+    }
+  }
+
+  /// Returns the result for an input at the reference voltage.
+  pub fn max_value(self) -> u32 {
+    (1 << self.bits()) - 1
+  }
+}
+
+/// Scales a raw result to millivolts, given the reference voltage.
+pub fn to_millivolts(value: u16, resolution: Resolution, vref_mv: u32) -> u32 {
+  value as u32 * vref_mv / resolution.max_value()
+}
+
+/// ADC trait.
+///
+/// Channels are numbered as in the MCU reference manual.
+pub trait Adc {
+  /// Sets the resolution of the following conversions.
+  fn set_resolution(&self, resolution: Resolution) -> Result<(), Error>;
+
+  /// Returns the current resolution.
+  fn resolution(&self) -> Resolution;
+
+  /// Sets the sample time of all channels, in ADC clock cycles.
+  ///
+  /// The shortest available sample time that is at least `cycles` long is
+  /// used.
+  fn set_sample_time(&self, cycles: u32) -> Result<(), Error>;
+
+  /// Runs a single conversion of `channel` and returns the result.
+  fn read(&self, channel: u8) -> Result<u16, Error>;
+
+  /// Converts each of `channels` in turn, storing the results in the matching
+  /// entries of `results`.
+  ///
+  /// Conversion stops at the end of the shorter slice.
+  fn scan(&self, channels: &[u8], results: &mut [u16]) -> Result<(), Error> {
+    for (&channel, result) in channels.iter().zip(results.iter_mut()) {
+      *result = try!(self.read(channel));
+    }
+    Ok(())
+  }
+
+  /// Starts converting `channel` continuously.
+  fn start_continuous(&self, channel: u8) -> Result<(), Error>;
+
+  /// Stops continuous or triggered conversions.
+  fn stop(&self);
+
+  /// Returns the result of the last completed conversion.
+  fn latest(&self) -> u16;
+
+  /// Scales a result of this ADC to millivolts, given the reference voltage.
+  fn to_millivolts(&self, value: u16, vref_mv: u32) -> u32 {
+    to_millivolts(value, self.resolution(), vref_mv)
+  }
+}
@@ -0,0 +1,236 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/*!
+Input capture interface.
+
+Capture channels are MCU-specific structs that implement the `InputCapture`
+trait. They timestamp the edges of a signal with a hardware timer, and turn
+them into a `Measurement` of the period and high time of the signal.
+
+Edges are captured from the timer interrupt, so the `isr_handler` of the driver
+must be called from it. The latest measurement can then be polled, or delivered
+to a callback as soon as each period completes.
+*/
+
+use core::option::Option::{self, Some, None};
+
+/// Timing of one period of a signal, in timer ticks.
+#[derive(PartialEq, Clone, Copy)]
+pub struct Measurement {
+  /// Time between two rising edges.
+  pub period: u32,
+  /// Time between the first rising edge and the falling edge that follows.
+  pub high: u32,
+}
+
+impl Measurement {
+  /// Returns the duty cycle, between 0 and 1.
+  pub fn duty_cycle(&self) -> f32 {
+    if self.period == 0 {
+      0.0
+    } else {
+      self.high as f32 / self.period as f32
+    }
+  }
+}
+
+/// Measurement callback, called from the timer ISR.
+pub type Callback = fn(Measurement);
+
+/// Input capture trait.
+pub trait InputCapture {
+  /// Returns the frequency of the timer ticks.
+  fn tick_hz(&self) -> u32;
+
+  /// Returns the last complete measurement, or `None` if no full period was
+  /// captured yet.
+  fn measurement(&self) -> Option<Measurement>;
+
+  /// Sets a callback to be run with each new measurement.
+  fn set_callback(&self, callback: Option<Callback>);
+
+  /// Returns the last measured frequency in Hz, or `None`.
+  fn frequency_hz(&self) -> Option<u32> {
+    match self.measurement() {
+      Some(m) if m.period != 0 => Some(self.tick_hz() / m.period),
+      _ => None,
+    }
+  }
+
+  /// Returns the last measured period in microseconds, or `None`.
+  fn period_us(&self) -> Option<u32> {
+    self.measurement().map(|m| self.ticks_to_us(m.period))
+  }
+
+  /// Returns the last measured pulse width in microseconds, or `None`.
+  fn pulse_width_us(&self) -> Option<u32> {
+    self.measurement().map(|m| self.ticks_to_us(m.high))
+  }
+
+  /// Converts a number of timer ticks to microseconds.
+  ///
+  /// This is exact for tick frequencies that are multiples or divisors of
+  /// 1MHz.
+  fn ticks_to_us(&self, ticks: u32) -> u32 {
+    let tick_hz = self.tick_hz();
+    if tick_hz >= 1_000_000 {
+      ticks / (tick_hz / 1_000_000)
+    } else {
+      ticks * (1_000_000 / tick_hz)
+    }
+  }
+}
+
+/// Edge bookkeeping shared by the capture drivers.
+///
+/// Drivers feed it the captured counter values of alternating rising and
+/// falling edges. Counters narrower than 32 bits are handled by masking the
+/// differences, so a signal period must be shorter than the counter overflow.
+#[derive(Clone, Copy)]
+pub struct EdgeTracker {
+  mask: u32,
+  last_rise: Option<u32>,
+  high: Option<u32>,
+  measurement: Option<Measurement>,
+  callback: Option<Callback>,
+}
+
+impl EdgeTracker {
+  /// Creates a tracker for a counter whose maximum value is `mask`, which
+  /// must be a power of two minus one.
+  pub const fn new(mask: u32) -> EdgeTracker {
+    EdgeTracker {
+      mask: mask,
+      last_rise: None,
+      high: None,
+      measurement: None,
+      callback: None,
+    }
+  }
+
+  /// Sets the callback, keeping the measurements.
+  pub fn set_callback(&mut self, callback: Option<Callback>) {
+    self.callback = callback;
+  }
+
+  /// Returns the last complete measurement.
+  pub fn measurement(&self) -> Option<Measurement> {
+    self.measurement
+  }
+
+  /// Records a rising edge captured at `count`.
+  ///
+  /// Returns the callback to run with the new measurement, if any. It's
+  /// returned instead of called so that drivers can run it outside of their
+  /// critical section.
+  pub fn rising(&mut self, count: u32) -> Option<(Callback, Measurement)> {
+    let completed = match (self.last_rise, self.high) {
+      (Some(rise), Some(high)) => Some(Measurement {
+        period: count.wrapping_sub(rise) & self.mask,
+        high: high,
+      }),
+      _ => None,
+    };
+    self.last_rise = Some(count);
+    self.high = None;
+
+    match completed {
+      Some(m) => {
+        self.measurement = Some(m);
+        self.callback.map(|callback| (callback, m))
+      },
+      None => None,
+    }
+  }
+
+  /// Records a falling edge captured at `count`.
+  pub fn falling(&mut self, count: u32) {
+    match self.last_rise {
+      Some(rise) => self.high = Some(count.wrapping_sub(rise) & self.mask),
+      None => (),
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use core::option::Option::{Some, None};
+  use super::{EdgeTracker, Measurement};
+
+  fn ignore(_: Measurement) {}
+
+  #[test]
+  fn measures_period_and_high() {
+    let mut tracker = EdgeTracker::new(0xffff);
+    assert!(tracker.rising(100).is_none());
+    tracker.falling(130);
+    assert!(tracker.rising(200).is_none());
+    assert!(tracker.measurement() ==
+            Some(Measurement { period: 100, high: 30 }));
+  }
+
+  #[test]
+  fn needs_a_falling_edge_for_a_measurement() {
+    let mut tracker = EdgeTracker::new(0xffff);
+    tracker.falling(50);
+    tracker.rising(100);
+    tracker.rising(200);
+    assert!(tracker.measurement() == None);
+  }
+
+  #[test]
+  fn handles_counter_wraparound() {
+    let mut tracker = EdgeTracker::new(0xffff);
+    tracker.rising(0xfff0);
+    tracker.falling(0x0010);
+    tracker.rising(0x0070);
+    assert!(tracker.measurement() ==
+            Some(Measurement { period: 0x80, high: 0x20 }));
+
+    tracker.falling(0xfff0);
+    tracker.rising(0x0060);
+    assert!(tracker.measurement() ==
+            Some(Measurement { period: 0xfff0, high: 0xff80 }));
+  }
+
+  #[test]
+  fn handles_32_bit_counter_wraparound() {
+    let mut tracker = EdgeTracker::new(0xffffffff);
+    tracker.rising(0xffffff00);
+    tracker.falling(0x40);
+    tracker.rising(0x100);
+    assert!(tracker.measurement() ==
+            Some(Measurement { period: 0x200, high: 0x140 }));
+  }
+
+  #[test]
+  fn returns_the_callback_with_each_measurement() {
+    let mut tracker = EdgeTracker::new(0xffff);
+    tracker.set_callback(Some(ignore));
+    assert!(tracker.rising(0).is_none());
+    tracker.falling(10);
+    match tracker.rising(40) {
+      Some((_, m)) => assert!(m == Measurement { period: 40, high: 10 }),
+      None => assert!(false),
+    }
+  }
+
+  #[test]
+  fn computes_duty_cycle() {
+    assert!(Measurement { period: 40, high: 10 }.duty_cycle() == 0.25);
+    assert!(Measurement { period: 0, high: 0 }.duty_cycle() == 0.0);
+  }
+}
@@ -0,0 +1,175 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Fault reporting.
+//!
+//! Faults and unhandled exceptions end up in `fault_handler`, which prints a
+//! report of the fault status registers and of the stacked exception frame to
+//! the output set with `set_output`, then resets the MCU. If a debugger is
+//! attached, it breaks into it before resetting.
+
+use core::intrinsics::volatile_load;
+use core::marker::PhantomData;
+use core::mem::transmute;
+use core::ops::Drop;
+use core::option::Option::{self, Some, None};
+
+use drivers::chario::CharIO;
+use hal::cortex_common::scb;
+
+/// Debug halting control and status register, bit 0 is set while a debugger
+/// is connected.
+const DHCSR: *const u32 = 0xE000_EDF0 as *const u32;
+
+/// Registers stacked by the core on exception entry.
+#[repr(C)]
+#[allow(missing_docs)]
+pub struct ExceptionFrame {
+  pub r0: u32,
+  pub r1: u32,
+  pub r2: u32,
+  pub r3: u32,
+  pub r12: u32,
+  pub lr: u32,
+  pub pc: u32,
+  pub xpsr: u32,
+}
+
+/// Fault status bits of CFSR, with MemManage, BusFault and UsageFault status
+/// in its bytes 0, 1 and 2-3.
+const CFSR_BITS: [(u32, &'static str); 17] = [
+  (0,  "IACCVIOL: instruction access violation"),
+  (1,  "DACCVIOL: data access violation"),
+  (3,  "MUNSTKERR: MemManage fault on unstacking"),
+  (4,  "MSTKERR: MemManage fault on stacking"),
+  (5,  "MLSPERR: MemManage fault on FP lazy state preservation"),
+  (8,  "IBUSERR: instruction bus error"),
+  (9,  "PRECISERR: precise data bus error"),
+  (10, "IMPRECISERR: imprecise data bus error"),
+  (11, "UNSTKERR: BusFault on unstacking"),
+  (12, "STKERR: BusFault on stacking"),
+  (13, "LSPERR: BusFault on FP lazy state preservation"),
+  (16, "UNDEFINSTR: undefined instruction"),
+  (17, "INVSTATE: invalid EPSR state"),
+  (18, "INVPC: invalid PC load on exception return"),
+  (19, "NOCP: no coprocessor"),
+  (24, "UNALIGNED: unaligned access"),
+  (25, "DIVBYZERO: divide by zero"),
+];
+
+const CFSR_MMARVALID: u32 = 1 << 7;
+const CFSR_BFARVALID: u32 = 1 << 15;
+
+static mut OUTPUT: Option<*const CharIO> = None;
+
+/// A token to ensure the fault output doesn't outlive its backend.
+#[must_use]
+pub struct Token<'a> {
+  marker: PhantomData<&'a CharIO>,
+}
+
+impl<'a> Drop for Token<'a> {
+  fn drop(&mut self) {
+    unsafe {
+      OUTPUT = None;
+    }
+  }
+}
+
+/// Sets the output of fault reports.
+///
+/// Faults are reported until the returned token is dropped.
+pub fn set_output<'a>(output: &'a CharIO) -> Token<'a> {
+  unsafe {
+    OUTPUT = Some(transmute(output));
+  }
+  Token { marker: PhantomData }
+}
+
+/// Reports the fault and resets, called from the default exception handler
+/// with the exception frame of the faulting code.
+#[no_mangle]
+pub unsafe extern fn fault_handler(frame: &ExceptionFrame) -> ! {
+  match OUTPUT {
+    Some(output) => report(&*output, frame),
+    None => (),
+  }
+
+  if volatile_load(DHCSR) & 1 != 0 {
+    asm!("bkpt" :::: "volatile");
+  }
+  scb::reset();
+}
+
+fn report(out: &CharIO, frame: &ExceptionFrame) {
+  out.puts("\n*** ");
+  match scb::active_exception() {
+    2 => out.puts("NMI"),
+    3 => out.puts("HardFault"),
+    4 => out.puts("MemManage fault"),
+    5 => out.puts("BusFault"),
+    6 => out.puts("UsageFault"),
+    n => {
+      out.puts("unhandled exception ");
+      out.puti(n);
+    },
+  }
+  out.puts("\n");
+
+  let hfsr = scb::hfsr();
+  if hfsr.vecttbl() {
+    out.puts("  VECTTBL: BusFault on vector table read\n");
+  }
+  if hfsr.forced() {
+    out.puts("  FORCED: escalated from a configurable fault\n");
+  }
+  if hfsr.debugevt() {
+    out.puts("  DEBUGEVT: debug event\n");
+  }
+
+  let cfsr = scb::cfsr();
+  let status = cfsr.memmanage() | cfsr.busfault() << 8 |
+      cfsr.usagefault() << 16;
+  for &(bit, description) in CFSR_BITS.iter() {
+    if status & (1 << bit) != 0 {
+      out.puts("  ");
+      out.puts(description);
+      out.puts("\n");
+    }
+  }
+  if status & CFSR_MMARVALID != 0 {
+    print_register(out, "MMFAR", scb::mmfar());
+  }
+  if status & CFSR_BFARVALID != 0 {
+    print_register(out, "BFAR", scb::bfar());
+  }
+
+  print_register(out, "r0", frame.r0);
+  print_register(out, "r1", frame.r1);
+  print_register(out, "r2", frame.r2);
+  print_register(out, "r3", frame.r3);
+  print_register(out, "r12", frame.r12);
+  print_register(out, "lr", frame.lr);
+  print_register(out, "pc", frame.pc);
+  print_register(out, "xpsr", frame.xpsr);
+}
+
+fn print_register(out: &CharIO, name: &str, value: u32) {
+  out.puts("  ");
+  out.puts(name);
+  out.puts(" = 0x");
+  out.puth(value);
+  out.puts("\n");
+}
@@ -17,8 +17,10 @@
 Common definitions to all ARM Cortex M* family members
 */
 
+pub mod fault;
 pub mod systick;
 pub mod mpu;
 pub mod nvic;
 pub mod scb;
 pub mod irq;
+pub mod vector_table;
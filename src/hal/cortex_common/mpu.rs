@@ -18,13 +18,191 @@
 //! MPU memory location is 0xE000_ED90.
 //  Link: http://infocenter.arm.com/help/topic/com.arm.doc.dui0552a/BIHJJABA.html
 
-// TODO(bharrisau): Remove dead_code when MPU is implemented.
-#[allow(dead_code)]
+use core::intrinsics::abort;
+
+use util::support::{dsb, isb};
+
 #[inline(always)]
 fn get_reg() -> &'static reg::MPU {
   unsafe { &*(0xE000_ED90 as *mut reg::MPU) }
 }
 
+/// Region size, regions must be aligned to their size.
+#[allow(missing_docs)]
+#[derive(Clone, Copy)]
+pub enum Size {
+  Size32B = 4,
+  Size64B,
+  Size128B,
+  Size256B,
+  Size512B,
+  Size1K,
+  Size2K,
+  Size4K,
+  Size8K,
+  Size16K,
+  Size32K,
+  Size64K,
+  Size128K,
+  Size256K,
+  Size512K,
+  Size1M,
+  Size2M,
+  Size4M,
+  Size8M,
+  Size16M,
+  Size32M,
+  Size64M,
+  Size128M,
+  Size256M,
+  Size512M,
+  Size1G,
+  Size2G,
+  Size4G,
+}
+
+/// Access permissions of a region.
+#[derive(Clone, Copy)]
+pub enum Access {
+  /// Any access faults.
+  NoAccess = 0,
+  /// Read/write in privileged mode, no unprivileged access.
+  PrivilegedOnly = 1,
+  /// Read/write in privileged mode, read-only in unprivileged mode.
+  UnprivilegedReadOnly = 2,
+  /// Read/write in any mode.
+  FullAccess = 3,
+  /// Read-only in privileged mode, no unprivileged access.
+  PrivilegedReadOnly = 5,
+  /// Read-only in any mode.
+  ReadOnly = 6,
+}
+
+/// Memory type and cache policy of a region.
+#[derive(Clone, Copy)]
+pub enum MemoryType {
+  /// Strongly ordered, accesses complete in program order.
+  StronglyOrdered,
+  /// Device memory, for peripherals.
+  Device,
+  /// Normal memory, write-through, no write allocate.
+  WriteThrough,
+  /// Normal memory, write-back, no write allocate.
+  WriteBack,
+  /// Normal memory, not cacheable.
+  NonCacheable,
+  /// Normal memory, write-back, write and read allocate.
+  WriteBackAllocate,
+}
+
+impl MemoryType {
+  /// Returns the TEX, C and B bits of the memory type.
+  fn encoding(self) -> (u32, bool, bool) {
+    match self {
+      MemoryType::StronglyOrdered   => (0, false, false),
+      MemoryType::Device            => (0, false, true),
+      MemoryType::WriteThrough      => (0, true, false),
+      MemoryType::WriteBack         => (0, true, true),
+      MemoryType::NonCacheable      => (1, false, false),
+      MemoryType::WriteBackAllocate => (1, true, true),
+    }
+  }
+}
+
+/// MPU region configuration.
+#[derive(Clone, Copy)]
+pub struct Region {
+  /// Base address, aligned to the region size.
+  pub base: u32,
+  /// Region size.
+  pub size: Size,
+  /// Access permissions.
+  pub access: Access,
+  /// Memory type.
+  pub memory: MemoryType,
+  /// Whether the region is shared between bus masters.
+  pub shareable: bool,
+  /// Whether instructions can be fetched from the region.
+  pub executable: bool,
+  /// Disabled subregions, bit n disables the n-th eighth of the region.
+  ///
+  /// Regions smaller than 256 bytes have no subregions.
+  pub disabled_subregions: u8,
+}
+
+/// Returns the number of regions supported by the MPU, zero if there is no
+/// MPU.
+pub fn regions() -> u32 {
+  get_reg().mpu_type.dregion()
+}
+
+/// Returns true if the core has an MPU.
+pub fn present() -> bool {
+  regions() != 0
+}
+
+/// Programs and enables region `number`.
+///
+/// Regions with higher numbers take precedence where regions overlap.
+pub fn set_region(number: u32, region: &Region) {
+  let size = region.size as u32;
+  let align_mask = 0xffff_ffff >> (31 - size);
+  if number >= regions() || region.base & align_mask != 0 ||
+      (region.disabled_subregions != 0 && size < Size::Size256B as u32) {
+    unsafe { abort() };
+  }
+
+  let (tex, c, b) = region.memory.encoding();
+  let reg = get_reg();
+  dsb();
+  reg.rnr.set_region(number);
+  reg.rasr.set_enable(false);
+  reg.rbar.ignoring_state().set_addr(region.base >> 5);
+  reg.rasr.ignoring_state()
+      .set_size(size)
+      .set_srd(region.disabled_subregions as u32)
+      .set_b(b)
+      .set_c(c)
+      .set_s(region.shareable)
+      .set_tex(tex)
+      .set_ap(region.access as u32)
+      .set_xn(!region.executable)
+      .set_enable(true);
+  dsb();
+  isb();
+}
+
+/// Disables region `number`.
+pub fn disable_region(number: u32) {
+  let reg = get_reg();
+  dsb();
+  reg.rnr.set_region(number);
+  reg.rasr.set_enable(false);
+  dsb();
+  isb();
+}
+
+/// Enables the MPU.
+///
+/// If `background` is true, privileged accesses outside of any region use the
+/// default memory map, otherwise they fault.
+pub fn enable(background: bool) {
+  dsb();
+  get_reg().ctrl.ignoring_state()
+      .set_privdefena(background)
+      .set_enable(true);
+  dsb();
+  isb();
+}
+
+/// Disables the MPU.
+pub fn disable() {
+  dsb();
+  get_reg().ctrl.set_enable(false);
+  dsb();
+  isb();
+}
+
 mod reg {
   use volatile_cell::VolatileCell;
   use core::ops::Drop;
@@ -23,51 +23,98 @@ fn get_reg() -> &'static reg::NVIC {
   unsafe { &*(0xE000_E000 as *mut reg::NVIC) }
 }
 
+/// An interrupt line, numbered from the first entry of the vector table after
+/// the core exceptions.
+///
+/// Each MCU provides an `Irq` enum of its interrupt lines. Raw numbers can be
+/// used for MCUs that lack one.
+pub trait Interrupt {
+  /// Returns the interrupt number.
+  fn number(&self) -> usize;
+}
+
+impl Interrupt for usize {
+  fn number(&self) -> usize {
+    *self
+  }
+}
+
+/// Returns the number of interrupt lines the NVIC supports, a multiple of 32.
+pub fn irq_lines() -> usize {
+  (get_reg().ictr.intlinesnum() as usize + 1) * 32
+}
+
 /// Enable an interrupt
-pub fn enable_irq(irqn: usize) {
+pub fn enable_irq<I: Interrupt>(irq: I) {
+  let irqn = irq.number();
   get_reg().iser[irqn / 32].clear_iser(irqn % 32);
 }
 
 /// Disable an interrupt
-pub fn disable_irq(irqn: usize) {
+pub fn disable_irq<I: Interrupt>(irq: I) {
+  let irqn = irq.number();
   get_reg().icer[irqn / 32].clear_icer(irqn % 32);
 }
 
 /// Return whether the given interrupt is enabled
-pub fn is_enabled(irqn: usize) -> bool {
+pub fn is_enabled<I: Interrupt>(irq: I) -> bool {
+  let irqn = irq.number();
   get_reg().iser[irqn / 32].iser(irqn % 32)
 }
 
+/// Set the pending flag for the given interrupt
+pub fn set_pending<I: Interrupt>(irq: I) {
+  let irqn = irq.number();
+  get_reg().ispr[irqn / 32].clear_ispr(irqn % 32);
+}
+
 /// Clear the pending flag for the given interrupt
-pub fn clear_pending(irqn: usize) {
+pub fn clear_pending<I: Interrupt>(irq: I) {
+  let irqn = irq.number();
   get_reg().icpr[irqn / 32].clear_icpr(irqn % 32);
 }
 
 /// Return whether the given interrupt is pending
-pub fn is_pending(irqn: usize) -> bool {
+pub fn is_pending<I: Interrupt>(irq: I) -> bool {
+  let irqn = irq.number();
   get_reg().ispr[irqn / 32].ispr(irqn % 32)
 }
 
 /// Return whether the given interrupt is active
-pub fn is_active(irqn: usize) -> bool {
+pub fn is_active<I: Interrupt>(irq: I) -> bool {
+  let irqn = irq.number();
   get_reg().iabr[irqn / 32].iabr(irqn % 32)
 }
 
 /// Set the priority for the given interrupt
-pub fn set_priority(irqn: usize, prio: u8) {
+///
+/// Lower values are more urgent. MCUs implement only the upper bits of the
+/// priority, the meaning of which is set by `scb::set_priority_grouping`.
+pub fn set_priority<I: Interrupt>(irq: I, prio: u8) {
+  let irqn = irq.number();
   get_reg().ipr[irqn / 4].set_ipr(irqn % 4, prio as u32);
 }
 
 /// Return the priority for the given interrupt
-pub fn get_priority(irqn: usize) -> u8 {
+pub fn get_priority<I: Interrupt>(irq: I) -> u8 {
+  let irqn = irq.number();
   get_reg().ipr[irqn / 4].ipr(irqn % 4) as u8
 }
 
+/// Triggers the given interrupt from software, through the software trigger
+/// interrupt register.
+pub fn trigger<I: Interrupt>(irq: I) {
+  get_reg().stir.set_intid(irq.number() as u32);
+}
+
 mod reg {
   use volatile_cell::VolatileCell;
   use core::ops::Drop;
 
   ioregs!(NVIC = {
+    0x4       => reg32 ictr {         //! Interrupt controller type register
+      0..3    => intlinesnum: ro,
+    }
     0x100     => reg32 iser[8] {      //! Interrupt set enable register
       0..31   => iser[32]: set_to_clear,
     }
@@ -83,11 +130,11 @@ mod reg {
     0x300     => reg32 iabr[8] {      //! Interrupt active bit register
       0..31   => iabr[32]: ro,
     }
-    0x400     => reg32 ipr[8] {       //! Interrupt priority register
+    0x400     => reg32 ipr[60] {      //! Interrupt priority register
       0..31   => ipr[4],
     }
-    0xF00     => reg32 stir[8] {      //! Software triggered interrupt register
-      0..8    => stir,
+    0xF00     => reg32 stir {         //! Software triggered interrupt register
+      0..8    => intid: wo,
     }
   });
 }
@@ -39,6 +39,75 @@ pub fn set_pendsv(val: bool) {
   }
 }
 
+/// Returns the number of the active exception, 0 in thread mode.
+pub fn active_exception() -> u32 {
+  get_reg().icsr.vectactive()
+}
+
+/// Returns the configurable fault status register.
+pub fn cfsr() -> reg::SCB_cfsr_Get {
+  get_reg().cfsr.get()
+}
+
+/// Returns the HardFault status register.
+pub fn hfsr() -> reg::SCB_hfsr_Get {
+  get_reg().hfsr.get()
+}
+
+/// Returns the address of the last MemManage fault, valid if flagged by CFSR.
+pub fn mmfar() -> u32 {
+  get_reg().mmfar.address()
+}
+
+/// Returns the address of the last precise BusFault, valid if flagged by
+/// CFSR.
+pub fn bfar() -> u32 {
+  get_reg().bfar.address()
+}
+
+/// Returns the address of the vector table.
+pub fn vector_table() -> u32 {
+  get_reg().vtor.tbloff() << 7
+}
+
+/// Relocates the vector table to `addr`, which must be aligned to the table
+/// size rounded up to a power of two, and to at least 128 bytes.
+pub fn set_vector_table(addr: u32) {
+  get_reg().vtor.set_tbloff(addr >> 7);
+}
+
+/// Sets the priority grouping.
+///
+/// The lowest `prigroup + 1` bits of interrupt priorities select the
+/// subpriority, which orders pending interrupts but doesn't allow
+/// preemption. The remaining bits select the preemption priority.
+pub fn set_priority_grouping(prigroup: u32) {
+  get_reg().aircr.set_vectkey(0x05FA).set_prigroup(prigroup);
+}
+
+/// Returns the priority grouping.
+pub fn priority_grouping() -> u32 {
+  get_reg().aircr.prigroup()
+}
+
+/// Requests a system reset.
+pub fn reset() -> ! {
+  get_reg().aircr.set_vectkey(0x05FA).set_sysresetreq(true);
+  // The reset isn't immediate.
+  loop {}
+}
+
+/// Selects deep sleep instead of sleep as the low power mode entered by WFI.
+pub fn set_sleepdeep(val: bool) {
+  get_reg().scr.set_sleepdeep(val);
+}
+
+/// Sets whether the core goes back to sleep after returning from an ISR to
+/// thread mode.
+pub fn set_sleeponexit(val: bool) {
+  get_reg().scr.set_sleeponexit(val);
+}
+
 mod reg {
   use volatile_cell::VolatileCell;
   use core::ops::Drop;
@@ -0,0 +1,153 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Vector table in RAM.
+//!
+//! Interrupt handlers are normally bound at link time, by defining symbols
+//! like `isr_timer_0` that override the weak defaults of the vector table in
+//! flash. This module copies the vector table to RAM and points VTOR at the
+//! copy, so that handlers can be installed and removed at runtime, letting
+//! drivers own their interrupts. Using an interrupt past the lines the NVIC
+//! implements aborts, while lines past the linked vector table start out with
+//! the default fault handler.
+//!
+//! The RAM table lives in the `.ram_vectors` section and is dropped by the
+//! linker if this module isn't used. Closures take an additional slot table,
+//! which is dropped as well if `set_closure` isn't used.
+
+use core::cmp::min;
+use core::intrinsics::abort;
+use core::option::Option::{self, Some, None};
+
+use hal::cortex_common::irq::NoInterrupts;
+use hal::cortex_common::nvic::{self, Interrupt};
+use hal::cortex_common::scb;
+
+extern {
+  fn __isr_vector_start();
+  fn __isr_vector_end();
+  fn isr_hardfault();
+}
+
+/// An interrupt handler, as stored in the vector table.
+pub type Handler = unsafe extern fn();
+
+/// Number of core exceptions preceding the interrupts in the vector table.
+const EXCEPTIONS: usize = 16;
+
+/// Most interrupts the NVIC can have.
+const MAX_IRQS: usize = 240;
+
+/// The RAM vector table. Its 1KiB size is a power of two, the linker script
+/// aligns the section to it as VTOR requires.
+#[link_section=".ram_vectors"]
+static mut RAM_VECTORS: [u32; EXCEPTIONS + MAX_IRQS] =
+    [0; EXCEPTIONS + MAX_IRQS];
+
+/// The vector table in use before `relocate`, to restore handlers from.
+static mut ORIGINAL_VECTORS: *const u32 = 0 as *const u32;
+
+static mut CLOSURES: [Option<&'static Fn()>; MAX_IRQS] = [None; MAX_IRQS];
+
+/// Copies the vector table to RAM and makes the core use the copy.
+///
+/// Installing a handler relocates the table if needed, but the copy is best
+/// done at startup, before any interrupt is enabled. Does nothing if the
+/// table is already in RAM.
+pub fn relocate() {
+  let _crit = NoInterrupts::new();
+  unsafe {
+    let ram = RAM_VECTORS.as_ptr() as u32;
+    let current = scb::vector_table();
+    if current == ram {
+      return;
+    }
+    ORIGINAL_VECTORS = current as *const u32;
+
+    let count = EXCEPTIONS + min(nvic::irq_lines(), MAX_IRQS);
+    for i in 0..count {
+      RAM_VECTORS[i] = original_vector(i);
+    }
+    scb::set_vector_table(ram);
+  }
+}
+
+/// Installs `handler` for `irq`.
+pub fn set_handler<I: Interrupt>(irq: I, handler: Handler) {
+  set_vector(checked_irqn(irq), handler as u32);
+}
+
+/// Installs a closure as the handler of `irq`.
+///
+/// The closure is called from a dispatcher that looks up the active
+/// interrupt, which adds a few cycles of latency over `set_handler`.
+pub fn set_closure<I: Interrupt>(irq: I, closure: &'static Fn()) {
+  let irqn = checked_irqn(irq);
+  {
+    let _crit = NoInterrupts::new();
+    unsafe { CLOSURES[irqn] = Some(closure) };
+  }
+  set_vector(irqn, dispatch_closure as u32);
+}
+
+/// Removes the handler installed for `irq`, restoring the one linked in the
+/// original vector table.
+pub fn remove_handler<I: Interrupt>(irq: I) {
+  let irqn = checked_irqn(irq);
+  relocate();
+  let original = unsafe { original_vector(EXCEPTIONS + irqn) };
+  set_vector(irqn, original);
+
+  // The dispatcher isn't installed anymore, so the closure can go.
+  let _crit = NoInterrupts::new();
+  unsafe { CLOSURES[irqn] = None };
+}
+
+/// Returns the number of `irq`, aborting if it's past the vector table.
+fn checked_irqn<I: Interrupt>(irq: I) -> usize {
+  let irqn = irq.number();
+  if irqn >= min(nvic::irq_lines(), MAX_IRQS) {
+    unsafe { abort() };
+  }
+  irqn
+}
+
+/// Returns entry `index` of the original vector table, or the default fault
+/// handler if it's past the linked table.
+///
+/// `nvic::irq_lines()` is rounded up to a multiple of 32, so it can go past
+/// the vectors the MCU actually has.
+unsafe fn original_vector(index: usize) -> u32 {
+  let linked = (__isr_vector_end as usize - __isr_vector_start as usize) / 4;
+  if index < linked {
+    *ORIGINAL_VECTORS.offset(index as isize)
+  } else {
+    isr_hardfault as u32
+  }
+}
+
+fn set_vector(irqn: usize, vector: u32) {
+  relocate();
+  let _crit = NoInterrupts::new();
+  unsafe { RAM_VECTORS[EXCEPTIONS + irqn] = vector };
+}
+
+unsafe extern fn dispatch_closure() {
+  let irqn = scb::active_exception() as usize - EXCEPTIONS;
+  match CLOSURES[irqn] {
+    Some(closure) => closure(),
+    None => abort(),
+  }
+}
@@ -89,12 +89,11 @@ impl SavedState {
   }
 }
 
-// TODO(farcaller): this should actually kill the task.
 // TODO(bgamari): It should also unlock anything the task holds
-/// Default handler for task that tries to return.
+/// Default handler for task that tries to return, kills the task.
 #[cfg(target_os = "none")]
 unsafe fn task_finished() {
-  core::intrinsics::breakpoint();
+  ::os::task::exit();
 }
 
 #[cfg(not(target_os = "none"))]
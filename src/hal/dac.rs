@@ -0,0 +1,141 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/*!
+Digital to analog converter interface.
+
+DACs are MCU-specific structs that implement the `Dac` trait. The output is
+set either as a raw code or in millivolts.
+
+Drivers can also play a `Waveform`, a buffer of raw codes output in a loop at
+the rate of a hardware timer. Samples are written from the timer interrupt, so
+the `isr_handler` of the driver must be called from it.
+*/
+
+use core::intrinsics::abort;
+
+/// DAC trait.
+pub trait Dac {
+  /// Returns the resolution of the DAC in bits.
+  fn bits(&self) -> u32;
+
+  /// Sets the output to a raw code.
+  ///
+  /// Codes above the maximum for the resolution are clamped.
+  fn set_raw(&self, value: u16);
+
+  /// Returns the raw code being output.
+  fn raw(&self) -> u16;
+
+  /// Sets the output in millivolts, given the reference voltage.
+  ///
+  /// Voltages above the reference are clamped.
+  fn set_millivolts(&self, mv: u32, vref_mv: u32) {
+    let max = (1 << self.bits()) - 1;
+    let value = if mv >= vref_mv {
+      max
+    } else {
+      mv * max / vref_mv
+    };
+    self.set_raw(value as u16);
+  }
+}
+
+/// A buffer of raw codes played in a loop.
+#[derive(Clone, Copy)]
+pub struct Waveform {
+  samples: &'static [u16],
+  position: usize,
+}
+
+impl Waveform {
+  /// Creates a waveform starting at the first of `samples`.
+  ///
+  /// `samples` can't be empty.
+  pub fn new(samples: &'static [u16]) -> Waveform {
+    if samples.len() == 0 {
+      unsafe { abort() };
+    }
+    Waveform {
+      samples: samples,
+      position: 0,
+    }
+  }
+
+  /// Returns the next sample, going back to the start after the last one.
+  pub fn next_sample(&mut self) -> u16 {
+    let sample = self.samples[self.position];
+    self.position += 1;
+    if self.position == self.samples.len() {
+      self.position = 0;
+    }
+    sample
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use core::cell::Cell;
+  use super::{Dac, Waveform};
+
+  struct TestDac {
+    value: Cell<u16>,
+  }
+
+  impl Dac for TestDac {
+    fn bits(&self) -> u32 {
+      12
+    }
+
+    fn set_raw(&self, value: u16) {
+      self.value.set(value);
+    }
+
+    fn raw(&self) -> u16 {
+      self.value.get()
+    }
+  }
+
+  static SAMPLES: [u16; 3] = [10, 20, 30];
+
+  #[test]
+  fn waveform_loops_over_samples() {
+    let mut waveform = Waveform::new(&SAMPLES);
+    for _ in 0..3 {
+      assert!(waveform.next_sample() == 10);
+      assert!(waveform.next_sample() == 20);
+      assert!(waveform.next_sample() == 30);
+    }
+  }
+
+  #[test]
+  fn single_sample_waveform_repeats() {
+    static SAMPLE: [u16; 1] = [42];
+    let mut waveform = Waveform::new(&SAMPLE);
+    assert!(waveform.next_sample() == 42);
+    assert!(waveform.next_sample() == 42);
+  }
+
+  #[test]
+  fn scales_and_clamps_millivolts() {
+    let dac = TestDac { value: Cell::new(0) };
+    dac.set_millivolts(1650, 3300);
+    assert!(dac.raw() == 2047);
+    dac.set_millivolts(3300, 3300);
+    assert!(dac.raw() == 4095);
+    dac.set_millivolts(5000, 3300);
+    assert!(dac.raw() == 4095);
+  }
+}
@@ -0,0 +1,62 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/*!
+Direct memory access interface.
+
+DMA channels are MCU-specific structs that implement the `Channel` trait. A
+channel is claimed from the controller before use and is released when it is
+dropped, so two drivers can never program the same channel.
+
+Transfers are started by peripheral drivers (SPI, UART), as they know the
+request line and data register of their peripheral. Completion can be polled
+with `Channel::status` and `Channel::wait`, or reported through a callback
+that is invoked from the DMA interrupt.
+*/
+
+use core::option::Option::{self, Some, None};
+use core::result::Result;
+
+/// DMA transfer errors.
+#[derive(PartialEq, Clone, Copy)]
+pub enum Error {
+  /// The controller hit a bus error while accessing memory or peripheral.
+  Bus,
+}
+
+/// Transfer completion callback, called from the DMA ISR.
+pub type Callback = fn(Result<(), Error>);
+
+/// DMA channel trait.
+pub trait Channel {
+  /// Returns the result of the last transfer, or `None` while it's running.
+  fn status(&self) -> Option<Result<(), Error>>;
+
+  /// Sets a callback to be run when a transfer completes.
+  ///
+  /// Passing `None` disables the channel interrupts; the transfer status can
+  /// then only be polled.
+  fn set_callback(&self, callback: Option<Callback>);
+
+  /// Busy-waits for the current transfer to complete.
+  fn wait(&self) -> Result<(), Error> {
+    loop {
+      match self.status() {
+        Some(result) => return result,
+        None => (),
+      }
+    }
+  }
+}
@@ -0,0 +1,125 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/*!
+Flash programming interface.
+
+Flash controllers are MCU-specific structs that implement the `Flash` trait.
+Flash is erased a sector at a time, which sets all of its bits, and
+programming can only clear bits, so a word must be erased before it can be
+programmed with a different value.
+
+The linker scripts of the supported MCUs reserve a `user_data` region at the
+end of flash for calibration data and settings, which `user_data()` returns.
+It spans the last two sectors.
+Code runs from flash, so MCUs stall or disable interrupts while flash is busy.
+*/
+
+use core::intrinsics::volatile_load;
+use core::result::Result::{self, Ok, Err};
+
+extern {
+  fn __user_data_start();
+  fn __user_data_end();
+}
+
+/// Flash operation errors.
+#[derive(PartialEq, Clone, Copy)]
+pub enum Error {
+  /// The address or sector is outside of flash.
+  OutOfRange,
+  /// The address isn't aligned to the programming unit.
+  Unaligned,
+  /// The sector is write protected.
+  WriteProtected,
+  /// The controller reported a failure.
+  Failed,
+  /// Flash contents don't match the expected data.
+  VerifyFailed,
+}
+
+/// A flash sector, the smallest erasable unit. STM32 manuals call them pages
+/// on some parts.
+#[derive(PartialEq, Clone, Copy)]
+pub struct Sector {
+  /// Address of the first byte.
+  pub start: u32,
+  /// Size in bytes.
+  pub size: u32,
+}
+
+impl Sector {
+  /// Returns true if `addr` is in the sector.
+  pub fn contains(&self, addr: u32) -> bool {
+    addr >= self.start && addr - self.start < self.size
+  }
+}
+
+/// Flash trait.
+pub trait Flash {
+  /// Returns the number of sectors.
+  fn sector_count(&self) -> usize;
+
+  /// Returns sector `index`, which must be less than `sector_count()`.
+  fn sector(&self, index: usize) -> Sector;
+
+  /// Erases sector `index`.
+  fn erase(&self, index: usize) -> Result<(), Error>;
+
+  /// Programs the word at `addr`, which must be word-aligned and erased.
+  fn program_word(&self, addr: u32, value: u32) -> Result<(), Error>;
+
+  /// Programs the half-word at `addr`, which must be aligned and erased.
+  fn program_half_word(&self, addr: u32, value: u16) -> Result<(), Error>;
+
+  /// Reads the word at the word-aligned `addr`.
+  fn read_word(&self, addr: u32) -> u32 {
+    unsafe { volatile_load(addr as *const u32) }
+  }
+
+  /// Returns the index of the sector containing `addr`.
+  fn sector_at(&self, addr: u32) -> Result<usize, Error> {
+    for index in 0..self.sector_count() {
+      if self.sector(index).contains(addr) {
+        return Ok(index);
+      }
+    }
+    Err(Error::OutOfRange)
+  }
+
+  /// Programs `data` starting at the word-aligned `addr`, then verifies it.
+  fn program(&self, addr: u32, data: &[u32]) -> Result<(), Error> {
+    for (i, &word) in data.iter().enumerate() {
+      try!(self.program_word(addr + i as u32 * 4, word));
+    }
+    self.verify(addr, data)
+  }
+
+  /// Checks that flash at `addr` holds `data`.
+  fn verify(&self, addr: u32, data: &[u32]) -> Result<(), Error> {
+    for (i, &word) in data.iter().enumerate() {
+      if self.read_word(addr + i as u32 * 4) != word {
+        return Err(Error::VerifyFailed);
+      }
+    }
+    Ok(())
+  }
+}
+
+/// Returns the start and end addresses of the flash region the linker script
+/// reserves for user data.
+pub fn user_data() -> (u32, u32) {
+  (__user_data_start as u32, __user_data_end as u32)
+}
@@ -0,0 +1,61 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/*!
+I2C interface.
+
+I2C peripherals are MCU-specific structs that implement the `I2c` trait in
+master mode. Addresses are always 7-bit, without the R/W bit; the driver
+shifts them into place.
+
+Empty transfers are no-ops that never address the slave, as not all
+controllers can issue an address phase without data. `write_read` with an
+empty `data` or `buffer` is a plain `read` or `write`.
+
+All operations return on the first error. A failed transaction is always
+terminated with a STOP condition, so the bus is left idle for the next one.
+*/
+
+use core::result::Result;
+
+/// I2C transaction errors.
+#[derive(PartialEq, Clone, Copy)]
+pub enum Error {
+  /// Slave did not acknowledge its address.
+  AddressNack,
+  /// Slave did not acknowledge a data byte.
+  DataNack,
+  /// Another master won the bus arbitration.
+  ArbitrationLost,
+  /// Misplaced START or STOP condition was detected on the bus.
+  Bus,
+}
+
+/// I2C master trait.
+pub trait I2c {
+  /// Writes all of `data` to the slave at `address`.
+  fn write(&self, address: u8, data: &[u8]) -> Result<(), Error>;
+
+  /// Fills `buffer` with data read from the slave at `address`.
+  fn read(&self, address: u8, buffer: &mut [u8]) -> Result<(), Error>;
+
+  /// Writes `data` to the slave at `address`, then issues a repeated START
+  /// and fills `buffer` with data read back from the same slave.
+  ///
+  /// This is the usual way to read a register of a sensor, as the bus is not
+  /// released between the two phases.
+  fn write_read(&self, address: u8, data: &[u8], buffer: &mut [u8])
+      -> Result<(), Error>;
+}
@@ -0,0 +1,79 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Generator for the per-MCU interrupt lists.
+
+/// Defines the `Irq` enum, `IRQ_COUNT` and the NVIC vector table of an MCU
+/// from a single list of its interrupt vectors.
+///
+/// The list follows the vector table order. Each entry is `Name => isr_name,`
+/// for a line with a handler, `Name,` for a line without one and `_,` for a
+/// reserved vector. The vector table is only built for the target, where the
+/// linker resolves the handlers.
+macro_rules! irqs {
+  (@parse [$($variant:tt)*] [$($handler:tt)*] [$($vector:tt)*] [$($n:tt)*]) => (
+    /// NVIC interrupt lines.
+    #[allow(missing_docs)]
+    #[derive(Clone, Copy, PartialEq)]
+    pub enum Irq {
+      $($variant)*
+    }
+
+    /// Number of NVIC vectors, reserved ones included.
+    pub const IRQ_COUNT: usize = $($n)*;
+
+    #[cfg(target_os = "none")]
+    extern {
+      $($handler)*
+    }
+
+    /// NVIC part of the vector table.
+    #[cfg(target_os = "none")]
+    #[allow(non_upper_case_globals)]
+    #[link_section=".isr_vector_nvic"]
+    #[no_mangle]
+    pub static NVICVectors:
+        [::core::option::Option<unsafe extern fn()>; IRQ_COUNT] = [
+      $($vector)*
+    ];
+  );
+  (@parse [$($variant:tt)*] [$($handler:tt)*] [$($vector:tt)*] [$($n:tt)*]
+   _, $($rest:tt)*) => (
+    irqs!(@parse [$($variant)*]
+                 [$($handler)*]
+                 [$($vector)* ::core::option::Option::None,]
+                 [$($n)* + 1]
+                 $($rest)*);
+  );
+  (@parse [$($variant:tt)*] [$($handler:tt)*] [$($vector:tt)*] [$($n:tt)*]
+   $name:ident => $isr:ident, $($rest:tt)*) => (
+    irqs!(@parse [$($variant)* $name = $($n)*,]
+                 [$($handler)* fn $isr();]
+                 [$($vector)* ::core::option::Option::Some($isr),]
+                 [$($n)* + 1]
+                 $($rest)*);
+  );
+  (@parse [$($variant:tt)*] [$($handler:tt)*] [$($vector:tt)*] [$($n:tt)*]
+   $name:ident, $($rest:tt)*) => (
+    irqs!(@parse [$($variant)* $name = $($n)*,]
+                 [$($handler)*]
+                 [$($vector)* ::core::option::Option::None,]
+                 [$($n)* + 1]
+                 $($rest)*);
+  );
+  ($($entry:tt)*) => (
+    irqs!(@parse [] [] [] [0] $($entry)*);
+  );
+}
@@ -0,0 +1,294 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/*!
+Analog to digital converter.
+
+The ADC runs from its internal asynchronous clock, so it doesn't depend on the
+bus clock configuration. It's calibrated when created, as required by the
+reference manual for the specified accuracy.
+
+Channels are the single-ended ADCH inputs; 26 is the temperature sensor, 27
+the bandgap reference, 29 and 30 are VREFH and VREFL.
+*/
+
+use core::option::Option::{Some, None};
+use core::result::Result::{self, Ok, Err};
+
+use hal::adc::{self, Error, Resolution};
+use super::sim;
+
+#[path="../../util/wait_for.rs"]
+#[macro_use] mod wait_for;
+
+/// Hardware conversion triggers, routed through SIM_SOPT7.
+#[allow(missing_docs)]
+#[derive(Clone, Copy)]
+pub enum Trigger {
+  Cmp0,
+  Cmp1,
+  Pit0,
+  Pit1,
+  Pit2,
+  Pit3,
+  Ftm0,
+  Ftm1,
+}
+
+/// Highest valid channel, 31 disables the ADC.
+const MAX_CHANNEL: u8 = 30;
+const CHANNEL_DISABLED: u32 = 31;
+
+/// Sample time extensions in ADC cycles, selected by ADLSTS.
+const LONG_SAMPLE_TIMES: [(u32, u32); 4] = [(2, 3), (6, 2), (12, 1), (20, 0)];
+
+/// Structure describing the ADC instance.
+#[derive(Clone, Copy)]
+pub struct Adc {
+  reg: &'static reg::ADC,
+}
+
+impl Adc {
+  /// Powers up and calibrates ADC0, with a 12-bit resolution.
+  pub fn new() -> Adc {
+    sim::reg::SIM.scgc6.set_adc0(true);
+
+    let adc = Adc { reg: &reg::ADC0 };
+    adc.reg.cfg1.ignoring_state()
+      .set_adiclk(reg::ADC_cfg1_adiclk::ADACK)
+      .set_mode(reg::ADC_cfg1_mode::BITS_12);
+    adc.reg.cfg2.set_adacken(true);
+    adc.calibrate();
+    adc
+  }
+
+  /// Starts a conversion of `channel` on each `trigger` event.
+  ///
+  /// Results are read with `latest`, until `stop` is called.
+  pub fn start_triggered(&self, channel: u8, trigger: Trigger)
+      -> Result<(), Error> {
+    use super::sim::reg::SIM_sopt7_adc0trgsel as trgsel;
+    if channel > MAX_CHANNEL {
+      return Err(Error::InvalidChannel);
+    }
+    sim::reg::SIM.sopt7
+      .set_adc0alttrgen(true)
+      .set_adc0trgsel(match trigger {
+        Trigger::Cmp0 => trgsel::CMP0_OUT,
+        Trigger::Cmp1 => trgsel::CMP1_OUT,
+        Trigger::Pit0 => trgsel::PIT_TRG0,
+        Trigger::Pit1 => trgsel::PIT_TRG1,
+        Trigger::Pit2 => trgsel::PIT_TRG2,
+        Trigger::Pit3 => trgsel::PIT_TRG3,
+        Trigger::Ftm0 => trgsel::FTM0_TRG,
+        Trigger::Ftm1 => trgsel::FTM1_TRG,
+      });
+    self.reg.sc3.set_adco(false);
+    self.reg.sc2.set_adtrg(true);
+    // With hardware triggers, writing SC1A only selects the channel.
+    self.reg.sc1a.ignoring_state().set_adch(channel as u32);
+    Ok(())
+  }
+
+  /// Runs the self-calibration with 32 samples averaging, then loads the
+  /// plus-side gain.
+  ///
+  /// A failed calibration leaves the ADC usable, with reduced accuracy.
+  fn calibrate(&self) {
+    let reg = self.reg;
+    reg.sc3.ignoring_state()
+      .set_avge(true)
+      .set_avgs(reg::ADC_sc3_avgs::SAMPLES_32)
+      .set_cal(true);
+    wait_for!(!reg.sc3.cal());
+
+    if !reg.sc3.calf() {
+      let sum = reg.clps.value() + reg.clp4.value() + reg.clp3.value() +
+                reg.clp2.value() + reg.clp1.value() + reg.clp0.value();
+      reg.pg.set_value((sum >> 1) | 0x8000);
+    }
+
+    reg.sc3.ignoring_state().set_calf(true);
+  }
+
+  fn start(&self, channel: u8, continuous: bool) -> Result<(), Error> {
+    if channel > MAX_CHANNEL {
+      return Err(Error::InvalidChannel);
+    }
+    self.reg.sc2.set_adtrg(false);
+    self.reg.sc3.set_adco(continuous);
+    self.reg.sc1a.ignoring_state().set_adch(channel as u32);
+    Ok(())
+  }
+}
+
+impl adc::Adc for Adc {
+  fn set_resolution(&self, resolution: Resolution) -> Result<(), Error> {
+    use self::reg::ADC_cfg1_mode as mode;
+    self.reg.cfg1.set_mode(match resolution {
+      Resolution::Bits8  => mode::BITS_8,
+      Resolution::Bits10 => mode::BITS_10,
+      Resolution::Bits12 => mode::BITS_12,
+      Resolution::Bits16 => mode::BITS_16,
+      Resolution::Bits6  => return Err(Error::UnsupportedResolution),
+    });
+    Ok(())
+  }
+
+  fn resolution(&self) -> Resolution {
+    use self::reg::ADC_cfg1_mode as mode;
+    match self.reg.cfg1.mode() {
+      mode::BITS_8  => Resolution::Bits8,
+      mode::BITS_10 => Resolution::Bits10,
+      mode::BITS_12 => Resolution::Bits12,
+      mode::BITS_16 => Resolution::Bits16,
+    }
+  }
+
+  /// Cycles are counted on top of the short sample time: 0 selects short
+  /// sampling, longer requests pick one of the 2, 6, 12 or 20 cycles long
+  /// sample extensions.
+  fn set_sample_time(&self, cycles: u32) -> Result<(), Error> {
+    if cycles == 0 {
+      self.reg.cfg1.set_adlsmp(false);
+      return Ok(());
+    }
+    match LONG_SAMPLE_TIMES.iter().find(|&&(t, _)| t >= cycles) {
+      Some(&(_, adlsts)) => {
+        self.reg.cfg2.set_adlsts(adlsts);
+        self.reg.cfg1.set_adlsmp(true);
+        Ok(())
+      },
+      None => Err(Error::UnsupportedSampleTime),
+    }
+  }
+
+  fn read(&self, channel: u8) -> Result<u16, Error> {
+    try!(self.start(channel, false));
+    wait_for!(self.reg.sc1a.coco());
+    Ok(self.reg.ra.d() as u16)
+  }
+
+  fn start_continuous(&self, channel: u8) -> Result<(), Error> {
+    self.start(channel, true)
+  }
+
+  fn stop(&self) {
+    self.reg.sc2.set_adtrg(false);
+    self.reg.sc3.set_adco(false);
+    self.reg.sc1a.ignoring_state().set_adch(CHANNEL_DISABLED);
+  }
+
+  fn latest(&self) -> u16 {
+    self.reg.ra.d() as u16
+  }
+}
+
+/// Register definitions
+#[allow(dead_code)]
+pub mod reg {
+  use volatile_cell::VolatileCell;
+  use core::ops::Drop;
+
+  ioregs!(ADC = {
+    0x0    => reg32 sc1a {    //= Status and control 1 A
+      0..4   => adch,         //= Input channel select
+      5      => diff,         //= Differential mode enable
+      6      => aien,         //= Interrupt enable
+      7      => coco: ro,     //= Conversion complete
+    }
+
+    0x8    => reg32 cfg1 {    //= Configuration 1
+      0..1   => adiclk {      //= Input clock select
+        0 => BUS,
+        1 => BUS_DIV2,
+        2 => ALTCLK,
+        3 => ADACK,
+      },
+      2..3   => mode {        //= Conversion mode
+        0 => BITS_8,
+        1 => BITS_12,
+        2 => BITS_10,
+        3 => BITS_16,
+      },
+      4      => adlsmp,       //= Long sample time
+      5..6   => adiv,         //= Clock divide select
+      7      => adlpc,        //= Low power configuration
+    }
+
+    0xc    => reg32 cfg2 {    //= Configuration 2
+      0..1   => adlsts,       //= Long sample time select
+      2      => adhsc,        //= High speed configuration
+      3      => adacken,      //= Asynchronous clock output enable
+      4      => muxsel,       //= ADC mux select
+    }
+
+    0x10   => reg32 ra {      //= Data result A
+      0..15  => d: ro,
+    }
+
+    0x20   => reg32 sc2 {     //= Status and control 2
+      0..1   => refsel,       //= Voltage reference selection
+      2      => dmaen,        //= DMA enable
+      6      => adtrg,        //= Hardware trigger select
+      7      => adact: ro,    //= Conversion active
+    }
+
+    0x24   => reg32 sc3 {     //= Status and control 3
+      0..1   => avgs {        //= Hardware average select
+        0 => SAMPLES_4,
+        1 => SAMPLES_8,
+        2 => SAMPLES_16,
+        3 => SAMPLES_32,
+      },
+      2      => avge,         //= Hardware average enable
+      3      => adco,         //= Continuous conversion enable
+      6      => calf,         //= Calibration failed, write 1 to clear
+      7      => cal,          //= Calibration
+    }
+
+    0x2c   => reg32 pg {      //= Plus-side gain
+      0..15  => value,
+    }
+
+    0x38   => reg32 clps {    //= Plus-side general calibration value S
+      0..5   => value: ro,
+    }
+
+    0x3c   => reg32 clp4 {    //= Plus-side general calibration value 4
+      0..9   => value: ro,
+    }
+
+    0x40   => reg32 clp3 {    //= Plus-side general calibration value 3
+      0..8   => value: ro,
+    }
+
+    0x44   => reg32 clp2 {    //= Plus-side general calibration value 2
+      0..7   => value: ro,
+    }
+
+    0x48   => reg32 clp1 {    //= Plus-side general calibration value 1
+      0..6   => value: ro,
+    }
+
+    0x4c   => reg32 clp0 {    //= Plus-side general calibration value 0
+      0..5   => value: ro,
+    }
+  });
+
+  extern {
+    #[link_name="k20_iomem_ADC0"] pub static ADC0: ADC;
+  }
+}
@@ -0,0 +1,125 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Interrupt lines and NVIC vector table for k20.
+
+use hal::cortex_m4::nvic::Interrupt;
+
+#[path="../irq_list.rs"]
+#[macro_use] mod irq_list;
+
+irqs! {
+  Dma0           => isr_dma_0,
+  Dma1           => isr_dma_1,
+  Dma2           => isr_dma_2,
+  Dma3           => isr_dma_3,
+  Dma4           => isr_dma_4,
+  Dma5           => isr_dma_5,
+  Dma6           => isr_dma_6,
+  Dma7           => isr_dma_7,
+  Dma8           => isr_dma_8,
+  Dma9           => isr_dma_9,
+  Dma10          => isr_dma_10,
+  Dma11          => isr_dma_11,
+  Dma12          => isr_dma_12,
+  Dma13          => isr_dma_13,
+  Dma14          => isr_dma_14,
+  Dma15          => isr_dma_15,
+  DmaErr         => isr_dma_err,
+  _,
+  FlashComplete  => isr_flash_complete,
+  FlashCollision => isr_flash_collision,
+  LowVolt        => isr_low_volt,
+  Llwu           => isr_llwu,
+  Wdt            => isr_wdt,
+  _,
+  I2c0           => isr_i2c_0,
+  I2c1           => isr_i2c_1,
+  Spi0           => isr_spi_0,
+  Spi1           => isr_spi_1,
+  _,
+  Can0Msg        => isr_can_0_msg,
+  Can0Bus        => isr_can_0_bus,
+  Can0Err        => isr_can_0_err,
+  Can0Tx         => isr_can_0_tx,
+  Can0Rx         => isr_can_0_rx,
+  Can0Wake       => isr_can_0_wake,
+  I2s0Tx         => isr_i2s_0_tx,
+  I2s0Rx         => isr_i2s_0_rx,
+  _,
+  _,
+  _,
+  _,
+  _,
+  _,
+  _,
+  Uart0Lon       => isr_uart_0_lon,
+  Uart0Stat      => isr_uart_0_stat,
+  Uart0Err       => isr_uart_0_err,
+  Uart1Stat      => isr_uart_1_stat,
+  Uart1Err       => isr_uart_1_err,
+  Uart2Stat      => isr_uart_2_stat,
+  Uart2Err       => isr_uart_2_err,
+  _,
+  _,
+  _,
+  _,
+  _,
+  _,
+  Adc0           => isr_adc_0,
+  Adc1           => isr_adc_1,
+  Cmp0           => isr_cmp_0,
+  Cmp1           => isr_cmp_1,
+  Cmp2           => isr_cmp_2,
+  Ftm0           => isr_ftm_0,
+  Ftm1           => isr_ftm_1,
+  Ftm2           => isr_ftm_2,
+  Cmt            => ist_cmt,
+  RtcAlarm       => isr_rtc_alarm,
+  RtcTick        => isr_rtc_tick,
+  Pit0           => isr_pit_0,
+  Pit1           => isr_pit_1,
+  Pit2           => isr_pit_2,
+  Pit3           => isr_pit_3,
+  Pdb            => isr_pdb,
+  Usb            => isr_usb,
+  UsbDcd         => isr_usb_dcd,
+  _,
+  _,
+  _,
+  _,
+  _,
+  _,
+  Dac0           => isr_dac_0,
+  _,
+  Tsi            => isr_tsi,
+  Mcg            => isr_mcg,
+  Lptimer        => isr_lptimer,
+  _,
+  PortA          => isr_port_a,
+  PortB          => isr_port_b,
+  PortC          => isr_port_c,
+  PortD          => isr_port_d,
+  PortE          => isr_port_e,
+  _,
+  _,
+  Soft           => isr_soft,
+}
+
+impl Interrupt for Irq {
+  fn number(&self) -> usize {
+    *self as usize
+  }
+}
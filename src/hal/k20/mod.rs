@@ -15,7 +15,10 @@
 
 //! HAL for Freescale Kinetis K20.
 
+pub mod adc;
+pub mod irq;
 pub mod sim;
 pub mod pin;
+pub mod pwm;
 pub mod uart;
 pub mod watchdog;
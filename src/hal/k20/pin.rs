@@ -18,11 +18,21 @@ Pin configuration.
 
 Some pins that could be configured here may be missing from actual MCU depending
 on the package.
+
+Each port has its own interrupt, `isr_handler` must be called from the
+`isr_port_*` ISR of the port.
 */
 
-use core::option::Option;
+use core::option::Option::{self, Some, None};
+use core::result::Result::{self, Ok, Err};
 use core::marker::Copy;
 
+use hal::cortex_m4::irq::NoInterrupts;
+use hal::cortex_m4::nvic;
+use hal::k20::irq::Irq;
+use hal::pin::{ConfigError, GpioEdge, GpioHandler};
+use util::shared::Shared;
+
 use super::sim;
 
 use self::Port::*;
@@ -157,14 +167,7 @@ impl Pin {
   }
 
   fn pcr(&self) -> &'static reg::Port_pcr {
-    let port: &reg::Port = match self.port {
-      PortA => &reg::PORT_A,
-      PortB => &reg::PORT_B,
-      PortC => &reg::PORT_C,
-      PortD => &reg::PORT_D,
-      PortE => &reg::PORT_E,
-    };
-    return &port.pcr[self.pin as usize];
+    return &port_reg(self.port).pcr[self.pin as usize];
   }
 }
 
@@ -200,6 +203,125 @@ impl ::hal::pin::Gpio for Pin {
   }
 }
 
+impl ::hal::pin::Electrical for Pin {
+  fn set_pull(&self, pull: ::hal::pin::Pull) -> Result<(), ConfigError> {
+    use hal::pin::Pull;
+    use self::reg::Port_pcr_ps as ps;
+    let (pe, ps) = match pull {
+      Pull::Floating => (false, ps::PULL_DOWN),
+      Pull::Down     => (true,  ps::PULL_DOWN),
+      Pull::Up       => (true,  ps::PULL_UP),
+    };
+    self.pcr().set_pe(pe).set_ps(ps);
+    Ok(())
+  }
+
+  fn set_output_type(&self, output_type: ::hal::pin::OutputType)
+      -> Result<(), ConfigError> {
+    self.pcr().set_ode(output_type == ::hal::pin::OutputType::OpenDrain);
+    Ok(())
+  }
+
+  fn set_drive_strength(&self, strength: ::hal::pin::DriveStrength)
+      -> Result<(), ConfigError> {
+    use hal::pin::DriveStrength;
+    use self::reg::Port_pcr_dse as dse;
+    let dse = match strength {
+      DriveStrength::Low    => dse::LOW_DRIVE,
+      DriveStrength::High   => dse::HIGH_DRIVE,
+      DriveStrength::Medium =>
+        return Err(ConfigError::UnsupportedDriveStrength),
+    };
+    self.pcr().set_dse(dse);
+    Ok(())
+  }
+
+  fn set_slew_rate(&self, rate: ::hal::pin::SlewRate)
+      -> Result<(), ConfigError> {
+    use hal::pin::SlewRate;
+    use self::reg::Port_pcr_sre as sre;
+    self.pcr().set_sre(match rate {
+      SlewRate::Slow => sre::SLOW,
+      SlewRate::Fast => sre::FAST,
+    });
+    Ok(())
+  }
+}
+
+impl ::hal::pin::GpioInterrupt for Pin {
+  fn set_handler(&self, handler: Option<GpioHandler>) {
+    let index = port_index(self.port) * 32 + self.pin as usize;
+    let crit = NoInterrupts::new();
+    HANDLERS.borrow(&crit)[index] = handler;
+  }
+
+  fn enable_interrupt(&self, edge: GpioEdge) {
+    use self::reg::Port_pcr_irqc as irqc;
+    self.pcr().set_irqc(match edge {
+      GpioEdge::Rising  => irqc::IRQ_RISING,
+      GpioEdge::Falling => irqc::IRQ_FALLING,
+      GpioEdge::Both    => irqc::IRQ_EITHER,
+    });
+    nvic::enable_irq(port_irq(self.port));
+  }
+
+  fn disable_interrupt(&self) {
+    use self::reg::Port_pcr_irqc as irqc;
+    self.pcr().set_irqc(irqc::IRQ_NONE);
+  }
+
+  fn clear_interrupt(&self) {
+    port_reg(self.port).isfr.ignoring_state().set_isf(self.pin as usize, true);
+  }
+}
+
+static HANDLERS: Shared<[Option<GpioHandler>; 160]> = Shared::new([None; 160]);
+
+fn port_irq(port: Port) -> Irq {
+  match port {
+    PortA => Irq::PortA,
+    PortB => Irq::PortB,
+    PortC => Irq::PortC,
+    PortD => Irq::PortD,
+    PortE => Irq::PortE,
+  }
+}
+
+fn port_index(port: Port) -> usize {
+  port as usize - 1
+}
+
+fn port_reg(port: Port) -> &'static reg::Port {
+  match port {
+    PortA => &reg::PORT_A,
+    PortB => &reg::PORT_B,
+    PortC => &reg::PORT_C,
+    PortD => &reg::PORT_D,
+    PortE => &reg::PORT_E,
+  }
+}
+
+/// Services the interrupt of a port, calling handlers of the pins that
+/// triggered.
+pub fn isr_handler(port: Port) {
+  let reg = port_reg(port);
+  for pin in 0..32 {
+    if !reg.isfr.isf(pin) {
+      continue;
+    }
+    reg.isfr.ignoring_state().set_isf(pin, true);
+
+    let handler = {
+      let crit = NoInterrupts::new();
+      HANDLERS.borrow(&crit)[port_index(port) * 32 + pin]
+    };
+    match handler {
+      Some(handler) => handler(),
+      None => (),
+    }
+  }
+}
+
 /// Register definitions
 pub mod reg {
   use volatile_cell::VolatileCell;
@@ -250,8 +372,8 @@ pub mod reg {
       16..31 => gpwe,
     }
 
-    0x88   => reg32 isfr {    //= Interrupt status
-      0..31  => isf
+    0xa0   => reg32 isfr {    //= Interrupt status, write 1 to clear
+      0..31  => isf[32]
     }
   });
 
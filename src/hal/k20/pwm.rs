@@ -0,0 +1,212 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/*!
+PWM outputs on the FlexTimer modules.
+
+FTM0 has eight channels and FTM1 two, all sharing the period of their module:
+setting the period of a channel changes it for the other channels of the same
+FTM. The prescaler is picked to fit the period in the 16-bit counter, so
+shorter periods have a finer resolution. Periods are clamped between one tick
+and the longest period the counter can hold, 65536 * 128 bus clock ticks.
+
+Output pins must be set to their FTM alternate function. There is no
+platformtree `pwm` node for the K20, outputs are created with `PWM::new`.
+*/
+
+use core::cmp::{max, min};
+use core::intrinsics::abort;
+
+use hal::pwm::PWMOutput;
+use super::sim;
+
+/// Available FlexTimer modules.
+#[allow(missing_docs)]
+#[derive(Clone, Copy)]
+pub enum FtmPeripheral {
+  Ftm0,
+  Ftm1,
+}
+
+/// FlexTimer channels, FTM1 only has channels 0 and 1.
+#[allow(missing_docs)]
+#[derive(Clone, Copy)]
+pub enum Channel {
+  Channel0,
+  Channel1,
+  Channel2,
+  Channel3,
+  Channel4,
+  Channel5,
+  Channel6,
+  Channel7,
+}
+
+/// Structure describing a PWM output.
+#[derive(Clone, Copy)]
+pub struct PWM {
+  reg: &'static reg::FTM,
+  channel: Channel,
+  clock: u32,
+  period_us: u32,
+  pulsewidth_us: u32,
+}
+
+impl PWM {
+  /// Creates a PWM output on `channel` of `ftm`, initially low.
+  ///
+  /// `bus_clock` is the frequency of the bus clock feeding the FTM, in Hz.
+  pub fn new(ftm: FtmPeripheral, channel: Channel, period_us: u32,
+      bus_clock: u32) -> PWM {
+    let reg = match ftm {
+      FtmPeripheral::Ftm0 => {
+        sim::reg::SIM.scgc6.set_ftm0(true);
+        &reg::FTM0
+      },
+      FtmPeripheral::Ftm1 => {
+        if channel as usize > 1 {
+          unsafe { abort() };
+        }
+        sim::reg::SIM.scgc6.set_ftm1(true);
+        &reg::FTM1
+      },
+    };
+
+    // Edge-aligned, high-true pulses.
+    reg.channel[channel as usize].csc.ignoring_state()
+      .set_msb(true)
+      .set_elsb(true);
+
+    let pwm = PWM {
+      reg: reg,
+      channel: channel,
+      clock: bus_clock,
+      period_us: period_us,
+      pulsewidth_us: 0,
+    };
+    pwm.update_period();
+    pwm.update_pulsewidth();
+    pwm
+  }
+
+  /// Returns the number of counter ticks in `us` at the current prescaler,
+  /// saturated to the counter width.
+  fn us_to_ticks(&self, us: u32) -> u32 {
+    let ticks = us as u64 * (self.clock / 1_000_000) as u64;
+    let ticks = ticks >> self.reg.sc.ps();
+    min(ticks, 0xffff) as u32
+  }
+
+  fn update_period(&self) {
+    let ticks = self.period_us as u64 * (self.clock / 1_000_000) as u64;
+    let ticks = min(max(ticks, 1), 0x10000 << 7) as u32;
+    let mut prescaler = 0;
+    while (ticks >> prescaler) > 0x10000 && prescaler < 7 {
+      prescaler += 1;
+    }
+
+    self.reg.cntin.set_init(0);
+    self.reg.md.set_value((ticks >> prescaler) - 1);
+    self.reg.sc
+      .set_ps(prescaler)
+      .set_clks(reg::FTM_sc_clks::SystemClock);
+  }
+
+  fn update_pulsewidth(&self) {
+    // Channel values above MOD keep the output high.
+    let ticks = self.us_to_ticks(self.pulsewidth_us);
+    self.reg.channel[self.channel as usize].cv.set_value(ticks);
+  }
+}
+
+impl PWMOutput for PWM {
+  fn set_period_us(&mut self, period_us: u32) {
+    self.period_us = period_us;
+    self.update_period();
+    // The prescaler may have changed.
+    self.update_pulsewidth();
+  }
+
+  fn get_period_us(&self) -> u32 {
+    self.period_us
+  }
+
+  fn set_pulsewidth_us(&mut self, pulsewidth_us: u32) {
+    self.pulsewidth_us = pulsewidth_us;
+    self.update_pulsewidth();
+  }
+
+  fn get_pulsewidth_us(&self) -> u32 {
+    self.pulsewidth_us
+  }
+}
+
+/// Register definitions
+#[allow(dead_code)]
+pub mod reg {
+  use volatile_cell::VolatileCell;
+  use core::ops::Drop;
+
+  ioregs!(FTM = {
+    0x0    => reg32 sc {      //= Status and control
+      0..2   => ps,           //= Prescale factor selection
+      3..4   => clks {        //= Clock source selection
+        0 => Disabled,
+        1 => SystemClock,
+        2 => FixedFrequency,
+        3 => External,
+      },
+      5      => cpwms,        //= Center-aligned PWM select
+      6      => toie,         //= Timer overflow interrupt enable
+      7      => tof,          //= Timer overflow flag
+    },
+
+    0x4    => reg32 cnt {     //= Counter
+      0..15  => count,
+    },
+
+    0x8    => reg32 md {      //= Modulo
+      0..15  => value,
+    },
+
+    0xc    => group channel[8] {
+      0x0    => reg32 csc {   //= Channel status and control
+        2      => elsa,       //= Edge or level select A
+        3      => elsb,       //= Edge or level select B
+        4      => msa,        //= Mode select A
+        5      => msb,        //= Mode select B
+        6      => chie,       //= Channel interrupt enable
+        7      => chf,        //= Channel flag
+      },
+      0x4    => reg32 cv {    //= Channel value
+        0..15  => value,
+      },
+    },
+
+    0x4c   => reg32 cntin {   //= Counter initial value
+      0..15  => init,
+    },
+
+    0x54   => reg32 mode {    //= Features mode selection
+      0      => ftmen,        //= FTM enable
+      2      => wpdis,        //= Write protection disable
+    },
+  });
+
+  extern {
+    #[link_name="k20_iomem_FTM0"] pub static FTM0: FTM;
+    #[link_name="k20_iomem_FTM1"] pub static FTM1: FTM;
+  }
+}
@@ -18,8 +18,10 @@ UART configuration.
 */
 
 use core::intrinsics::abort;
+use core::option::Option::{Some, None};
+use core::result::Result::{self, Ok, Err};
 
-use drivers::chario::CharIO;
+use drivers::chario::{CharIO, CharInput, ReadError};
 use hal::uart;
 
 use self::UARTPeripheral::*;
@@ -122,6 +124,28 @@ impl CharIO for UART {
   }
 }
 
+impl CharInput for UART {
+  fn try_getc(&self) -> Result<Option<char>, ReadError> {
+    // Error flags are cleared by reading S1 followed by D, so the data
+    // register is always read, even if the character is to be dropped.
+    let s1 = self.reg.s1.get();
+    if s1.fe() || s1.pf() || s1.or() {
+      let _ = self.reg.d.re();
+      return Err(if s1.fe() {
+        ReadError::Framing
+      } else if s1.pf() {
+        ReadError::Parity
+      } else {
+        ReadError::Overrun
+      });
+    }
+    if !s1.rdrf() {
+      return Ok(None);
+    }
+    Ok(Some(self.reg.d.re() as char))
+  }
+}
+
 /// Register definitions
 pub mod reg {
   use volatile_cell::VolatileCell;
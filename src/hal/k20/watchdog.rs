@@ -15,6 +15,7 @@
 
 //! Watchdog for Kinetis SIM module.
 
+use hal::watchdog;
 use util::support::nop;
 
 #[path="../../util/ioreg.rs"] mod ioreg;
@@ -57,6 +58,39 @@ pub fn refresh() {
   reg::WDOG.refresh.set_refresh(RefreshSeq2);
 }
 
+/// Watchdog clocked from the 1kHz low power oscillator.
+///
+/// The watchdog can only be reconfigured once after reset, unless `init` was
+/// called with `State::Enabled`.
+#[derive(Clone, Copy)]
+pub struct Watchdog;
+
+impl watchdog::Watchdog for Watchdog {
+  fn start(&self, timeout_ms: u32) {
+    unlock();
+    // The low power oscillator ticks every millisecond.
+    reg::WDOG.tovalh.set_toval((timeout_ms >> 16) as u16);
+    reg::WDOG.tovall.set_toval(timeout_ms as u16);
+    reg::WDOG.presc.set_prescval(0);
+    reg::WDOG.stctrlh
+      .set_clksrc(false)
+      .set_allowupdate(true)
+      .set_en(true);
+  }
+
+  fn feed(&self) {
+    refresh();
+  }
+
+  fn caused_reset(&self) -> bool {
+    reg::WDOG.rstcnt.rstcnt() != 0
+  }
+
+  fn clear_reset_flags(&self) {
+    reg::WDOG.rstcnt.set_rstcnt(0xffff);
+  }
+}
+
 #[allow(dead_code)]
 mod reg {
   use volatile_cell::VolatileCell;
@@ -67,10 +101,21 @@ mod reg {
     0x0 => reg16 stctrlh
     {
       0 => en,             //= Watchdog enable
+      1 => clksrc,         //= Use the alternate clock instead of the LPO
       4 => allowupdate     //= Enables updates to watchdog write-once registers,
                            //= after the reset-triggered initial configuration window
     },
 
+    /// Timeout Value Register High
+    0x4 => reg16 tovalh {
+      0..15 => toval,
+    },
+
+    /// Timeout Value Register Low
+    0x6 => reg16 tovall {
+      0..15 => toval,
+    },
+
     /// Refresh Register
     0xc => reg16 refresh {
       0..15 => refresh: wo
@@ -89,6 +134,16 @@ mod reg {
       },
     },
 
+    /// Reset Count Register, cleared by writing 1s
+    0x14 => reg16 rstcnt {
+      0..15 => rstcnt,
+    },
+
+    /// Prescaler Register
+    0x16 => reg16 presc {
+      8..10 => prescval,   //= Clock divided by prescval + 1
+    },
+
   });
 
 
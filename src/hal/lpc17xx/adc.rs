@@ -0,0 +1,193 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/*!
+Analog to digital converter for LPC17xx.
+
+The ADC has eight 12-bit channels, AD0.0 to AD0.7, whose pins must be switched
+to their ADC function with `pin::Pin`. A conversion always takes 65 ADC clocks
+and the ADC clock can't exceed 13 MHz.
+
+This supersedes the blocking `hal::pin::Adc` implementation of `pin::Pin`.
+*/
+
+use core::result::Result::{self, Ok, Err};
+
+use hal::adc::{self, Error, Resolution};
+use hal::lpc17xx::peripheral_clock::PeripheralClock::ADCClock;
+
+/// Conversion triggers, a conversion starts on their rising edge.
+#[derive(Clone, Copy)]
+pub enum Trigger {
+  /// P2.10, EINT0.
+  Eint0 = 0b010,
+  /// P1.27, CAP0.1.
+  Cap01 = 0b011,
+  /// Timer 0 match 1.
+  Mat01 = 0b100,
+  /// Timer 0 match 3.
+  Mat03 = 0b101,
+  /// Timer 1 match 0.
+  Mat10 = 0b110,
+  /// Timer 1 match 1.
+  Mat11 = 0b111,
+}
+
+/// Highest channel number.
+const MAX_CHANNEL: u8 = 7;
+
+/// ADC clocks taken by a conversion.
+const CONVERSION_CLOCKS: u32 = 65;
+
+/// ADC maximum clock frequency.
+const MAX_CLOCK: u32 = 13_000_000;
+
+/// START value that starts a conversion immediately.
+const START_NOW: u32 = 0b001;
+
+/// Structure describing the ADC.
+#[derive(Clone, Copy)]
+pub struct Adc {
+  reg: &'static reg::ADC,
+}
+
+impl Adc {
+  /// Powers up the ADC, running it at the highest clock it supports.
+  pub fn new() -> Adc {
+    ADCClock.enable();
+    ADCClock.set_divisor(1);
+
+    let pclk = ADCClock.frequency();
+    let clkdiv = (pclk + MAX_CLOCK - 1) / MAX_CLOCK;
+
+    let adc = Adc { reg: &reg::ADC };
+    adc.reg.cr.ignoring_state()
+      .set_clkdiv(clkdiv - 1)
+      .set_pdn(true);
+    adc
+  }
+
+  /// Starts a conversion of `channel` on each rising edge of `trigger`.
+  ///
+  /// Results are read with `latest`, until `stop` is called.
+  pub fn start_triggered(&self, channel: u8, trigger: Trigger)
+      -> Result<(), Error> {
+    try!(self.select(channel));
+    self.reg.cr
+      .set_edge(false)
+      .set_start(trigger as u32);
+    Ok(())
+  }
+
+  /// Selects `channel` alone and stops any running conversion.
+  fn select(&self, channel: u8) -> Result<(), Error> {
+    if channel > MAX_CHANNEL {
+      return Err(Error::InvalidChannel);
+    }
+    self.reg.cr
+      .set_burst(false)
+      .set_start(0)
+      .set_sel(1 << channel);
+    Ok(())
+  }
+}
+
+impl adc::Adc for Adc {
+  fn set_resolution(&self, resolution: Resolution) -> Result<(), Error> {
+    match resolution {
+      Resolution::Bits12 => Ok(()),
+      _                  => Err(Error::UnsupportedResolution),
+    }
+  }
+
+  fn resolution(&self) -> Resolution {
+    Resolution::Bits12
+  }
+
+  fn set_sample_time(&self, cycles: u32) -> Result<(), Error> {
+    if cycles <= CONVERSION_CLOCKS {
+      Ok(())
+    } else {
+      Err(Error::UnsupportedSampleTime)
+    }
+  }
+
+  fn read(&self, channel: u8) -> Result<u16, Error> {
+    try!(self.select(channel));
+    self.reg.cr.set_start(START_NOW);
+    // Reading GDR clears DONE, so the result is taken from the same read.
+    let result;
+    loop {
+      let gdr = self.reg.gdr.get();
+      if gdr.done() {
+        result = gdr.result();
+        break;
+      }
+    }
+    self.reg.cr.set_start(0);
+    Ok(result as u16)
+  }
+
+  fn start_continuous(&self, channel: u8) -> Result<(), Error> {
+    try!(self.select(channel));
+    self.reg.cr.set_burst(true);
+    Ok(())
+  }
+
+  fn stop(&self) {
+    self.reg.cr
+      .set_burst(false)
+      .set_start(0);
+  }
+
+  fn latest(&self) -> u16 {
+    self.reg.gdr.result() as u16
+  }
+}
+
+mod reg {
+  use volatile_cell::VolatileCell;
+  use core::ops::Drop;
+
+  ioregs!(ADC = {
+    0x00 => reg32 cr {      //! Control register
+      7..0   => sel,        //= Channels to convert
+      15..8  => clkdiv,     //= ADC clock is PCLK / (CLKDIV + 1)
+      16     => burst,      //= Repeated conversions
+      21     => pdn,        //= Operational, the ADC is powered down if 0
+      26..24 => start,      //= Conversion start
+      27     => edge,       //= Start on falling edge
+    },
+    0x04 => reg32 gdr {     //! Global data register
+      15..4  => result : ro,
+      26..24 => chn : ro,   //= Channel of the result
+      30     => overrun : ro,
+      31     => done : ro,
+    },
+    0x0C => reg32 inten {   //! Interrupt enable register
+      7..0   => channels,
+      8      => global,
+    },
+    0x30 => reg32 stat {    //! Status register
+      7..0   => done : ro,
+      15..8  => overrun : ro,
+      16     => int : ro,
+    },
+  });
+
+  extern {
+    #[link_name="lpc17xx_iomem_ADC"] pub static ADC: ADC;
+  }
+}
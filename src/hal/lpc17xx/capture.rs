@@ -0,0 +1,194 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/*!
+Input capture on the timer CAPn.m inputs.
+
+Each of the four timers has two capture inputs, which share its counter. The
+capture pins must be set to their CAPn.m function.
+
+`isr_handler` must be called from the interrupt of each timer used for capture.
+*/
+
+use core::option::Option::{self, Some, None};
+
+use hal::capture::{self, Callback, EdgeTracker, Measurement};
+use hal::cortex_m3::irq::NoInterrupts;
+use hal::cortex_m3::nvic;
+use hal::lpc17xx::irq::Irq;
+use hal::lpc17xx::peripheral_clock::PeripheralClock;
+use hal::lpc17xx::timer::TimerPeripheral;
+use util::shared::Shared;
+
+/// Capture inputs of a timer.
+#[allow(missing_docs)]
+#[derive(Clone, Copy)]
+pub enum CaptureChannel {
+  Capture0,
+  Capture1,
+}
+
+const CAP_RISING: u32 = 0b001;
+const CAP_FALLING: u32 = 0b010;
+const CAP_INTERRUPT: u32 = 0b100;
+
+static TRACKERS: Shared<[EdgeTracker; 8]> =
+    Shared::new([EdgeTracker::new(0xffff_ffff); 8]);
+
+/// Structure describing a capture input.
+#[derive(Clone, Copy)]
+pub struct Capture {
+  timer: TimerPeripheral,
+  channel: CaptureChannel,
+  tick_hz: u32,
+}
+
+impl Capture {
+  /// Starts capturing edges on `channel` of `timer`.
+  ///
+  /// The timer is set up as by `timer::Timer::new` with the same `counter`
+  /// and `divisor`, so it can also be used as a `Timer`. Setting up the other
+  /// channel of the timer restarts the counter, which spoils the measurement
+  /// in progress.
+  pub fn new(timer: TimerPeripheral, channel: CaptureChannel, counter: u32,
+      divisor: u8) -> Capture {
+    let (clock, reg, irq) = peripheral(timer);
+    clock.enable();
+    clock.set_divisor(divisor);
+
+    reg.ctcr.set_value(0);
+    reg.tcr.set_value(2);
+    reg.pr.set_value(counter - 1);
+    reg.tcr.set_value(1);
+
+    let capture = Capture {
+      timer: timer,
+      channel: channel,
+      tick_hz: clock.frequency() / counter,
+    };
+    {
+      let crit = NoInterrupts::new();
+      TRACKERS.borrow(&crit)[capture.index()] = EdgeTracker::new(0xffff_ffff);
+    }
+
+    reg.ir.ignoring_state().set_cr(channel as usize, true);
+    reg.ccr.set_cap(channel as usize, CAP_RISING | CAP_INTERRUPT);
+    nvic::enable_irq(irq);
+    capture
+  }
+
+  fn index(&self) -> usize {
+    self.timer as usize * 2 + self.channel as usize
+  }
+}
+
+impl capture::InputCapture for Capture {
+  fn tick_hz(&self) -> u32 {
+    self.tick_hz
+  }
+
+  fn measurement(&self) -> Option<Measurement> {
+    let crit = NoInterrupts::new();
+    TRACKERS.borrow(&crit)[self.index()].measurement()
+  }
+
+  fn set_callback(&self, callback: Option<Callback>) {
+    let crit = NoInterrupts::new();
+    TRACKERS.borrow(&crit)[self.index()].set_callback(callback);
+  }
+}
+
+fn peripheral(timer: TimerPeripheral)
+    -> (PeripheralClock, &'static reg::TIMER, Irq) {
+  match timer {
+    TimerPeripheral::Timer0 =>
+        (PeripheralClock::TIM0Clock, &reg::TIMER0, Irq::Timer0),
+    TimerPeripheral::Timer1 =>
+        (PeripheralClock::TIM1Clock, &reg::TIMER1, Irq::Timer1),
+    TimerPeripheral::Timer2 =>
+        (PeripheralClock::TIM2Clock, &reg::TIMER2, Irq::Timer2),
+    TimerPeripheral::Timer3 =>
+        (PeripheralClock::TIM3Clock, &reg::TIMER3, Irq::Timer3),
+  }
+}
+
+/// Records the captured edges of `timer`, must be called from its interrupt.
+///
+/// Capture alternates between rising and falling edges, so edges closer than
+/// the interrupt latency are missed.
+pub fn isr_handler(timer: TimerPeripheral) {
+  let (_, reg, _) = peripheral(timer);
+  for channel in 0..2 {
+    if !reg.ir.cr(channel) {
+      continue;
+    }
+    let count = reg.cr[channel].value();
+    reg.ir.ignoring_state().set_cr(channel, true);
+
+    let rising = reg.ccr.cap(channel) & CAP_RISING != 0;
+    reg.ccr.set_cap(channel, CAP_INTERRUPT |
+                    if rising { CAP_FALLING } else { CAP_RISING });
+
+    let completed = {
+      let crit = NoInterrupts::new();
+      let mut trackers = TRACKERS.borrow(&crit);
+      let tracker = &mut trackers[timer as usize * 2 + channel];
+      if rising {
+        tracker.rising(count)
+      } else {
+        tracker.falling(count);
+        None
+      }
+    };
+    match completed {
+      Some((callback, measurement)) => callback(measurement),
+      None => (),
+    }
+  }
+}
+
+mod reg {
+  use volatile_cell::VolatileCell;
+  use core::ops::Drop;
+
+  ioregs!(TIMER = {
+    0x00 => reg32 ir {              //! Interrupt flags, write 1 to clear
+      3..0   => mr[4],
+      5..4   => cr[2],
+    },
+    0x04 => reg32 tcr {             //! Timer control
+      1..0   => value,
+    },
+    0x0C => reg32 pr {              //! Prescale
+      31..0  => value,
+    },
+    0x28 => reg32 ccr {             //! Capture control, 3 bits per input
+      5..0   => cap[2],
+    },
+    0x2C => reg32 cr[2] {           //! Capture values
+      31..0  => value: ro,
+    },
+    0x70 => reg32 ctcr {            //! Count control
+      3..0   => value,
+    },
+  });
+
+  extern {
+    #[link_name="lpc17xx_iomem_TIMER0"] pub static TIMER0: TIMER;
+    #[link_name="lpc17xx_iomem_TIMER1"] pub static TIMER1: TIMER;
+    #[link_name="lpc17xx_iomem_TIMER2"] pub static TIMER2: TIMER;
+    #[link_name="lpc17xx_iomem_TIMER3"] pub static TIMER3: TIMER;
+  }
+}
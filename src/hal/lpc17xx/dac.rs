@@ -0,0 +1,138 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/*!
+Digital to analog converter.
+
+The DAC has a single 10-bit output on P0.26 (AOUT), referenced to VREFP.
+*/
+
+use core::option::Option::{self, Some, None};
+
+use hal::cortex_m3::irq::NoInterrupts;
+use hal::dac::{self, Waveform};
+use hal::lpc17xx::pin::{Pin, Port, Function};
+use hal::lpc17xx::timer::Timer;
+use hal::pin::{Electrical, Pull};
+use util::shared::Shared;
+
+/// Largest raw code.
+const MAX_VALUE: u16 = 0x3FF;
+
+static WAVEFORM: Shared<Option<(Waveform, Timer)>> = Shared::new(None);
+
+/// Structure describing the DAC.
+#[derive(Clone, Copy)]
+pub struct Dac {
+  reg: &'static reg::DAC,
+}
+
+impl Dac {
+  /// Switches P0.26 to AOUT and sets the output to 0.
+  ///
+  /// The DAC is left in its fast settling mode (1us, 700uA).
+  pub fn new() -> Dac {
+    let pin = Pin::new(Port::Port0, 26, Function::AltFunction2, None);
+    // The output must not be loaded by the pin pull resistors, P0.26 has a
+    // PINMODE setting so this can't fail.
+    let _ = pin.set_pull(Pull::Floating);
+
+    let dac = Dac { reg: &reg::DAC };
+    dac.reg.dacr.ignoring_state().set_value(0);
+    dac
+  }
+
+  /// Plays `samples` in a loop, writing one every `period` ticks of `timer`.
+  ///
+  /// The timer is reserved for the waveform until `stop_waveform` is called,
+  /// and `isr_handler` must be called from its interrupt.
+  pub fn start_waveform(&self, timer: Timer, period: u32,
+      samples: &'static [u16]) {
+    self.stop_waveform();
+    let mut waveform = Waveform::new(samples);
+    dac::Dac::set_raw(self, waveform.next_sample());
+    {
+      let crit = NoInterrupts::new();
+      *WAVEFORM.borrow(&crit) = Some((waveform, timer));
+    }
+    timer.start_periodic_interrupt(period);
+  }
+
+  /// Stops the waveform, leaving the output at the last sample.
+  pub fn stop_waveform(&self) {
+    let crit = NoInterrupts::new();
+    let mut state = WAVEFORM.borrow(&crit);
+    match *state {
+      Some((_, ref timer)) => timer.stop_periodic_interrupt(),
+      None => return,
+    }
+    *state = None;
+  }
+}
+
+impl dac::Dac for Dac {
+  fn bits(&self) -> u32 {
+    10
+  }
+
+  fn set_raw(&self, value: u16) {
+    let value = if value > MAX_VALUE { MAX_VALUE } else { value };
+    self.reg.dacr.set_value(value as u32);
+  }
+
+  fn raw(&self) -> u16 {
+    self.reg.dacr.value() as u16
+  }
+}
+
+/// Writes the next waveform sample, must be called from the interrupt of the
+/// timer passed to `Dac::start_waveform`.
+pub fn isr_handler() {
+  let crit = NoInterrupts::new();
+  let mut state = WAVEFORM.borrow(&crit);
+  match *state {
+    Some((ref mut waveform, ref timer)) => {
+      timer.clear_interrupt();
+      let dac = Dac { reg: &reg::DAC };
+      dac::Dac::set_raw(&dac, waveform.next_sample());
+    },
+    None => (),
+  }
+}
+
+mod reg {
+  use volatile_cell::VolatileCell;
+  use core::ops::Drop;
+
+  ioregs!(DAC = {
+    0x00 => reg32 dacr {            //! Converter register
+      15..6  => value,
+      16     => bias,               //= Slow settling, low power mode
+    },
+    0x04 => reg32 dacctrl {         //! Control register
+      0      => int_dma_req,        //= Counter timeout flag
+      1      => dblbuf_ena,         //= Double buffering enable
+      2      => cnt_ena,            //= Timeout counter enable
+      3      => dma_ena,            //= DMA access enable
+    },
+    0x08 => reg32 daccntval {       //! Counter reload value
+      15..0  => value,
+    },
+  });
+
+  extern {
+    #[link_name="lpc17xx_iomem_DAC"] pub static DAC: DAC;
+  }
+}
@@ -0,0 +1,294 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/*!
+General purpose DMA controller (GPDMA).
+
+Any of the eight channels can serve any request line, so channels are claimed
+in priority order. Each transfer moves at most `MAX_TRANSFER` bytes.
+
+Callbacks are run from `isr_handler`, which must be called from `isr_dma`.
+*/
+
+use core::intrinsics::abort;
+use core::ops::Drop;
+use core::option::Option::{self, Some, None};
+use core::result::Result::{self, Ok, Err};
+
+use hal::cortex_m3::irq::NoInterrupts;
+use hal::cortex_m3::nvic;
+use hal::dma;
+use hal::lpc17xx::irq::Irq;
+use hal::lpc17xx::peripheral_clock::PeripheralClock::GPDMAClock;
+use util::shared::Shared;
+use util::support::get_reg_ref;
+
+/// Number of GPDMA channels.
+const CHANNEL_COUNT: usize = 8;
+
+/// Maximum number of bytes in a single transfer.
+pub const MAX_TRANSFER: usize = 4095;
+
+/// Peripheral request lines.
+#[allow(missing_docs)]
+#[derive(Clone, Copy)]
+pub enum Request {
+  SSP0Tx  = 0,
+  SSP0Rx  = 1,
+  SSP1Tx  = 2,
+  SSP1Rx  = 3,
+  UART0Tx = 8,
+  UART0Rx = 9,
+  UART2Tx = 12,
+  UART2Rx = 13,
+  UART3Tx = 14,
+  UART3Rx = 15,
+}
+
+struct State {
+  claimed: u8,
+  callbacks: [Option<dma::Callback>; CHANNEL_COUNT],
+}
+
+static STATE: Shared<State> = Shared::new(State {
+  claimed: 0,
+  callbacks: [None; CHANNEL_COUNT],
+});
+
+/// A claimed GPDMA channel, released when dropped.
+pub struct Channel {
+  index: usize,
+}
+
+impl Channel {
+  /// Claims the highest priority free channel.
+  ///
+  /// Returns `None` if all channels are in use.
+  pub fn claim() -> Option<Channel> {
+    let crit = NoInterrupts::new();
+    let mut state = STATE.borrow(&crit);
+
+    if state.claimed == 0 {
+      GPDMAClock.enable();
+      reg::GPDMA.config.set_e(true);
+    }
+
+    for index in 0..CHANNEL_COUNT {
+      if state.claimed & (1 << index) == 0 {
+        state.claimed |= 1 << index;
+        state.callbacks[index] = None;
+        return Some(Channel { index: index });
+      }
+    }
+    None
+  }
+
+  /// Starts a transfer of `len` bytes from memory to a peripheral register.
+  ///
+  /// If `increment` is false, the same byte at `source` is sent `len` times.
+  ///
+  /// This is unsafe as `source` must stay valid until the transfer completes.
+  pub unsafe fn memory_to_peripheral(&self, source: *const u8,
+      increment: bool, len: usize, request: Request, destination: u32) {
+    self.start(source as u32, increment, destination, false, len);
+    self.reg().config.ignoring_state()
+      .set_dest_peripheral(request as u32)
+      .set_transfer_type(TRANSFER_M2P)
+      .set_error_interrupt(self.has_callback())
+      .set_tc_interrupt(self.has_callback())
+      .set_enable(true);
+  }
+
+  /// Starts a transfer of `len` bytes from a peripheral register to memory.
+  ///
+  /// This is unsafe as `destination` must stay valid until the transfer
+  /// completes.
+  pub unsafe fn peripheral_to_memory(&self, request: Request, source: u32,
+      destination: *mut u8, len: usize) {
+    self.start(source, false, destination as u32, true, len);
+    self.reg().config.ignoring_state()
+      .set_src_peripheral(request as u32)
+      .set_transfer_type(TRANSFER_P2M)
+      .set_error_interrupt(self.has_callback())
+      .set_tc_interrupt(self.has_callback())
+      .set_enable(true);
+  }
+
+  fn start(&self, source: u32, src_increment: bool, destination: u32,
+      dest_increment: bool, len: usize) {
+    if len > MAX_TRANSFER {
+      unsafe { abort() };
+    }
+    let ch = self.reg();
+    ch.config.set_enable(false);
+    reg::GPDMA.inttcclear.set_channel(self.index, true);
+    reg::GPDMA.interrclr.set_channel(self.index, true);
+
+    ch.srcaddr.set_addr(source);
+    ch.destaddr.set_addr(destination);
+    ch.lli.set_addr(0);
+    // Byte wide, single transfers match the FIFO trigger levels of both SSP
+    // and UART.
+    ch.control.ignoring_state()
+      .set_transfer_size(len as u32)
+      .set_src_increment(src_increment)
+      .set_dest_increment(dest_increment)
+      .set_tc_interrupt(true);
+  }
+
+  fn has_callback(&self) -> bool {
+    let crit = NoInterrupts::new();
+    STATE.borrow(&crit).callbacks[self.index].is_some()
+  }
+
+  fn reg(&self) -> &'static reg::Channel {
+    get_reg_ref((CHANNEL_BASE + CHANNEL_STRIDE * self.index)
+                as *const reg::Channel)
+  }
+}
+
+impl dma::Channel for Channel {
+  fn status(&self) -> Option<Result<(), dma::Error>> {
+    if reg::GPDMA.rawinterrstat.channel(self.index) {
+      Some(Err(dma::Error::Bus))
+    } else if reg::GPDMA.enbldchns.channel(self.index) {
+      None
+    } else {
+      Some(Ok(()))
+    }
+  }
+
+  fn set_callback(&self, callback: Option<dma::Callback>) {
+    let crit = NoInterrupts::new();
+    STATE.borrow(&crit).callbacks[self.index] = callback;
+    if callback.is_some() {
+      nvic::enable_irq(Irq::Dma);
+    }
+  }
+}
+
+impl Drop for Channel {
+  fn drop(&mut self) {
+    let crit = NoInterrupts::new();
+    let mut state = STATE.borrow(&crit);
+    self.reg().config.set_enable(false);
+    state.claimed &= !(1 << self.index);
+    state.callbacks[self.index] = None;
+  }
+}
+
+/// Services the GPDMA interrupt, running callbacks of finished channels.
+pub fn isr_handler() {
+  let gpdma = &reg::GPDMA;
+  for index in 0..CHANNEL_COUNT {
+    let result = if gpdma.interrstat.channel(index) {
+      gpdma.interrclr.set_channel(index, true);
+      Err(dma::Error::Bus)
+    } else if gpdma.inttcstat.channel(index) {
+      gpdma.inttcclear.set_channel(index, true);
+      Ok(())
+    } else {
+      continue;
+    };
+
+    let callback = {
+      let crit = NoInterrupts::new();
+      STATE.borrow(&crit).callbacks[index]
+    };
+    match callback {
+      Some(callback) => callback(result),
+      None => (),
+    }
+  }
+}
+
+const CHANNEL_BASE: usize = 0x5000_4100;
+const CHANNEL_STRIDE: usize = 0x20;
+
+const TRANSFER_M2P: u32 = 0b001;
+const TRANSFER_P2M: u32 = 0b010;
+
+mod reg {
+  use volatile_cell::VolatileCell;
+  use core::ops::Drop;
+
+  ioregs!(GPDMA = {
+    0x000 => reg32 intstat {        //! Interrupt status
+      0..7 => channel[8]: ro,
+    },
+    0x004 => reg32 inttcstat {      //! Terminal count interrupt status
+      0..7 => channel[8]: ro,
+    },
+    0x008 => reg32 inttcclear {     //! Terminal count interrupt clear
+      0..7 => channel[8]: wo,
+    },
+    0x00C => reg32 interrstat {     //! Error interrupt status
+      0..7 => channel[8]: ro,
+    },
+    0x010 => reg32 interrclr {      //! Error interrupt clear
+      0..7 => channel[8]: wo,
+    },
+    0x014 => reg32 rawinttcstat {   //! Raw terminal count status
+      0..7 => channel[8]: ro,
+    },
+    0x018 => reg32 rawinterrstat {  //! Raw error status
+      0..7 => channel[8]: ro,
+    },
+    0x01C => reg32 enbldchns {      //! Enabled channels
+      0..7 => channel[8]: ro,
+    },
+    0x030 => reg32 config {         //! Controller configuration
+      0 => e,                       //= Controller enable
+      1 => m,                       //= Big-endian AHB master
+    },
+  });
+
+  ioregs!(Channel = {
+    0x00 => reg32 srcaddr {         //! Source address
+      31..0 => addr,
+    },
+    0x04 => reg32 destaddr {        //! Destination address
+      31..0 => addr,
+    },
+    0x08 => reg32 lli {             //! Linked list item
+      31..0 => addr,
+    },
+    0x0C => reg32 control {         //! Channel control
+      11..0  => transfer_size,
+      14..12 => src_burst,
+      17..15 => dest_burst,
+      20..18 => src_width,
+      23..21 => dest_width,
+      26     => src_increment,
+      27     => dest_increment,
+      31     => tc_interrupt,       //= Terminal count interrupt enable
+    },
+    0x10 => reg32 config {          //! Channel configuration
+      0      => enable,
+      5..1   => src_peripheral,
+      10..6  => dest_peripheral,
+      13..11 => transfer_type,
+      14     => error_interrupt,    //= Error interrupt mask
+      15     => tc_interrupt,       //= Terminal count interrupt mask
+      16     => lock,
+      17     => active: ro,
+      18     => halt,
+    },
+  });
+
+  extern {
+    #[link_name="lpc17xx_iomem_GPDMA"] pub static GPDMA: GPDMA;
+  }
+}
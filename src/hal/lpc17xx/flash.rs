@@ -0,0 +1,178 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/*!
+Flash programming through the in-application programming (IAP) routines of
+the boot ROM.
+
+IAP writes flash in blocks of 256 bytes copied from RAM. Programming bits
+that are already set leaves them unchanged, so words and half-words are
+programmed by writing a block of all ones except for them. The vector table is
+in flash, so interrupts are disabled while IAP runs. IAP also uses the top 32
+bytes of local SRAM, which the stack must not reach.
+*/
+
+use core::mem::transmute;
+use core::result::Result::{self, Ok, Err};
+
+use hal::cortex_m3::irq::NoInterrupts;
+use hal::flash::{self, Error, Sector};
+use hal::lpc17xx::system_clock::system_clock;
+
+/// Entry point of the IAP routines, in thumb mode.
+const IAP_ENTRY: u32 = 0x1FFF_1FF1;
+
+/// IAP command codes.
+const PREPARE_SECTORS: u32 = 50;
+const COPY_RAM_TO_FLASH: u32 = 51;
+const ERASE_SECTORS: u32 = 52;
+
+/// IAP status codes.
+const CMD_SUCCESS: u32 = 0;
+const SRC_ADDR_ERROR: u32 = 2;
+const DST_ADDR_ERROR: u32 = 3;
+const SRC_ADDR_NOT_MAPPED: u32 = 4;
+const DST_ADDR_NOT_MAPPED: u32 = 5;
+const INVALID_SECTOR: u32 = 7;
+
+/// Words in a programming block.
+const BLOCK_WORDS: usize = 64;
+
+/// Size of a programming block.
+const BLOCK_SIZE: u32 = BLOCK_WORDS as u32 * 4;
+
+/// The first 16 sectors are 4KiB, the others 32KiB.
+const SMALL_SECTORS: usize = 16;
+const SMALL_SECTOR_SIZE: u32 = 4 * 1024;
+const LARGE_SECTOR_SIZE: u32 = 32 * 1024;
+
+/// Structure describing the flash.
+#[derive(Clone, Copy)]
+pub struct Flash {
+  size: u32,
+}
+
+impl Flash {
+  /// Returns the flash of a part with `size` bytes of flash, e.g. 512KiB for
+  /// LPC1768.
+  pub fn new(size: u32) -> Flash {
+    Flash { size: size }
+  }
+
+  /// Writes a block at the block-aligned `addr`.
+  fn write_block(&self, addr: u32, block: &[u32; BLOCK_WORDS])
+      -> Result<(), Error> {
+    let sector = try!(flash::Flash::sector_at(self, addr)) as u32;
+    let _crit = NoInterrupts::new();
+    try!(iap(&[PREPARE_SECTORS, sector, sector, 0, 0]));
+    iap(&[COPY_RAM_TO_FLASH, addr, block.as_ptr() as u32, BLOCK_SIZE,
+          system_clock() / 1000])
+  }
+}
+
+impl flash::Flash for Flash {
+  fn sector_count(&self) -> usize {
+    let small = SMALL_SECTOR_SIZE * SMALL_SECTORS as u32;
+    if self.size <= small {
+      (self.size / SMALL_SECTOR_SIZE) as usize
+    } else {
+      SMALL_SECTORS + ((self.size - small) / LARGE_SECTOR_SIZE) as usize
+    }
+  }
+
+  fn sector(&self, index: usize) -> Sector {
+    if index < SMALL_SECTORS {
+      Sector {
+        start: index as u32 * SMALL_SECTOR_SIZE,
+        size: SMALL_SECTOR_SIZE,
+      }
+    } else {
+      Sector {
+        start: SMALL_SECTORS as u32 * SMALL_SECTOR_SIZE +
+            (index - SMALL_SECTORS) as u32 * LARGE_SECTOR_SIZE,
+        size: LARGE_SECTOR_SIZE,
+      }
+    }
+  }
+
+  fn erase(&self, index: usize) -> Result<(), Error> {
+    if index >= flash::Flash::sector_count(self) {
+      return Err(Error::OutOfRange);
+    }
+    let sector = index as u32;
+    let _crit = NoInterrupts::new();
+    try!(iap(&[PREPARE_SECTORS, sector, sector, 0, 0]));
+    iap(&[ERASE_SECTORS, sector, sector, system_clock() / 1000, 0])
+  }
+
+  fn program_word(&self, addr: u32, value: u32) -> Result<(), Error> {
+    if addr & 3 != 0 {
+      return Err(Error::Unaligned);
+    }
+    let start = addr & !(BLOCK_SIZE - 1);
+    let mut block = [0xFFFF_FFFF; BLOCK_WORDS];
+    block[((addr - start) / 4) as usize] = value;
+    self.write_block(start, &block)
+  }
+
+  fn program_half_word(&self, addr: u32, value: u16) -> Result<(), Error> {
+    if addr & 1 != 0 {
+      return Err(Error::Unaligned);
+    }
+    let word = if addr & 2 == 0 {
+      0xFFFF_0000 | value as u32
+    } else {
+      ((value as u32) << 16) | 0xFFFF
+    };
+    flash::Flash::program_word(self, addr & !3, word)
+  }
+
+  /// Programs `data` a block at a time rather than a word at a time.
+  fn program(&self, addr: u32, data: &[u32]) -> Result<(), Error> {
+    if addr & 3 != 0 {
+      return Err(Error::Unaligned);
+    }
+    let end = addr + data.len() as u32 * 4;
+    let mut start = addr & !(BLOCK_SIZE - 1);
+    while start < end {
+      let mut block = [0xFFFF_FFFF; BLOCK_WORDS];
+      for (i, word) in block.iter_mut().enumerate() {
+        let word_addr = start + i as u32 * 4;
+        if word_addr >= addr && word_addr < end {
+          *word = data[((word_addr - addr) / 4) as usize];
+        }
+      }
+      try!(self.write_block(start, &block));
+      start += BLOCK_SIZE;
+    }
+    flash::Flash::verify(self, addr, data)
+  }
+}
+
+/// Runs an IAP command.
+fn iap(command: &[u32; 5]) -> Result<(), Error> {
+  let mut result = [0u32; 5];
+  unsafe {
+    let entry: extern "C" fn(*const u32, *mut u32) = transmute(IAP_ENTRY);
+    entry(command.as_ptr(), result.as_mut_ptr());
+  }
+  match result[0] {
+    CMD_SUCCESS => Ok(()),
+    SRC_ADDR_ERROR | DST_ADDR_ERROR => Err(Error::Unaligned),
+    SRC_ADDR_NOT_MAPPED | DST_ADDR_NOT_MAPPED | INVALID_SECTOR =>
+        Err(Error::OutOfRange),
+    _ => Err(Error::Failed),
+  }
+}
@@ -0,0 +1,261 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/*!
+I2C master for LPC17xx.
+
+The driver polls the SI flag and walks the status codes described in the user
+manual (19.10). Pins must be configured to their SDA/SCL functions separately.
+*/
+
+use core::result::Result;
+use core::result::Result::{Ok, Err};
+
+use hal::i2c;
+use hal::lpc17xx::peripheral_clock::PeripheralClock;
+use hal::lpc17xx::peripheral_clock::PeripheralClock::{I2C0Clock, I2C1Clock,
+    I2C2Clock};
+
+use self::I2CPeripheral::*;
+
+#[path="../../util/wait_for.rs"]
+#[macro_use] mod wait_for;
+
+/// Available I2C peripherals.
+#[allow(missing_docs)]
+#[derive(Clone, Copy)]
+pub enum I2CPeripheral {
+  I2C0,
+  I2C1,
+  I2C2,
+}
+
+impl I2CPeripheral {
+  fn reg(self) -> &'static reg::I2C {
+    match self {
+      I2C0 => &reg::I2C0,
+      I2C1 => &reg::I2C1,
+      I2C2 => &reg::I2C2,
+    }
+  }
+
+  fn peripheral_clock(self) -> PeripheralClock {
+    match self {
+      I2C0 => I2C0Clock,
+      I2C1 => I2C1Clock,
+      I2C2 => I2C2Clock,
+    }
+  }
+}
+
+/// Structure describing an I2C instance.
+#[derive(Clone, Copy)]
+pub struct I2C {
+  reg: &'static reg::I2C,
+}
+
+impl I2C {
+  /// Create and setup an I2C master running at `frequency` Hz.
+  pub fn new(peripheral: I2CPeripheral, frequency: u32) -> I2C {
+    let clock = peripheral.peripheral_clock();
+    let i2c = I2C {
+      reg: peripheral.reg(),
+    };
+
+    clock.enable();
+
+    // SCL is high for SCLH and low for SCLL PCLK cycles, use 50% duty.
+    let half_period = clock.frequency() / frequency / 2;
+    i2c.reg.sclh.set_value(half_period);
+    i2c.reg.scll.set_value(half_period);
+
+    i2c.reg.conclr.set_aac(true).set_sic(true).set_stac(true);
+    i2c.reg.conset.ignoring_state().set_i2en(true);
+
+    i2c
+  }
+
+  fn wait(&self) -> u8 {
+    wait_for!(self.reg.conset.si());
+    self.reg.stat.status() as u8
+  }
+
+  fn start(&self) -> Result<(), i2c::Error> {
+    // SI is cleared after STA is set so that a repeated START is issued if
+    // the interface is still in master mode.
+    self.reg.conset.ignoring_state().set_sta(true);
+    self.reg.conclr.set_sic(true);
+    let status = self.wait();
+    self.reg.conclr.set_stac(true);
+    match status {
+      STATUS_START | STATUS_REPEATED_START => Ok(()),
+      _ => Err(to_error(status)),
+    }
+  }
+
+  fn stop(&self) {
+    self.reg.conset.ignoring_state().set_sto(true);
+    self.reg.conclr.set_sic(true);
+    wait_for!(!self.reg.conset.sto());
+  }
+
+  fn transmit(&self, value: u8, expected: u8) -> Result<(), i2c::Error> {
+    self.reg.dat.set_data(value as u32);
+    self.reg.conclr.set_sic(true);
+    expect(self.wait(), expected)
+  }
+
+  fn receive(&self, ack: bool) -> Result<u8, i2c::Error> {
+    let expected = if ack {
+      self.reg.conset.ignoring_state().set_aa(true);
+      STATUS_DATA_RX_ACK
+    } else {
+      self.reg.conclr.set_aac(true);
+      STATUS_DATA_RX_NACK
+    };
+    self.reg.conclr.set_sic(true);
+    try!(expect(self.wait(), expected));
+    Ok(self.reg.dat.data() as u8)
+  }
+
+  fn write_bytes(&self, address: u8, data: &[u8]) -> Result<(), i2c::Error> {
+    try!(self.start());
+    try!(self.transmit(address << 1, STATUS_SLA_W_ACK));
+    for &b in data.iter() {
+      try!(self.transmit(b, STATUS_DATA_TX_ACK));
+    }
+    Ok(())
+  }
+
+  fn read_bytes(&self, address: u8, buffer: &mut [u8])
+      -> Result<(), i2c::Error> {
+    try!(self.start());
+    try!(self.transmit((address << 1) | 1, STATUS_SLA_R_ACK));
+    let len = buffer.len();
+    for (i, b) in buffer.iter_mut().enumerate() {
+      // NACK the last byte to tell the slave we are done.
+      *b = try!(self.receive(i + 1 != len));
+    }
+    Ok(())
+  }
+}
+
+impl i2c::I2c for I2C {
+  fn write(&self, address: u8, data: &[u8]) -> Result<(), i2c::Error> {
+    if data.is_empty() {
+      return Ok(());
+    }
+    let result = self.write_bytes(address, data);
+    self.stop();
+    result
+  }
+
+  fn read(&self, address: u8, buffer: &mut [u8]) -> Result<(), i2c::Error> {
+    if buffer.is_empty() {
+      return Ok(());
+    }
+    let result = self.read_bytes(address, buffer);
+    self.stop();
+    result
+  }
+
+  fn write_read(&self, address: u8, data: &[u8], buffer: &mut [u8])
+      -> Result<(), i2c::Error> {
+    if data.is_empty() {
+      return i2c::I2c::read(self, address, buffer);
+    }
+    if buffer.is_empty() {
+      return i2c::I2c::write(self, address, data);
+    }
+    let result = match self.write_bytes(address, data) {
+      Ok(()) => self.read_bytes(address, buffer),
+      err => err,
+    };
+    self.stop();
+    result
+  }
+}
+
+fn expect(status: u8, expected: u8) -> Result<(), i2c::Error> {
+  if status == expected {
+    Ok(())
+  } else {
+    Err(to_error(status))
+  }
+}
+
+fn to_error(status: u8) -> i2c::Error {
+  match status {
+    STATUS_SLA_W_NACK | STATUS_SLA_R_NACK => i2c::Error::AddressNack,
+    STATUS_DATA_TX_NACK                   => i2c::Error::DataNack,
+    STATUS_ARBITRATION_LOST               => i2c::Error::ArbitrationLost,
+    _                                     => i2c::Error::Bus,
+  }
+}
+
+const STATUS_START:            u8 = 0x08;
+const STATUS_REPEATED_START:   u8 = 0x10;
+const STATUS_SLA_W_ACK:        u8 = 0x18;
+const STATUS_SLA_W_NACK:       u8 = 0x20;
+const STATUS_DATA_TX_ACK:      u8 = 0x28;
+const STATUS_DATA_TX_NACK:     u8 = 0x30;
+const STATUS_ARBITRATION_LOST: u8 = 0x38;
+const STATUS_SLA_R_ACK:        u8 = 0x40;
+const STATUS_SLA_R_NACK:       u8 = 0x48;
+const STATUS_DATA_RX_ACK:      u8 = 0x50;
+const STATUS_DATA_RX_NACK:     u8 = 0x58;
+
+mod reg {
+  use volatile_cell::VolatileCell;
+  use core::ops::Drop;
+
+  ioregs!(I2C = {
+    0x00 => reg32 conset {  //! Control set register
+      2 => aa,              //= Assert acknowledge flag
+      3 => si,              //= Interrupt flag
+      4 => sto,             //= STOP flag
+      5 => sta,             //= START flag
+      6 => i2en,            //= Interface enable
+    },
+    0x04 => reg32 stat {    //! Status register
+      7..0 => status : ro,
+    },
+    0x08 => reg32 dat {     //! Data register
+      7..0 => data,
+    },
+    0x0C => reg32 adr0 {    //! Slave address register 0
+      0    => gc,
+      7..1 => address,
+    },
+    0x10 => reg32 sclh {    //! SCL high duty cycle register
+      15..0 => value,
+    },
+    0x14 => reg32 scll {    //! SCL low duty cycle register
+      15..0 => value,
+    },
+    0x18 => reg32 conclr {  //! Control clear register
+      2 => aac : wo,        //= Assert acknowledge clear
+      3 => sic : wo,        //= Interrupt clear
+      5 => stac : wo,       //= START flag clear
+      6 => i2enc : wo,      //= Interface disable
+    },
+  });
+
+  extern {
+    #[link_name="lpc17xx_iomem_I2C0"] pub static I2C0: I2C;
+    #[link_name="lpc17xx_iomem_I2C1"] pub static I2C1: I2C;
+    #[link_name="lpc17xx_iomem_I2C2"] pub static I2C2: I2C;
+  }
+}
@@ -0,0 +1,66 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Interrupt lines and NVIC vector table for lpc17xx.
+
+use hal::cortex_m3::nvic::Interrupt;
+
+#[path="../irq_list.rs"]
+#[macro_use] mod irq_list;
+
+irqs! {
+  // s.a. lpc17xx user manual, table 50 (chapter 6.3)
+  Wdt         => isr_wdt,
+  Timer0      => isr_timer_0,
+  Timer1      => isr_timer_1,
+  Timer2      => isr_timer_2,
+  Timer3      => isr_timer_3,
+  Uart0       => isr_uart_0,
+  Uart1       => isr_uart_1,
+  Uart2       => isr_uart_2,
+  Uart3       => isr_uart_3,
+  Pwm1        => isr_pwm_1,
+  I2c0        => isr_i2c_0,
+  I2c1        => isr_i2c_1,
+  I2c2        => isr_i2c_2,
+  Spi         => isr_spi,
+  Ssp0        => isr_ssp_0,
+  Ssp1        => isr_ssp_1,
+  Pll0        => isr_pll_0,
+  Rtc         => isr_rtc,
+  Eint0       => isr_eint_0,
+  Eint1       => isr_eint_1,
+  Eint2       => isr_eint_2,
+  Eint3       => isr_eint_3,
+  Adc         => isr_adc,
+  Bod         => isr_bod,
+  Usb         => isr_usb,
+  Can         => isr_can,
+  Dma         => isr_dma,
+  I2s         => isr_i2s,
+  Enet        => isr_enet,
+  Rit         => isr_rit,
+  Mcpwm       => isr_mcpwm,
+  Qei         => isr_qei,
+  Pll1        => isr_pll_1,
+  UsbActivity => isr_usb_activity,
+  CanActivity => isr_can_activity,
+}
+
+impl Interrupt for Irq {
+  fn number(&self) -> usize {
+    *self as usize
+  }
+}
@@ -17,8 +17,18 @@
 
 pub mod system_clock;
 pub mod peripheral_clock;
+pub mod adc;
+pub mod capture;
+pub mod dac;
+pub mod dma;
+pub mod flash;
+pub mod i2c;
+pub mod irq;
 pub mod pin;
+pub mod power;
 pub mod pwm;
-// pub mod ssp;
+pub mod rtc;
+pub mod ssp;
 pub mod timer;
 pub mod uart;
+pub mod watchdog;
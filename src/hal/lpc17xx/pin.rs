@@ -18,10 +18,20 @@ Pin configuration.
 
 Some pins that could be configured here may be missing from actual MCU depending
 on the package.
+
+Only pins of Port0 and Port2 can raise GPIO interrupts. They share the EINT3
+interrupt, so `isr_handler` must be called from `isr_eint_3`.
 */
 
 use core::intrinsics::abort;
-use core::option::Option;
+use core::option::Option::{self, Some, None};
+use core::result::Result::{self, Ok, Err};
+
+use hal::cortex_m3::irq::NoInterrupts;
+use hal::cortex_m3::nvic;
+use hal::lpc17xx::irq::Irq;
+use hal::pin::{ConfigError, GpioEdge, GpioHandler};
+use util::shared::Shared;
 
 use self::Port::*;
 
@@ -106,11 +116,24 @@ impl Pin {
   }
 
   fn set_mode(&self, mode: Mode) {
-    let (offset, reg) = self.get_pimode_reg_and_offset();
-    let value = reg.value() | (mode as u32) << offset;
+    let (offset, reg) = match self.get_pimode_reg_and_offset() {
+      Some(r) => r,
+      None    => unsafe { abort() },
+    };
+    let value = reg.value() & !(3u32 << offset) | (mode as u32) << offset;
     reg.set_value(value)
   }
 
+  fn odreg(&self) -> &reg::PINMODE_OD {
+    match self.port {
+      Port0 => &reg::PINMODE_OD0,
+      Port1 => &reg::PINMODE_OD1,
+      Port2 => &reg::PINMODE_OD2,
+      Port3 => &reg::PINMODE_OD3,
+      Port4 => &reg::PINMODE_OD4,
+    }
+  }
+
   fn gpioreg(&self) -> &reg::Gpio {
     match self.port {
       Port0 => &reg::GPIO_0,
@@ -148,35 +171,35 @@ impl Pin {
     }
   }
 
-  fn get_pimode_reg_and_offset(&self) -> (u8, &reg::PINMODE) {
+  fn get_pimode_reg_and_offset(&self) -> Option<(u8, &reg::PINMODE)> {
     match self.port {
       Port0 => match self.pin {
-        0...11  => (self.pin*2, &reg::PINMODE0),
-        15      => (self.pin*2, &reg::PINMODE0),
-        16...26 => ((self.pin-16)*2, &reg::PINMODE1),
-        _       => unsafe { abort() },
+        0...11  => Some((self.pin*2, &reg::PINMODE0)),
+        15      => Some((self.pin*2, &reg::PINMODE0)),
+        16...26 => Some(((self.pin-16)*2, &reg::PINMODE1)),
+        _       => None,
       },
       Port1 => match self.pin {
-        0...1   => (self.pin*2, &reg::PINMODE2),
-        4       => (self.pin*2, &reg::PINMODE2),
-        8...10  => (self.pin*2, &reg::PINMODE2),
-        14...15 => (self.pin*2, &reg::PINMODE2),
-        16...31 => ((self.pin-16)*2, &reg::PINMODE3),
-        _      => unsafe { abort() },
+        0...1   => Some((self.pin*2, &reg::PINMODE2)),
+        4       => Some((self.pin*2, &reg::PINMODE2)),
+        8...10  => Some((self.pin*2, &reg::PINMODE2)),
+        14...15 => Some((self.pin*2, &reg::PINMODE2)),
+        16...31 => Some(((self.pin-16)*2, &reg::PINMODE3)),
+        _      => None,
       },
       Port2 => match self.pin {
-        0...13 => (self.pin*2, &reg::PINMODE4),
-        _      => unsafe { abort() },
+        0...13 => Some((self.pin*2, &reg::PINMODE4)),
+        _      => None,
       },
       Port3 => match self.pin {
-        25 => (18, &reg::PINMODE7),
-        26 => (20, &reg::PINMODE7),
-        _  => unsafe { abort() },
+        25 => Some((18, &reg::PINMODE7)),
+        26 => Some((20, &reg::PINMODE7)),
+        _  => None,
       },
       Port4 => match self.pin {
-        28 => (24, &reg::PINMODE9),
-        29 => (26, &reg::PINMODE9),
-        _  => unsafe { abort() },
+        28 => Some((24, &reg::PINMODE9)),
+        29 => Some((26, &reg::PINMODE9)),
+        _  => None,
       },
     }
   }
@@ -301,6 +324,119 @@ impl ::hal::pin::Adc for Pin {
   }
 }
 
+impl ::hal::pin::Electrical for Pin {
+  /// Pins without a PINMODE setting (e.g. the I2C0 pins) have no pull
+  /// resistors.
+  fn set_pull(&self, pull: ::hal::pin::Pull) -> Result<(), ConfigError> {
+    use hal::pin::Pull;
+    if self.get_pimode_reg_and_offset().is_none() {
+      return Err(ConfigError::UnsupportedPull);
+    }
+    self.set_mode(match pull {
+      Pull::Floating => Mode::Floating,
+      Pull::Up       => Mode::PullUp,
+      Pull::Down     => Mode::PullDown,
+    });
+    Ok(())
+  }
+
+  fn set_output_type(&self, output_type: ::hal::pin::OutputType)
+      -> Result<(), ConfigError> {
+    let bit: u32 = 1 << (self.pin as usize);
+    let reg = self.odreg();
+    let val = reg.value() & !bit;
+    reg.set_value(match output_type {
+      ::hal::pin::OutputType::PushPull  => val,
+      ::hal::pin::OutputType::OpenDrain => val | bit,
+    });
+    Ok(())
+  }
+
+  fn set_drive_strength(&self, _strength: ::hal::pin::DriveStrength)
+      -> Result<(), ConfigError> {
+    Err(ConfigError::UnsupportedDriveStrength)
+  }
+
+  fn set_slew_rate(&self, _rate: ::hal::pin::SlewRate)
+      -> Result<(), ConfigError> {
+    Err(ConfigError::UnsupportedSlewRate)
+  }
+}
+
+impl ::hal::pin::GpioInterrupt for Pin {
+  fn set_handler(&self, handler: Option<GpioHandler>) {
+    let index = self.interrupt_bank() * 32 + self.pin as usize;
+    let crit = NoInterrupts::new();
+    HANDLERS.borrow(&crit)[index] = handler;
+  }
+
+  fn enable_interrupt(&self, edge: GpioEdge) {
+    let reg = self.intreg();
+    let pin = self.pin as usize;
+    reg.enr.set_pin(pin, edge != GpioEdge::Falling);
+    reg.enf.set_pin(pin, edge != GpioEdge::Rising);
+    nvic::enable_irq(Irq::Eint3);
+  }
+
+  fn disable_interrupt(&self) {
+    let reg = self.intreg();
+    let pin = self.pin as usize;
+    reg.enr.set_pin(pin, false);
+    reg.enf.set_pin(pin, false);
+  }
+
+  fn clear_interrupt(&self) {
+    self.intreg().clr.set_pin(self.pin as usize, true);
+  }
+}
+
+impl Pin {
+  /// Index of the interrupt registers block of this pin's port.
+  fn interrupt_bank(&self) -> usize {
+    match self.port {
+      Port0 => 0,
+      Port2 => 1,
+      _     => unsafe { abort() },
+    }
+  }
+
+  fn intreg(&self) -> &'static reg::GpioInt {
+    match self.interrupt_bank() {
+      0 => &reg::GPIOINT_0,
+      _ => &reg::GPIOINT_2,
+    }
+  }
+}
+
+static HANDLERS: Shared<[Option<GpioHandler>; 64]> = Shared::new([None; 64]);
+
+/// Services GPIO interrupts of Port0 and Port2, calling handlers of the pins
+/// that triggered.
+pub fn isr_handler() {
+  for bank in 0..2 {
+    let reg = match bank {
+      0 => &reg::GPIOINT_0,
+      _ => &reg::GPIOINT_2,
+    };
+    let pending = reg.statr.pins() | reg.statf.pins();
+    for pin in 0..32 {
+      if pending & (1 << pin) == 0 {
+        continue;
+      }
+      reg.clr.set_pin(pin, true);
+
+      let handler = {
+        let crit = NoInterrupts::new();
+        HANDLERS.borrow(&crit)[bank * 32 + pin]
+      };
+      match handler {
+        Some(handler) => handler(),
+        None => (),
+      }
+    }
+  }
+}
+
 /// Sets the state of trace port interface.
 pub fn set_trace_port_interface_enabled(enabled: bool) {
   let value: u32 = if enabled { 0b1000 } else { 0 };
@@ -309,6 +445,7 @@ pub fn set_trace_port_interface_enabled(enabled: bool) {
 
 mod reg {
   use volatile_cell::VolatileCell;
+  use core::ops::Drop;
 
   ioreg_old!(PINSEL: u32, value);
   reg_rw!(PINSEL, u32, value, set_value, value);
@@ -336,6 +473,16 @@ mod reg {
     #[link_name="lpc17xx_iomem_PINMODE9"] pub static PINMODE9: PINMODE;
   }
 
+  ioreg_old!(PINMODE_OD: u32, value);
+  reg_rw!(PINMODE_OD, u32, value, set_value, value);
+  extern {
+    #[link_name="lpc17xx_iomem_PINMODE_OD0"] pub static PINMODE_OD0: PINMODE_OD;
+    #[link_name="lpc17xx_iomem_PINMODE_OD1"] pub static PINMODE_OD1: PINMODE_OD;
+    #[link_name="lpc17xx_iomem_PINMODE_OD2"] pub static PINMODE_OD2: PINMODE_OD;
+    #[link_name="lpc17xx_iomem_PINMODE_OD3"] pub static PINMODE_OD3: PINMODE_OD;
+    #[link_name="lpc17xx_iomem_PINMODE_OD4"] pub static PINMODE_OD4: PINMODE_OD;
+  }
+
   ioreg_old!(Gpio: u32, FIODIR, _r0, _r1, _r2, FIOMASK, FIOPIN, FIOSET, FIOCLR);
   reg_rw!(Gpio, u32, FIODIR,  set_FIODIR,  FIODIR);
   reg_rw!(Gpio, u32, FIOMASK, set_FIOMASK, FIOMASK);
@@ -352,6 +499,29 @@ mod reg {
   }
 
 
+  ioregs!(GpioInt = {
+    0x00 => reg32 statr {         //! Rising edge interrupt status
+      0..31 => pins: ro,
+    },
+    0x04 => reg32 statf {         //! Falling edge interrupt status
+      0..31 => pins: ro,
+    },
+    0x08 => reg32 clr {           //! Interrupt clear
+      0..31 => pin[32]: wo,
+    },
+    0x0C => reg32 enr {           //! Rising edge interrupt enable
+      0..31 => pin[32],
+    },
+    0x10 => reg32 enf {           //! Falling edge interrupt enable
+      0..31 => pin[32],
+    },
+  });
+
+  extern {
+    #[link_name="lpc17xx_iomem_GPIOINT0"] pub static GPIOINT_0: GpioInt;
+    #[link_name="lpc17xx_iomem_GPIOINT2"] pub static GPIOINT_2: GpioInt;
+  }
+
   ioreg_old!(PCONP: u32, value);
   ioreg_old!(PCLKSEL0: u32, value);
   reg_rw!(PCONP, u32, value, set_value, value);
@@ -22,7 +22,9 @@ use node;
 mod system_clock_pt;
 mod timer_pt;
 mod pin_pt;
+mod pwm_pt;
 mod uart_pt;
+mod watchdog_pt;
 
 mod pinmap;
 
@@ -36,6 +38,8 @@ pub fn attach(builder: &mut Builder, cx: &mut ExtCtxt, node: Rc<node::Node>) {
       "timer" => timer_pt::attach(builder, cx, sub.clone()),
       "uart"  => uart_pt::attach(builder, cx, sub.clone()),
       "gpio"  => pin_pt::attach(builder, cx, sub.clone()),
+      "pwm"   => pwm_pt::attach(builder, cx, sub.clone()),
+      "watchdog" => watchdog_pt::attach(builder, cx, sub.clone()),
       _ => (),
     }
   }
@@ -43,7 +47,8 @@ pub fn attach(builder: &mut Builder, cx: &mut ExtCtxt, node: Rc<node::Node>) {
 
 fn verify(_: &mut Builder, cx: &mut ExtCtxt, node: Rc<node::Node>) {
   node.expect_no_attributes(cx);
-  node.expect_subnodes(cx, &["clock", "timer", "uart", "gpio"]);
+  node.expect_subnodes(cx, &["clock", "timer", "uart", "gpio", "pwm",
+      "watchdog"]);
 }
 
 pub fn add_node_dependency_on_clock(builder: &mut Builder,
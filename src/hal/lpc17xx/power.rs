@@ -0,0 +1,93 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/*!
+Power modes.
+
+`DeepSleep` stops the main oscillator and the PLL, the core wakes up running
+from the internal RC oscillator. Any interrupt wakes it up from `Sleep`, while
+only external, RTC, USB activity or CAN interrupts, and the watchdog, can end
+`DeepSleep` and `power_down`. `Standby` is the deep power-down mode, which is
+only left through a reset or an RTC alarm.
+*/
+
+use hal::cortex_m3::scb;
+use hal::power::Mode;
+use util::support::wfi;
+
+/// Power mode selection for sleep and deep sleep.
+const PM_NORMAL: u32 = 0b00;
+/// Power mode selection for power-down.
+const PM_POWER_DOWN: u32 = 0b01;
+/// Power mode selection for deep power-down.
+const PM_DEEP_POWER_DOWN: u32 = 0b11;
+
+/// Enters `mode` until a wake-up event.
+pub fn enter(mode: Mode) {
+  match mode {
+    Mode::Sleep     => sleep(PM_NORMAL, false),
+    Mode::DeepSleep => sleep(PM_NORMAL, true),
+    Mode::Standby   => sleep(PM_DEEP_POWER_DOWN, true),
+  }
+}
+
+/// Enters power-down, which also powers the flash down, until a wake-up event.
+///
+/// Wake-up takes longer than from `Mode::DeepSleep`, as the flash has to
+/// power up again.
+pub fn power_down() {
+  sleep(PM_POWER_DOWN, true);
+}
+
+/// Returns true if the MCU was reset by waking up from deep power-down.
+pub fn woke_from_standby() -> bool {
+  reg::PCON.pcon.dpdflag()
+}
+
+fn sleep(pm: u32, deep: bool) {
+  // The mode flags are cleared by writing 1, so they're written as 0 while the
+  // brown-out settings are written back as they are.
+  let pcon = reg::PCON.pcon.get();
+  reg::PCON.pcon.ignoring_state()
+    .set_pm(pm)
+    .set_bodrpm(pcon.bodrpm())
+    .set_bogd(pcon.bogd())
+    .set_bord(pcon.bord());
+  scb::set_sleepdeep(deep);
+  wfi();
+  scb::set_sleepdeep(false);
+}
+
+mod reg {
+  use volatile_cell::VolatileCell;
+  use core::ops::Drop;
+
+  ioregs!(PCON = {
+    0x00 => reg32 pcon {            //! Power control
+      1..0   => pm,                 //= Power mode
+      2      => bodrpm,             //= Brown-out reduced power mode
+      3      => bogd,               //= Brown-out global disable
+      4      => bord,               //= Brown-out reset disable
+      8      => smflag,             //= Sleep mode entered
+      9      => dsflag,             //= Deep sleep mode entered
+      10     => pdflag,             //= Power-down mode entered
+      11     => dpdflag,            //= Deep power-down mode entered
+    },
+  });
+
+  extern {
+    #[link_name="lpc17xx_iomem_PCON"] pub static PCON: PCON;
+  }
+}
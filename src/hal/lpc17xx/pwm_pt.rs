@@ -0,0 +1,105 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::rc::Rc;
+use syntax::ext::base::ExtCtxt;
+
+use builder::{Builder, TokenString, add_node_dependency};
+use node;
+
+pub fn attach(builder: &mut Builder, _: &mut ExtCtxt, node: Rc<node::Node>) {
+  node.materializer.set(Some(verify as fn(&mut Builder, &mut ExtCtxt, Rc<node::Node>)));
+  for pwm_node in node.subnodes().iter() {
+    pwm_node.materializer.set(Some(build_pwm as fn(&mut Builder, &mut ExtCtxt, Rc<node::Node>)));
+    add_node_dependency(&node, pwm_node);
+    super::add_node_dependency_on_clock(builder, pwm_node);
+  }
+}
+
+pub fn verify(_: &mut Builder, cx: &mut ExtCtxt, node: Rc<node::Node>) {
+  node.expect_no_attributes(cx);
+}
+
+fn build_pwm(builder: &mut Builder, cx: &mut ExtCtxt, node: Rc<node::Node>) {
+  if !node.expect_attributes(cx, &[("period_us", node::IntAttribute)]) {
+    return
+  }
+
+  if node.name.is_none() {
+    cx.parse_sess().span_diagnostic.span_err(node.name_span,
+        "pwm node must have a name");
+    return
+  }
+
+  let name = TokenString(node.name.clone().unwrap());
+  let period_us = node.get_int_attr("period_us").unwrap() as u32;
+
+  let channel = match node.path.as_str().parse::<usize>() {
+    Ok(index @ 1...6) => TokenString(format!(
+        "zinc::hal::lpc17xx::pwm::PWMChannel::Channel{}", index)),
+    _ => {
+      cx.parse_sess().span_diagnostic.span_err(node.path_span,
+          format!("unknown pwm channel `{}`, allowed channels: 1-6",
+              node.path).as_str());
+      return
+    }
+  };
+
+  node.set_type_name("zinc::hal::lpc17xx::pwm::PWM".to_string());
+
+  let st = quote_stmt!(&*cx,
+      let $name = zinc::hal::lpc17xx::pwm::PWM::new($channel, $period_us);
+  ).unwrap();
+  builder.add_main_statement(st);
+}
+
+#[cfg(test)]
+mod test {
+  use builder::Builder;
+  use test_helpers::{assert_equal_source, with_parsed};
+
+  #[test]
+  fn builds_pwm() {
+    with_parsed("
+      pwm {
+        red@4 {
+          period_us = 20000;
+        }
+      }", |cx, failed, pt| {
+      let mut builder = Builder::new(pt.clone(), cx);
+      super::build_pwm(&mut builder, cx, pt.get_by_name("red").unwrap());
+      assert!(unsafe{*failed} == false);
+      assert!(builder.main_stmts().len() == 1);
+
+      assert_equal_source(&builder.main_stmts()[0],
+          "let red = zinc::hal::lpc17xx::pwm::PWM::new(
+              zinc::hal::lpc17xx::pwm::PWMChannel::Channel4, 20000u32);");
+    });
+  }
+
+  #[test]
+  fn fails_on_reserved_channel() {
+    with_parsed("
+      pwm {
+        red@0 {
+          period_us = 20000;
+        }
+      }", |cx, failed, pt| {
+      let mut builder = Builder::new(pt.clone(), cx);
+      super::build_pwm(&mut builder, cx, pt.get_by_name("red").unwrap());
+      assert!(unsafe{*failed} == true);
+    });
+  }
+}
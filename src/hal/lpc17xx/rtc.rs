@@ -0,0 +1,233 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/*!
+Real-time clock.
+
+The RTC runs from its own 32kHz oscillator, powered from VBAT, and counts the
+calendar fields directly. Alarms compare all fields but the day of the week and
+the day of the year, and `isr_handler` must be called from `isr_rtc`.
+*/
+
+use core::intrinsics::abort;
+use core::option::Option::{self, Some, None};
+
+use hal::cortex_m3::irq::NoInterrupts;
+use hal::cortex_m3::nvic;
+use hal::lpc17xx::irq::Irq;
+use hal::lpc17xx::peripheral_clock::PeripheralClock;
+use hal::rtc::{self, AlarmHandler, DateTime};
+use util::shared::Shared;
+
+/// Alarm mask value that disables all comparisons.
+const ALARM_OFF: u32 = 0xFF;
+
+/// Alarm mask value that ignores the day of week and day of year.
+const ALARM_DATE_TIME: u32 = 0b0011_0000;
+
+static HANDLER: Shared<Option<AlarmHandler>> = Shared::new(None);
+
+/// Structure describing the RTC.
+#[derive(Clone, Copy)]
+pub struct Rtc {
+  reg: &'static reg::RTC,
+}
+
+impl Rtc {
+  /// Powers the RTC interface and starts the clock if it's stopped.
+  ///
+  /// The time is kept if the clock was already running.
+  pub fn new() -> Rtc {
+    PeripheralClock::RTCClock.enable();
+
+    let rtc = Rtc { reg: &reg::RTC };
+    rtc.reg.ccr
+      .set_ctcrst(false)
+      .set_ccalen(true)
+      .set_clken(true);
+    rtc.reg.ciir.set_value(0);
+    rtc
+  }
+}
+
+impl rtc::Rtc for Rtc {
+  fn set_time(&self, time: &DateTime) {
+    if !time.is_valid() {
+      unsafe { abort() };
+    }
+    self.reg.ccr.set_clken(false);
+    self.reg.sec.set_value(time.second as u32);
+    self.reg.min.set_value(time.minute as u32);
+    self.reg.hour.set_value(time.hour as u32);
+    self.reg.dom.set_value(time.day as u32);
+    self.reg.dow.set_value(time.weekday() as u32);
+    self.reg.doy.set_value(time.day_of_year() as u32);
+    self.reg.month.set_value(time.month as u32);
+    self.reg.year.set_value(time.year as u32);
+    // Restarting the clock resets the sub-second counter.
+    self.reg.ccr.set_ctcrst(true);
+    self.reg.ccr.set_ctcrst(false).set_clken(true);
+  }
+
+  fn get_time(&self) -> DateTime {
+    // The consolidated registers can't be read together, so read them again
+    // if the time changed in between.
+    let mut time = self.reg.ctime0.get();
+    let mut date = self.reg.ctime1.get();
+    while self.reg.ctime0.get().sec() != time.sec() {
+      time = self.reg.ctime0.get();
+      date = self.reg.ctime1.get();
+    }
+
+    DateTime {
+      year: date.year() as u16,
+      month: date.month() as u8,
+      day: date.dom() as u8,
+      hour: time.hour() as u8,
+      minute: time.min() as u8,
+      second: time.sec() as u8,
+    }
+  }
+
+  fn set_alarm(&self, time: &DateTime, handler: AlarmHandler) {
+    if !time.is_valid() {
+      unsafe { abort() };
+    }
+    self.reg.amr.set_value(ALARM_OFF);
+    {
+      let crit = NoInterrupts::new();
+      *HANDLER.borrow(&crit) = Some(handler);
+    }
+
+    self.reg.alsec.set_value(time.second as u32);
+    self.reg.almin.set_value(time.minute as u32);
+    self.reg.alhour.set_value(time.hour as u32);
+    self.reg.aldom.set_value(time.day as u32);
+    self.reg.almon.set_value(time.month as u32);
+    self.reg.alyear.set_value(time.year as u32);
+
+    self.reg.ilr.ignoring_state().set_rtcalf(true);
+    self.reg.amr.set_value(ALARM_DATE_TIME);
+    nvic::enable_irq(Irq::Rtc);
+  }
+
+  fn cancel_alarm(&self) {
+    self.reg.amr.set_value(ALARM_OFF);
+    let crit = NoInterrupts::new();
+    *HANDLER.borrow(&crit) = None;
+  }
+}
+
+/// Services the RTC interrupt, calling the alarm handler once.
+pub fn isr_handler() {
+  let rtc = &reg::RTC;
+  if !rtc.ilr.rtcalf() {
+    return;
+  }
+  rtc.ilr.ignoring_state().set_rtcalf(true);
+  rtc.amr.set_value(ALARM_OFF);
+
+  let handler = {
+    let crit = NoInterrupts::new();
+    let mut handler = HANDLER.borrow(&crit);
+    let current = *handler;
+    *handler = None;
+    current
+  };
+  match handler {
+    Some(handler) => handler(),
+    None => (),
+  }
+}
+
+mod reg {
+  use volatile_cell::VolatileCell;
+  use core::ops::Drop;
+
+  ioregs!(RTC = {
+    0x00 => reg32 ilr {             //! Interrupt location
+      0      => rtccif,             //= Counter increment interrupt
+      1      => rtcalf,             //= Alarm interrupt
+    },
+    0x08 => reg32 ccr {             //! Clock control
+      0      => clken,              //= Clock enable
+      1      => ctcrst,             //= Oscillator divider reset
+      4      => ccalen,             //= Calibration counter disable
+    },
+    0x0C => reg32 ciir {            //! Counter increment interrupt
+      7..0   => value,
+    },
+    0x10 => reg32 amr {             //! Alarm mask, set bits are ignored
+      7..0   => value,
+    },
+    0x14 => reg32 ctime0 {          //! Consolidated time
+      5..0   => sec: ro,
+      13..8  => min: ro,
+      20..16 => hour: ro,
+      26..24 => dow: ro,
+    },
+    0x18 => reg32 ctime1 {          //! Consolidated date
+      4..0   => dom: ro,
+      11..8  => month: ro,
+      27..16 => year: ro,
+    },
+    0x20 => reg32 sec {             //! Seconds
+      5..0   => value,
+    },
+    0x24 => reg32 min {             //! Minutes
+      5..0   => value,
+    },
+    0x28 => reg32 hour {            //! Hours
+      4..0   => value,
+    },
+    0x2C => reg32 dom {             //! Day of month
+      4..0   => value,
+    },
+    0x30 => reg32 dow {             //! Day of week
+      2..0   => value,
+    },
+    0x34 => reg32 doy {             //! Day of year
+      8..0   => value,
+    },
+    0x38 => reg32 month {           //! Month
+      3..0   => value,
+    },
+    0x3C => reg32 year {            //! Year
+      11..0  => value,
+    },
+    0x60 => reg32 alsec {           //! Alarm seconds
+      5..0   => value,
+    },
+    0x64 => reg32 almin {           //! Alarm minutes
+      5..0   => value,
+    },
+    0x68 => reg32 alhour {          //! Alarm hours
+      4..0   => value,
+    },
+    0x6C => reg32 aldom {           //! Alarm day of month
+      4..0   => value,
+    },
+    0x78 => reg32 almon {           //! Alarm month
+      3..0   => value,
+    },
+    0x7C => reg32 alyear {          //! Alarm year
+      11..0  => value,
+    },
+  });
+
+  extern {
+    #[link_name="lpc17xx_iomem_RTC"] pub static RTC: RTC;
+  }
+}
@@ -18,21 +18,36 @@ SSP configuration.
 
 Currently supports only SPI mode. Note that `SPI` is not the same peripheral and
 it's currently not supported at all.
+
+Long writes can be offloaded to a GPDMA channel with `write_dma`, or with
+`start_write_dma` and `finish_write_dma` to keep the CPU free meanwhile.
 */
 
 use core::intrinsics::abort;
+use core::result::Result::{self, Ok};
 
-use hal::lpc17xx::peripheral_clock::{PeripheralClock, SSP0Clock, SSP1Clock};
+use hal::dma::{self, Channel};
+use hal::lpc17xx::dma as gpdma;
+use hal::lpc17xx::peripheral_clock::PeripheralClock;
+use hal::lpc17xx::peripheral_clock::PeripheralClock::{SSP0Clock, SSP1Clock};
 use hal::lpc17xx::system_clock::system_clock;
-use hal::pin::PinConf_;
 use hal::spi;
 
-#[path="../../util/ioreg.rs"] mod ioreg;
+use self::SSPPeripheral::*;
+
+#[path="../../util/ioreg.rs"]
+#[macro_use] mod ioreg;
+#[path="../../util/wait_for.rs"]
+#[macro_use] mod wait_for;
+
+/// Depth of SSP transmit and receive FIFOs, in frames.
+const FIFO_DEPTH: usize = 8;
 
 /// SPI configuration.
 ///
-/// This configuration doesn't manage the chip-select pin, it must be configured
-/// and used externally via GPIOConf.
+/// This configuration doesn't manage the pins. MOSI, MISO and SCLK must be set
+/// to their SSP function by the caller, e.g. with `pin::Pin::new`, and the
+/// chip-select pin must be configured and used externally as GPIO.
 pub struct SPIConf {
   /// Peripheral to use, mcu-specific.
   pub peripheral: SSPPeripheral,
@@ -44,13 +59,6 @@ pub struct SPIConf {
   ///
   /// The divisor is currently hardcoded and is equal to 1.
   pub frequency: u32,
-
-  /// MOSI pin to use, supports NotConnected pin.
-  pub mosi: PinConf_,
-  /// MISO pin to use, supports NotConnected pin.
-  pub miso: PinConf_,
-  /// SCLK pin to use, supports NotConnected pin.
-  pub sclk: PinConf_,
 }
 
 impl SPIConf {
@@ -67,25 +75,23 @@ impl SPIConf {
     ssp.set_format(self.bits, self.mode);
     ssp.set_frequency(self.frequency);
 
-    self.mosi.setup();
-    self.miso.setup();
-    self.sclk.setup();
-
     ssp
   }
 }
 
 /// Opaque object that manages the configured peripheral.
-#[allow(dead_code)]
 pub struct SSP {
-  peripheral: SSPPeripheral, // TODO(farcaller): clean up the warning
+  peripheral: SSPPeripheral,
   reg: &'static reg::SSP,
 }
 
+/// Available SSP peripherals.
+#[allow(missing_docs)]
+#[derive(Clone, Copy)]
 pub enum SSPPeripheral {SSP0, SSP1}
 
 impl SSPPeripheral {
-  fn reg(self) -> &reg::SSP {
+  fn reg(self) -> &'static reg::SSP {
     match self {
       SSP0 => &reg::SSP0,
       SSP1 => &reg::SSP1,
@@ -98,6 +104,13 @@ impl SSPPeripheral {
       SSP1 => SSP1Clock,
     }
   }
+
+  fn tx_request(self) -> gpdma::Request {
+    match self {
+      SSP0 => gpdma::Request::SSP0Tx,
+      SSP1 => gpdma::Request::SSP1Tx,
+    }
+  }
 }
 
 impl SSP {
@@ -198,43 +211,134 @@ impl SSP {
 
     (val & 0b10000) == 0
   }
+
+  /// Writes `data` through a DMA channel, blocking until it's sent.
+  ///
+  /// Received frames are dropped, as with `Spi::write_all`.
+  pub fn write_dma(&self, channel: &gpdma::Channel, data: &[u8])
+      -> Result<(), dma::Error> {
+    for chunk in data.chunks(gpdma::MAX_TRANSFER) {
+      unsafe { self.start_write_dma(channel, chunk) };
+      try!(self.finish_write_dma(channel));
+    }
+    Ok(())
+  }
+
+  /// Starts writing `data` through a DMA channel and returns immediately.
+  ///
+  /// `data` can't be longer than `gpdma::MAX_TRANSFER`. The write must be
+  /// completed with `finish_write_dma` before the SSP is used again.
+  ///
+  /// This is unsafe as `data` must stay valid until the transfer completes.
+  pub unsafe fn start_write_dma(&self, channel: &gpdma::Channel, data: &[u8]) {
+    self.reg.set_DMACR(DMACR_TX_ENABLE);
+    channel.memory_to_peripheral(data.as_ptr(), true, data.len(),
+        self.peripheral.tx_request(), self.reg.dr_address());
+  }
+
+  /// Waits for a write started with `start_write_dma` to complete.
+  pub fn finish_write_dma(&self, channel: &gpdma::Channel)
+      -> Result<(), dma::Error> {
+    let result = channel.wait();
+    self.reg.set_DMACR(0);
+    wait_for!(self.written());
+
+    // The RX FIFO has most likely overflown while nobody was reading it.
+    while self.readable() {
+      self.reg.DR();
+    }
+    self.reg.set_ICR(ICR_RX_OVERRUN_CLEAR);
+    result
+  }
 }
 
 impl spi::Spi for SSP {
   fn write(&self, value: u8) {
-    loop { if self.writeable() {
-      break;
-    } }
+    wait_for!(self.writeable());
     self.reg.set_DR(value as u32);
-    loop { if self.written() {
-        break;
-    } }
+    wait_for!(self.written());
   }
 
   fn read(&self) -> u8 {
-    loop {
+    wait_for!(self.readable());
+    (self.reg.DR() & 0xff) as u8
+  }
+
+  fn transfer_in_place(&self, buffer: &mut [u8]) {
+    let len = buffer.len();
+    let mut tx: usize = 0;
+    let mut rx: usize = 0;
+
+    // Keep the TX FIFO fed, but never have more frames in flight than the RX
+    // FIFO can hold, or received data would be lost.
+    while rx < len {
+      if tx < len && tx - rx < FIFO_DEPTH && self.writeable() {
+        self.reg.set_DR(buffer[tx] as u32);
+        tx += 1;
+      }
       if self.readable() {
-        break;
+        buffer[rx] = (self.reg.DR() & 0xff) as u8;
+        rx += 1;
       }
     }
-    (self.reg.DR() & 0xff) as u8
+  }
+
+  fn write_all(&self, data: &[u8]) {
+    for b in data.iter() {
+      wait_for!(self.writeable());
+      self.reg.set_DR(*b as u32);
+      // Drop received frames as we go, so the RX FIFO never overruns.
+      while self.readable() {
+        self.reg.DR();
+      }
+    }
+    wait_for!(self.written());
+    while self.readable() {
+      self.reg.DR();
+    }
+  }
+
+  fn read_into(&self, buffer: &mut [u8]) {
+    for b in buffer.iter_mut() {
+      *b = 0;
+    }
+    self.transfer_in_place(buffer);
+  }
+}
+
+impl spi::SpiDma for SSP {
+  type Channel = gpdma::Channel;
+
+  fn write_dma(&self, channel: &gpdma::Channel, data: &[u8])
+      -> Result<(), dma::Error> {
+    SSP::write_dma(self, channel, data)
   }
 }
 
+const DMACR_TX_ENABLE: u32 = 0b10;
+const ICR_RX_OVERRUN_CLEAR: u32 = 0b01;
+
 mod reg {
   use volatile_cell::VolatileCell;
 
-  ioreg_old!(SSP: u32, CR0, CR1, DR, SR, CPSR, IMSC, RIS, MIS, ICR, DMACR)
-  reg_rw!(SSP, u32, CR0,   set_CR0,   CR0)
-  reg_rw!(SSP, u32, CR1,   set_CR1,   CR1)
-  reg_rw!(SSP, u32, DR,    set_DR,    DR)
-  reg_r!( SSP, u32, SR,               SR)
-  reg_rw!(SSP, u32, CPSR,  set_CPSR,  CPSR)
-  reg_rw!(SSP, u32, IMSC,  set_IMSC,  IMSC)
-  reg_rw!(SSP, u32, RIS,   set_RIS,   RIS)
-  reg_rw!(SSP, u32, MIS,   set_MIS,   MIS)
-  reg_rw!(SSP, u32, ICR,   set_ICR,   ICR)
-  reg_rw!(SSP, u32, DMACR, set_DMACR, DMACR)
+  ioreg_old!(SSP: u32, CR0, CR1, DR, SR, CPSR, IMSC, RIS, MIS, ICR, DMACR);
+  reg_rw!(SSP, u32, CR0,   set_CR0,   CR0);
+  reg_rw!(SSP, u32, CR1,   set_CR1,   CR1);
+  reg_rw!(SSP, u32, DR,    set_DR,    DR);
+  reg_r!( SSP, u32, SR,               SR);
+  reg_rw!(SSP, u32, CPSR,  set_CPSR,  CPSR);
+  reg_rw!(SSP, u32, IMSC,  set_IMSC,  IMSC);
+  reg_rw!(SSP, u32, RIS,   set_RIS,   RIS);
+  reg_rw!(SSP, u32, MIS,   set_MIS,   MIS);
+  reg_rw!(SSP, u32, ICR,   set_ICR,   ICR);
+  reg_rw!(SSP, u32, DMACR, set_DMACR, DMACR);
+
+  impl SSP {
+    /// Address of the data register, as seen by the DMA controller.
+    pub fn dr_address(&self) -> u32 {
+      &self.DR as *const VolatileCell<u32> as u32
+    }
+  }
 
   extern {
     #[link_name="lpc17xx_iomem_SSP0"] pub static SSP0: SSP;
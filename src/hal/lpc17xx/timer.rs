@@ -19,6 +19,8 @@ Timer configuration.
 This code supports all four primary timers of the MCU.
 */
 
+use hal::cortex_m3::nvic;
+use hal::lpc17xx::irq::Irq;
 use hal::timer;
 
 use self::TimerPeripheral::*;
@@ -51,17 +53,18 @@ pub struct TimerConf {
 #[derive(Clone, Copy)]
 pub struct Timer {
   reg: &'static reg::TIMER,
+  irq: Irq,
 }
 
 impl Timer {
   /// Create an start a timer.
   pub fn new(peripheral: TimerPeripheral, counter: u32, divisor: u8) -> Timer {
     use hal::lpc17xx::peripheral_clock::PeripheralClock as Clock;
-    let (clock, reg) = match peripheral {
-      Timer0 => (Clock::TIM0Clock, &reg::TIMER0),
-      Timer1 => (Clock::TIM1Clock, &reg::TIMER1),
-      Timer2 => (Clock::TIM2Clock, &reg::TIMER2),
-      Timer3 => (Clock::TIM3Clock, &reg::TIMER3),
+    let (clock, reg, irq) = match peripheral {
+      Timer0 => (Clock::TIM0Clock, &reg::TIMER0, Irq::Timer0),
+      Timer1 => (Clock::TIM1Clock, &reg::TIMER1, Irq::Timer1),
+      Timer2 => (Clock::TIM2Clock, &reg::TIMER2, Irq::Timer2),
+      Timer3 => (Clock::TIM3Clock, &reg::TIMER3, Irq::Timer3),
     };
 
     clock.enable();
@@ -74,10 +77,40 @@ impl Timer {
 
     Timer {
       reg: reg,
+      irq: irq,
     }
   }
+
+  /// Raises the timer interrupt every `period` ticks.
+  ///
+  /// The counter is reset on each interrupt, so the timer can't be used for
+  /// delays until `stop_periodic_interrupt` is called.
+  pub fn start_periodic_interrupt(&self, period: u32) {
+    self.reg.set_TCR(2);
+    self.reg.set_MR0(period - 1);
+    self.reg.set_MCR(MCR_MR0_INTERRUPT | MCR_MR0_RESET);
+    self.reg.set_IR(IR_MR0);
+    self.reg.set_TCR(1);
+    nvic::enable_irq(self.irq);
+  }
+
+  /// Stops the periodic interrupt, leaving the counter running.
+  pub fn stop_periodic_interrupt(&self) {
+    nvic::disable_irq(self.irq);
+    self.reg.set_MCR(0);
+    self.reg.set_IR(IR_MR0);
+  }
+
+  /// Acknowledges the periodic interrupt, must be called from its ISR.
+  pub fn clear_interrupt(&self) {
+    self.reg.set_IR(IR_MR0);
+  }
 }
 
+const MCR_MR0_INTERRUPT: u32 = 1 << 0;
+const MCR_MR0_RESET: u32 = 1 << 1;
+const IR_MR0: u32 = 1 << 0;
+
 impl timer::Timer for Timer {
   #[inline(always)]
   fn get_counter(&self) -> u32 {
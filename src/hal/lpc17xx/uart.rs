@@ -18,16 +18,32 @@ UART configuration.
 
 This code doesn't support UART1, while it really should (UART1 has more features
 than other UARTs in MCU).
+
+`BufferedUART` is an interrupt-driven alternative to `UART`. It queues data
+in static ring buffers that are serviced by `isr_handler`, which must be
+called from the matching `isr_uart_N`. Platform tree generates the ISR when
+the `uart` node has `interrupt = true`.
+
+`UART` can also move blocks of data with a GPDMA channel, see `write_dma`
+and `read_dma`.
 */
 
 use core::intrinsics::abort;
+use core::option::Option::{Some, None};
+use core::result::Result::{self, Ok, Err};
 
 use hal::lpc17xx::peripheral_clock::PeripheralClock;
 use hal::lpc17xx::peripheral_clock::PeripheralClock::UART0Clock;
 use hal::lpc17xx::peripheral_clock::PeripheralClock::UART2Clock;
 use hal::lpc17xx::peripheral_clock::PeripheralClock::UART3Clock;
-use drivers::chario::CharIO;
+use drivers::chario::{CharIO, CharInput, ReadError};
+use hal::cortex_m3::irq::NoInterrupts;
+use hal::cortex_m3::nvic;
+use hal::dma::{self, Channel};
+use hal::lpc17xx::dma as gpdma;
+use hal::lpc17xx::irq::Irq;
 use hal::uart;
+use util::shared::Shared;
 
 use self::UARTPeripheral::*;
 
@@ -131,6 +147,7 @@ enum FIFOTriggerLevel {
 /// Structure describing a UART instance.
 #[derive(Clone)]
 pub struct UART {
+  peripheral: UARTPeripheral,
   reg: &'static reg::UART,
   clock: PeripheralClock,
 }
@@ -151,6 +168,30 @@ impl UARTPeripheral {
       UART3 => UART3Clock,
     }
   }
+
+  fn buffers(self) -> &'static Shared<uart::Buffers> {
+    match self {
+      UART0 => &UART0Buffers,
+      UART2 => &UART2Buffers,
+      UART3 => &UART3Buffers,
+    }
+  }
+
+  fn irq(self) -> Irq {
+    match self {
+      UART0 => Irq::Uart0,
+      UART2 => Irq::Uart2,
+      UART3 => Irq::Uart3,
+    }
+  }
+
+  fn dma_requests(self) -> (gpdma::Request, gpdma::Request) {
+    match self {
+      UART0 => (gpdma::Request::UART0Tx, gpdma::Request::UART0Rx),
+      UART2 => (gpdma::Request::UART2Tx, gpdma::Request::UART2Rx),
+      UART3 => (gpdma::Request::UART3Tx, gpdma::Request::UART3Rx),
+    }
+  }
 }
 
 impl UART {
@@ -158,6 +199,7 @@ impl UART {
   pub fn new(peripheral: UARTPeripheral, baudrate: u32, word_len: u8,
       parity: uart::Parity, stop_bits: u8) -> UART {
     let uart = UART {
+      peripheral: peripheral,
       reg: peripheral.reg(),
       clock: peripheral.peripheral_clock(),
     };
@@ -166,7 +208,7 @@ impl UART {
     uart.set_baud_rate(baudrate);
     uart.set_mode(WordLen::from_u8(word_len), parity,
         StopBit::from_u8(stop_bits));
-    uart.set_fifo_enabled(true, true);
+    uart.set_fifo_enabled(true, true, false);
 
     uart
   }
@@ -203,7 +245,7 @@ impl UART {
     (*(self.reg)).set_LCR(new_lcr as u32);
   }
 
-  fn set_fifo_enabled(&self, enabled: bool, reset: bool) {
+  fn set_fifo_enabled(&self, enabled: bool, reset: bool, dma: bool) {
     use self::FIFOEnabled::*;
     use self::FIFODmaMode::*;
     use self::FIFOTriggerLevel::*;
@@ -213,11 +255,62 @@ impl UART {
     } | match reset {
       true  => FIFOResetTx & FIFOResetRx,
       false => 0,
-    } | FDDisabled as u8 | FT1char as u8;
+    } | match dma {
+      true  => FDEnabled as u8,
+      false => FDDisabled as u8,
+    } | FT1char as u8;
 
     (*(self.reg)).set_FCR(val as u32);
   }
 
+  /// Writes `data` through a DMA channel, blocking until it's queued.
+  pub fn write_dma(&self, channel: &gpdma::Channel, data: &[u8])
+      -> Result<(), dma::Error> {
+    for chunk in data.chunks(gpdma::MAX_TRANSFER) {
+      unsafe { self.start_write_dma(channel, chunk) };
+      try!(channel.wait());
+    }
+    Ok(())
+  }
+
+  /// Starts writing `data` through a DMA channel and returns immediately.
+  ///
+  /// `data` can't be longer than `gpdma::MAX_TRANSFER`. Completion is
+  /// reported by the channel. The FIFOs are left in DMA mode afterwards.
+  ///
+  /// This is unsafe as `data` must stay valid until the transfer completes.
+  pub unsafe fn start_write_dma(&self, channel: &gpdma::Channel,
+      data: &[u8]) {
+    let (tx, _) = self.peripheral.dma_requests();
+    self.set_fifo_enabled(true, false, true);
+    channel.memory_to_peripheral(data.as_ptr(), true, data.len(), tx,
+        self.reg.data_address());
+  }
+
+  /// Fills `buffer` through a DMA channel, blocking until it's full.
+  pub fn read_dma(&self, channel: &gpdma::Channel, buffer: &mut [u8])
+      -> Result<(), dma::Error> {
+    for chunk in buffer.chunks_mut(gpdma::MAX_TRANSFER) {
+      unsafe { self.start_read_dma(channel, chunk) };
+      try!(channel.wait());
+    }
+    Ok(())
+  }
+
+  /// Starts filling `buffer` through a DMA channel and returns immediately.
+  ///
+  /// `buffer` can't be longer than `gpdma::MAX_TRANSFER`. Completion is
+  /// reported by the channel. The FIFOs are left in DMA mode afterwards.
+  ///
+  /// This is unsafe as `buffer` must stay valid until the transfer completes.
+  pub unsafe fn start_read_dma(&self, channel: &gpdma::Channel,
+      buffer: &mut [u8]) {
+    let (_, rx) = self.peripheral.dma_requests();
+    self.set_fifo_enabled(true, false, true);
+    channel.peripheral_to_memory(rx, self.reg.data_address(),
+        buffer.as_mut_ptr(), buffer.len());
+  }
+
   // TODO(farcaller): license note
   // loosely based on serial_api.c
   // Copyright (c) 2006-2013 ARM Limited
@@ -289,6 +382,147 @@ impl CharIO for UART {
   }
 }
 
+impl CharInput for UART {
+  fn try_getc(&self) -> Result<Option<char>, ReadError> {
+    // Reading LSR clears the error bits.
+    let lsr = self.reg.LSR() as u8;
+    if lsr & (LSRFramingError | LSRParityError) != 0 {
+      // The faulty character is at the top of the FIFO, drop it.
+      self.reg.RBR();
+      if lsr & LSRFramingError != 0 {
+        return Err(ReadError::Framing);
+      } else {
+        return Err(ReadError::Parity);
+      }
+    }
+    if lsr & LSROverrunError != 0 {
+      return Err(ReadError::Overrun);
+    }
+    if lsr & LSRRxDataReady == 0 {
+      return Ok(None);
+    }
+    Ok(Some(self.reg.RBR() as u8 as char))
+  }
+}
+
+/// Interrupt-driven UART.
+///
+/// `putc` only blocks when the transmit buffer is full, so it must not be
+/// called with interrupts disabled.
+pub struct BufferedUART {
+  uart: UART,
+  buffers: &'static Shared<uart::Buffers>,
+}
+
+impl BufferedUART {
+  /// Create and setup a UART, enabling its receive interrupts.
+  pub fn new(peripheral: UARTPeripheral, baudrate: u32, word_len: u8,
+      parity: uart::Parity, stop_bits: u8) -> BufferedUART {
+    let buffered = BufferedUART {
+      uart: UART::new(peripheral, baudrate, word_len, parity, stop_bits),
+      buffers: peripheral.buffers(),
+    };
+
+    {
+      let crit = NoInterrupts::new();
+      let mut buffers = buffered.buffers.borrow(&crit);
+      buffers.tx.clear();
+      buffers.rx.clear();
+      buffers.error = None;
+    }
+
+    buffered.uart.reg.set_IER((IERRxDataAvailable | IERRxLineStatus) as u32);
+    nvic::enable_irq(peripheral.irq());
+
+    buffered
+  }
+}
+
+impl CharIO for BufferedUART {
+  fn putc(&self, value: char) {
+    let reg = self.uart.reg;
+    loop {
+      let crit = NoInterrupts::new();
+      let mut buffers = self.buffers.borrow(&crit);
+      if buffers.tx.is_empty() && reg.LSR() as u8 & LSRTHREmpty != 0 {
+        // Transmitter is idle, no need to involve the ISR.
+        reg.set_THR(value as u32);
+        return;
+      }
+      if buffers.tx.push(value as u8) {
+        reg.set_IER(reg.IER() | IERTHREmpty as u32);
+        return;
+      }
+      // Buffer is full, let the ISR drain it.
+    }
+  }
+}
+
+impl CharInput for BufferedUART {
+  fn try_getc(&self) -> Result<Option<char>, ReadError> {
+    let crit = NoInterrupts::new();
+    let mut buffers = self.buffers.borrow(&crit);
+    match buffers.error.take() {
+      Some(err) => Err(err),
+      None      => Ok(buffers.rx.pop().map(|c| c as char)),
+    }
+  }
+}
+
+/// Services the interrupt of a `BufferedUART`.
+///
+/// Call it from `isr_uart_N` of the corresponding peripheral.
+pub fn isr_handler(peripheral: UARTPeripheral) {
+  let reg = peripheral.reg();
+  let crit = NoInterrupts::new();
+  let mut buffers = peripheral.buffers().borrow(&crit);
+
+  // Reading IIR acknowledges the THRE interrupt, all the other sources are
+  // cleared by servicing LSR and RBR below.
+  reg.IIR();
+
+  loop {
+    let lsr = reg.LSR() as u8;
+    if lsr & (LSRFramingError | LSRParityError) != 0 {
+      reg.RBR();
+      buffers.error = Some(if lsr & LSRFramingError != 0 {
+        ReadError::Framing
+      } else {
+        ReadError::Parity
+      });
+      continue;
+    } else if lsr & LSROverrunError != 0 {
+      buffers.error = Some(ReadError::Overrun);
+    }
+    if lsr & LSRRxDataReady == 0 {
+      break;
+    }
+    let c = reg.RBR() as u8;
+    if !buffers.rx.push(c) {
+      buffers.error = Some(ReadError::Overrun);
+    }
+  }
+
+  if reg.LSR() as u8 & LSRTHREmpty != 0 {
+    for _ in 0..TxFIFODepth {
+      match buffers.tx.pop() {
+        Some(c) => reg.set_THR(c as u32),
+        None    => break,
+      }
+    }
+    if buffers.tx.is_empty() {
+      reg.set_IER(reg.IER() & !(IERTHREmpty as u32));
+    }
+  }
+}
+
+#[allow(non_upper_case_globals)]
+static UART0Buffers: Shared<uart::Buffers> = Shared::new(uart::Buffers::new());
+#[allow(non_upper_case_globals)]
+static UART2Buffers: Shared<uart::Buffers> = Shared::new(uart::Buffers::new());
+#[allow(non_upper_case_globals)]
+static UART3Buffers: Shared<uart::Buffers> = Shared::new(uart::Buffers::new());
+
 #[allow(non_upper_case_globals)]
 static FIFOResetRx: u8 = 0b1_0;
 #[allow(non_upper_case_globals)]
@@ -297,9 +531,27 @@ static FIFOResetTx: u8 = 0b1_0_0;
 #[allow(non_upper_case_globals)]
 static LCRModeMask: u8 = 0b1_11_1_1_11;
 
+#[allow(non_upper_case_globals)]
+static LSRRxDataReady: u8 = 0x01;
+#[allow(non_upper_case_globals)]
+static LSROverrunError: u8 = 0x02;
+#[allow(non_upper_case_globals)]
+static LSRParityError: u8 = 0x04;
+#[allow(non_upper_case_globals)]
+static LSRFramingError: u8 = 0x08;
 #[allow(non_upper_case_globals)]
 static LSRTHREmpty: u8 = 0x20;
 
+#[allow(non_upper_case_globals)]
+static IERRxDataAvailable: u8 = 0x01;
+#[allow(non_upper_case_globals)]
+static IERTHREmpty: u8 = 0x02;
+#[allow(non_upper_case_globals)]
+static IERRxLineStatus: u8 = 0x04;
+
+#[allow(non_upper_case_globals)]
+static TxFIFODepth: usize = 16;
+
 mod reg {
   use volatile_cell::VolatileCell;
 
@@ -319,6 +571,13 @@ mod reg {
   reg_rw!(UART, u32, FDR, set_FDR, FDR);
   reg_rw!(UART, u32, TER, set_TER, TER);
 
+  impl UART {
+    /// Address of RBR/THR, as seen by the DMA controller.
+    pub fn data_address(&self) -> u32 {
+      &self.RBR_THR_DLL as *const VolatileCell<u32> as u32
+    }
+  }
+
   extern {
     #[link_name="lpc17xx_iomem_UART0"] pub static UART0: UART;
     #[link_name="lpc17xx_iomem_UART2"] pub static UART2: UART;
@@ -95,11 +95,15 @@ pub fn build_uart(builder: &mut Builder, cx: &mut ExtCtxt,
       }.to_string());
   let stop_bits = mode.as_str().chars().nth(2).unwrap().to_digit(10).unwrap() as u8;
 
-  sub.set_type_name("zinc::hal::lpc17xx::uart::UART".to_string());
+  let interrupt = sub.get_bool_attr("interrupt").unwrap_or(false);
+  let type_name = if interrupt { "BufferedUART" } else { "UART" };
+  let uart_type = TokenString(type_name.to_string());
+
+  sub.set_type_name(format!("zinc::hal::lpc17xx::uart::{}", type_name));
   let uart_name = TokenString(sub.name.clone().unwrap());
 
   let st = quote_stmt!(&*cx,
-      let $uart_name = zinc::hal::lpc17xx::uart::UART::new(
+      let $uart_name = zinc::hal::lpc17xx::uart::$uart_type::new(
           zinc::hal::lpc17xx::uart::$uart_peripheral,
           $baud_rate,
           $word_len,
@@ -107,6 +111,18 @@ pub fn build_uart(builder: &mut Builder, cx: &mut ExtCtxt,
           $stop_bits)
   ).unwrap();
   builder.add_main_statement(st);
+
+  if interrupt {
+    let isr_name = TokenString(format!("isr_uart_{}", sub.path));
+    let isr = quote_item!(&*cx,
+        #[no_mangle]
+        pub unsafe extern fn $isr_name() {
+          zinc::hal::lpc17xx::uart::isr_handler(
+              zinc::hal::lpc17xx::uart::$uart_peripheral);
+        }
+    ).unwrap();
+    builder.add_type_item((*isr).clone());
+  }
 }
 
 pub fn build_uart_gpio(builder: &Builder, uart_idx: usize, name: &str,
@@ -122,8 +138,9 @@ pub fn build_uart_gpio(builder: &Builder, uart_idx: usize, name: &str,
 
 #[cfg(test)]
 mod test {
+  use std::ops::Deref;
   use builder::Builder;
-  use test_helpers::{assert_equal_source, with_parsed};
+  use test_helpers::{assert_equal_items, assert_equal_source, with_parsed};
 
   #[test]
   fn builds_uart() {
@@ -169,4 +186,53 @@ mod test {
       assert!(rx_node.get_string_attr("function").unwrap() == "rxd0".to_string());
     });
   }
+
+  #[test]
+  fn builds_interrupt_uart() {
+    with_parsed("
+      mcu {
+        clock {
+          system_frequency = 100_000_000;
+        }
+      }
+      uart {
+        uart@2 {
+          baud_rate = 9600;
+          mode = \"8N1\";
+          interrupt = true;
+          tx = &uart_tx;
+          rx = &uart_rx;
+        }
+      }
+      gpio {
+        uart_tx@0;
+        uart_rx@1;
+      }
+      ", |cx, failed, pt| {
+      let mut builder = Builder::new(pt.clone(), cx);
+      super::build_uart(&mut builder, cx, pt.get_by_name("uart").unwrap());
+      assert!(unsafe{*failed} == false);
+      assert!(builder.main_stmts().len() == 1);
+
+      assert_equal_source(&builder.main_stmts()[0],
+          "let uart = zinc::hal::lpc17xx::uart::BufferedUART::new(
+               zinc::hal::lpc17xx::uart::UARTPeripheral::UART2,
+               9600u32,
+               8u8,
+               zinc::hal::uart::Parity::Disabled,
+               1u8);");
+
+      let items = builder.emit_items(cx);
+      assert_equal_items(items[0].deref(), "
+          #[allow(non_camel_case_types)]
+          mod pt {
+            use zinc;
+            #[no_mangle]
+            pub unsafe extern \"C\" fn isr_uart_2() {
+              zinc::hal::lpc17xx::uart::isr_handler(
+                  zinc::hal::lpc17xx::uart::UARTPeripheral::UART2);
+            }
+          }");
+    });
+  }
 }
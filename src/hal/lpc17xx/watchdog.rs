@@ -0,0 +1,111 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/*!
+Watchdog timer.
+
+The WDT is clocked from the 4MHz internal RC oscillator, which it divides by 4.
+Once started, it can't be stopped until the next reset.
+*/
+
+use core::intrinsics::abort;
+use core::option::Option::Some;
+
+use hal::cortex_m3::irq::NoInterrupts;
+use hal::watchdog;
+
+/// Watchdog ticks per millisecond.
+const TICKS_PER_MS: u32 = 1000;
+
+/// Smallest timeout value.
+const MIN_TICKS: u32 = 0xFF;
+
+/// Watchdog clock selection of the internal RC oscillator.
+const CLKSEL_IRC: u32 = 0;
+
+/// Structure describing the watchdog.
+#[derive(Clone, Copy)]
+pub struct Watchdog {
+  reg: &'static reg::WDT,
+}
+
+impl Watchdog {
+  /// Returns the watchdog, which is left as is.
+  pub fn new() -> Watchdog {
+    Watchdog { reg: &reg::WDT }
+  }
+}
+
+impl watchdog::Watchdog for Watchdog {
+  fn start(&self, timeout_ms: u32) {
+    let ticks = match timeout_ms.checked_mul(TICKS_PER_MS) {
+      Some(ticks) if ticks >= MIN_TICKS => ticks,
+      _ => unsafe { abort() },
+    };
+
+    self.reg.wdclksel.set_wdsel(CLKSEL_IRC);
+    self.reg.wdtc.set_count(ticks);
+    self.reg.wdmod.set_wden(true).set_wdreset(true);
+    // The watchdog only starts counting after a first feed.
+    self.feed();
+  }
+
+  fn feed(&self) {
+    // Any other WDT access within the sequence causes a reset, so interrupts
+    // must not touch the watchdog between the two writes.
+    let _crit = NoInterrupts::new();
+    self.reg.wdfeed.ignoring_state().set_feed(0xAA);
+    self.reg.wdfeed.ignoring_state().set_feed(0x55);
+  }
+
+  fn caused_reset(&self) -> bool {
+    self.reg.wdmod.wdtof()
+  }
+
+  fn clear_reset_flags(&self) {
+    self.reg.wdmod.set_wdtof(false);
+  }
+}
+
+mod reg {
+  use volatile_cell::VolatileCell;
+  use core::ops::Drop;
+
+  ioregs!(WDT = {
+    0x00 => reg32 wdmod {           //! Mode register
+      0      => wden,               //= Watchdog enable, set only
+      1      => wdreset,            //= Reset on timeout, set only
+      2      => wdtof,              //= Timeout flag
+      3      => wdint,              //= Warning interrupt flag
+    },
+    0x04 => reg32 wdtc {            //! Timer constant
+      31..0  => count,
+    },
+    0x08 => reg32 wdfeed {          //! Feed sequence
+      7..0   => feed: wo,
+    },
+    0x0C => reg32 wdtv {            //! Timer value
+      31..0  => value: ro,
+    },
+    0x10 => reg32 wdclksel {        //! Clock source selection
+      1..0   => wdsel,
+      31     => wdlock,             //= Lock the clock source
+    },
+  });
+
+  extern {
+    #[link_name="lpc17xx_iomem_WDT"] pub static WDT: WDT;
+  }
+}
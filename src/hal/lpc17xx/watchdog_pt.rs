@@ -0,0 +1,140 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::rc::Rc;
+use syntax::ext::base::ExtCtxt;
+
+use builder::{Builder, TokenString};
+use node;
+
+/// Longest timeout, in milliseconds, whose count fits the 32-bit WDTC at the
+/// 1MHz watchdog tick.
+const MAX_TIMEOUT_MS: usize = 0xFFFFFFFF / 1000;
+
+pub fn attach(_: &mut Builder, _: &mut ExtCtxt, node: Rc<node::Node>) {
+  node.materializer.set(Some(build_watchdog as fn(&mut Builder, &mut ExtCtxt, Rc<node::Node>)));
+}
+
+fn build_watchdog(builder: &mut Builder, cx: &mut ExtCtxt,
+    node: Rc<node::Node>) {
+  if !node.expect_no_subnodes(cx) ||
+      !node.expect_attributes(cx, &[("timeout_ms", node::IntAttribute)]) {
+    return
+  }
+
+  let timeout_ms = node.get_int_attr("timeout_ms").unwrap();
+  if timeout_ms == 0 || timeout_ms > MAX_TIMEOUT_MS {
+    cx.parse_sess().span_diagnostic.span_err(
+        node.get_attr("timeout_ms").value_span,
+        format!("`timeout_ms` must be between 1 and {}",
+                MAX_TIMEOUT_MS).as_str());
+    return
+  }
+  let timeout_ms = timeout_ms as u32;
+
+  // A named watchdog can be passed to tasks, which have to feed it.
+  match node.name {
+    Some(ref name) => {
+      let name = TokenString(name.clone());
+      node.set_type_name("zinc::hal::lpc17xx::watchdog::Watchdog".to_string());
+
+      let st = quote_stmt!(&*cx,
+          let $name = zinc::hal::lpc17xx::watchdog::Watchdog::new();
+      ).unwrap();
+      builder.add_main_statement(st);
+      let st = quote_stmt!(&*cx,
+          zinc::hal::watchdog::Watchdog::start(&$name, $timeout_ms);
+      ).unwrap();
+      builder.add_main_statement(st);
+    },
+    None => {
+      let st = quote_stmt!(&*cx,
+          zinc::hal::watchdog::Watchdog::start(
+              &zinc::hal::lpc17xx::watchdog::Watchdog::new(), $timeout_ms);
+      ).unwrap();
+      builder.add_main_statement(st);
+    },
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use builder::Builder;
+  use test_helpers::{assert_equal_source, with_parsed};
+
+  #[test]
+  fn builds_watchdog() {
+    with_parsed("
+      watchdog {
+        timeout_ms = 500;
+      }", |cx, failed, pt| {
+      let mut builder = Builder::new(pt.clone(), cx);
+      super::build_watchdog(&mut builder, cx,
+          pt.get_by_path("watchdog").unwrap());
+      assert!(unsafe{*failed} == false);
+      assert!(builder.main_stmts().len() == 1);
+
+      assert_equal_source(&builder.main_stmts()[0],
+          "zinc::hal::watchdog::Watchdog::start(
+              &zinc::hal::lpc17xx::watchdog::Watchdog::new(), 500u32);");
+    });
+  }
+
+  #[test]
+  fn builds_named_watchdog() {
+    with_parsed("
+      wdt@watchdog {
+        timeout_ms = 500;
+      }", |cx, failed, pt| {
+      let mut builder = Builder::new(pt.clone(), cx);
+      super::build_watchdog(&mut builder, cx, pt.get_by_name("wdt").unwrap());
+      assert!(unsafe{*failed} == false);
+      assert!(builder.main_stmts().len() == 2);
+
+      assert_equal_source(&builder.main_stmts()[0],
+          "let wdt = zinc::hal::lpc17xx::watchdog::Watchdog::new();");
+      assert_equal_source(&builder.main_stmts()[1],
+          "zinc::hal::watchdog::Watchdog::start(&wdt, 500u32);");
+    });
+  }
+
+  #[test]
+  fn fails_to_build_watchdog_with_zero_timeout() {
+    with_parsed("
+      watchdog {
+        timeout_ms = 0;
+      }", |cx, failed, pt| {
+      let mut builder = Builder::new(pt.clone(), cx);
+      super::build_watchdog(&mut builder, cx,
+          pt.get_by_path("watchdog").unwrap());
+      assert!(unsafe{*failed} == true);
+      assert!(builder.main_stmts().len() == 0);
+    });
+  }
+
+  #[test]
+  fn fails_to_build_watchdog_with_too_long_timeout() {
+    with_parsed("
+      watchdog {
+        timeout_ms = 5000000;
+      }", |cx, failed, pt| {
+      let mut builder = Builder::new(pt.clone(), cx);
+      super::build_watchdog(&mut builder, cx,
+          pt.get_by_path("watchdog").unwrap());
+      assert!(unsafe{*failed} == true);
+      assert!(builder.main_stmts().len() == 0);
+    });
+  }
+}
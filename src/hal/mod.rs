@@ -35,6 +35,11 @@ pub mod lpc11xx;
           feature = "cpu_cortex-m4",
           feature = "cpu_cortex-m7"))]
 mod cortex_common;
+#[cfg(any(feature = "mcu_stm32f1",
+          feature = "mcu_stm32f4",
+          feature = "mcu_stm32f7",
+          feature = "mcu_stm32l1"))]
+mod stm32_common;
 #[cfg(feature = "cpu_cortex-m3")]
 pub mod cortex_m3;
 #[cfg(feature = "cpu_cortex-m4")]
@@ -42,13 +47,22 @@ pub mod cortex_m4;
 #[cfg(feature = "cpu_cortex-m7")]
 pub mod cortex_m7;
 
+pub mod adc;
+pub mod capture;
+pub mod dac;
+pub mod dma;
+pub mod flash;
+pub mod i2c;
 pub mod mem_init;
 pub mod pin;
+pub mod power;
 pub mod pwm;
+pub mod rtc;
 pub mod spi;
 pub mod stack;
 pub mod timer;
 pub mod uart;
+pub mod watchdog;
 
 #[cfg(target_os = "none")]
 pub mod isr;
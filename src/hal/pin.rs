@@ -15,6 +15,9 @@
 
 //! Common definitions for pin HAL.
 
+use core::option::Option;
+use core::result::Result;
+
 pub use self::GpioDirection::*;
 pub use self::GpioLevel::*;
 
@@ -52,6 +55,115 @@ pub trait Gpio {
   fn set_direction(&self, new_mode: GpioDirection);
 }
 
+/// Pull resistor configuration.
+#[derive(PartialEq, Clone, Copy)]
+pub enum Pull {
+  /// No pull resistor, the pin floats when not driven.
+  Floating,
+  /// Pull-up resistor.
+  Up,
+  /// Pull-down resistor.
+  Down,
+}
+
+/// Output driver type.
+#[derive(PartialEq, Clone, Copy)]
+pub enum OutputType {
+  /// Pin is driven both high and low.
+  PushPull,
+  /// Pin is only driven low, and left floating when high.
+  OpenDrain,
+}
+
+/// Output drive strength.
+///
+/// Parts with two strengths only support `Low` and `High`.
+#[derive(PartialEq, Clone, Copy)]
+pub enum DriveStrength {
+  /// Weakest drive available.
+  Low,
+  /// Intermediate drive.
+  Medium,
+  /// Strongest drive available.
+  High,
+}
+
+/// Output slew rate.
+#[derive(PartialEq, Clone, Copy)]
+pub enum SlewRate {
+  /// Slowest edges available, reduces EMI.
+  Slow,
+  /// Fastest edges available.
+  Fast,
+}
+
+/// Errors returned for electrical options a pin doesn't support.
+#[derive(PartialEq, Clone, Copy)]
+pub enum ConfigError {
+  /// The pull resistor setting is not available on this pin or in its
+  /// current mode.
+  UnsupportedPull,
+  /// The output type is not available on this pin or in its current mode.
+  UnsupportedOutputType,
+  /// The drive strength is not available on this pin.
+  UnsupportedDriveStrength,
+  /// The slew rate is not configurable on this pin.
+  UnsupportedSlewRate,
+}
+
+/// Electrical configuration of a pin.
+///
+/// Each setting only touches its own bits, so they can be applied in any
+/// order after the pin is set up by its MCU-specific constructor. Settings
+/// the part can't provide are rejected without changing the pin.
+pub trait Electrical {
+  /// Set the pull resistor.
+  fn set_pull(&self, pull: Pull) -> Result<(), ConfigError>;
+
+  /// Set the output driver type.
+  fn set_output_type(&self, output_type: OutputType) -> Result<(), ConfigError>;
+
+  /// Set the output drive strength.
+  fn set_drive_strength(&self, strength: DriveStrength)
+      -> Result<(), ConfigError>;
+
+  /// Set the output slew rate.
+  fn set_slew_rate(&self, rate: SlewRate) -> Result<(), ConfigError>;
+}
+
+/// Edges that trigger a GPIO interrupt.
+#[derive(PartialEq, Clone, Copy)]
+pub enum GpioEdge {
+  /// Low to high transition.
+  Rising,
+  /// High to low transition.
+  Falling,
+  /// Any transition.
+  Both,
+}
+
+/// GPIO interrupt handler, called from the port ISR.
+pub type GpioHandler = fn();
+
+/// General Purpose I/O that can raise interrupts on edges.
+///
+/// Each MCU provides an `isr_handler` to be called from the port interrupt.
+/// It clears the pending interrupts and dispatches them to the handlers set
+/// with `set_handler`.
+pub trait GpioInterrupt : Gpio {
+  /// Set the handler to be called when the interrupt fires.
+  fn set_handler(&self, handler: Option<GpioHandler>);
+
+  /// Enable the interrupt on the given edge.
+  fn enable_interrupt(&self, edge: GpioEdge);
+
+  /// Disable the interrupt.
+  fn disable_interrupt(&self);
+
+  /// Clear a pending interrupt.
+  fn clear_interrupt(&self);
+}
+
 /// Analog Input
 pub trait Adc {
   /// Read analog input value
@@ -0,0 +1,38 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/*!
+Low power modes.
+
+Each MCU provides a `power` module with an `enter` function that puts the core
+to sleep until a wake-up event. The generic modes are mapped to the closest
+MCU-specific ones, see the MCU modules for which events wake them up.
+
+Most MCUs stop their PLL in `DeepSleep`, so clocks have to be configured
+again after waking up. Waking up from `Standby` resets the MCU.
+*/
+
+/// Low power modes, from the lightest to the deepest.
+#[derive(PartialEq, Clone, Copy)]
+pub enum Mode {
+  /// The core clock is stopped, peripherals keep running and any interrupt
+  /// wakes the core up.
+  Sleep,
+  /// Most clocks are stopped, RAM and registers are kept. Only a few
+  /// peripherals, like external interrupts or the RTC, can wake the core up.
+  DeepSleep,
+  /// The core is powered off, RAM and registers are lost.
+  Standby,
+}
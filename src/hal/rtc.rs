@@ -0,0 +1,261 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/*!
+Real-time clock interface.
+
+RTCs are MCU-specific structs that implement the `Rtc` trait. They keep the
+time of day in a battery-backed domain, so the time survives resets and
+`new()` only starts the clock if it isn't running yet.
+
+Times are in UTC and use the Gregorian calendar. Epoch conversions count the
+seconds since 1970-01-01 00:00:00, which fits a `u32` until 2106, so valid dates
+are limited to the years 1970 to 2105. Drivers abort on invalid dates, or on
+years their calendar can't hold.
+*/
+
+/// Calendar date and time of day.
+#[derive(PartialEq, Clone, Copy)]
+pub struct DateTime {
+  /// Full year, e.g. 2015.
+  pub year: u16,
+  /// Month, 1 to 12.
+  pub month: u8,
+  /// Day of the month, 1 to 31.
+  pub day: u8,
+  /// Hour, 0 to 23.
+  pub hour: u8,
+  /// Minute, 0 to 59.
+  pub minute: u8,
+  /// Second, 0 to 59.
+  pub second: u8,
+}
+
+/// First year of a valid date.
+pub const MIN_YEAR: u16 = 1970;
+
+/// Last year of a valid date, the last full year of the `u32` epoch.
+pub const MAX_YEAR: u16 = 2105;
+
+/// Days from 1970-01-01 to 0000-03-01, the start of the shifted calendar
+/// used by the conversions.
+const EPOCH_SHIFT: u32 = 719468;
+
+/// Days in a 400 years cycle.
+const DAYS_PER_ERA: u32 = 146097;
+
+const SECONDS_PER_DAY: u32 = 86400;
+
+fn is_leap_year(year: u16) -> bool {
+  (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+  match month {
+    2 => if is_leap_year(year) { 29 } else { 28 },
+    4 | 6 | 9 | 11 => 30,
+    _ => 31,
+  }
+}
+
+impl DateTime {
+  /// Returns true if all fields are in range and the date exists.
+  pub fn is_valid(&self) -> bool {
+    self.year >= MIN_YEAR && self.year <= MAX_YEAR &&
+      self.month >= 1 && self.month <= 12 &&
+      self.day >= 1 && self.day <= days_in_month(self.year, self.month) &&
+      self.hour < 24 && self.minute < 60 && self.second < 60
+  }
+
+  /// Converts seconds since the epoch to a date and time.
+  pub fn from_epoch(seconds: u32) -> DateTime {
+    let days = seconds / SECONDS_PER_DAY;
+    let time = seconds % SECONDS_PER_DAY;
+
+    // The year is shifted to start in March, so that the leap day is the last
+    // day of the year.
+    let shifted = days + EPOCH_SHIFT;
+    let era = shifted / DAYS_PER_ERA;
+    let day_of_era = shifted % DAYS_PER_ERA;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 -
+                       day_of_era / 146096) / 365;
+    let day_of_year = day_of_era -
+        (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+      month_index + 3
+    } else {
+      month_index - 9
+    };
+    let year = era * 400 + year_of_era + if month <= 2 { 1 } else { 0 };
+
+    DateTime {
+      year: year as u16,
+      month: month as u8,
+      day: day as u8,
+      hour: (time / 3600) as u8,
+      minute: (time / 60 % 60) as u8,
+      second: (time % 60) as u8,
+    }
+  }
+
+  /// Converts the date and time to seconds since the epoch.
+  pub fn to_epoch(&self) -> u32 {
+    self.days_since_epoch() * SECONDS_PER_DAY + self.hour as u32 * 3600 +
+      self.minute as u32 * 60 + self.second as u32
+  }
+
+  /// Returns the day of the week, 0 being Sunday.
+  pub fn weekday(&self) -> u8 {
+    // 1970-01-01 was a Thursday.
+    ((self.days_since_epoch() + 4) % 7) as u8
+  }
+
+  /// Returns the day of the year, 1 to 366.
+  pub fn day_of_year(&self) -> u16 {
+    let mut days = self.day as u16;
+    for month in 1..self.month {
+      days += days_in_month(self.year, month) as u16;
+    }
+    days
+  }
+
+  fn days_since_epoch(&self) -> u32 {
+    let month = self.month as u32;
+    let year = self.year as u32 - if month <= 2 { 1 } else { 0 };
+    let era = year / 400;
+    let year_of_era = year % 400;
+    let shifted_month = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * shifted_month + 2) / 5 + self.day as u32 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 +
+        day_of_year;
+    era * DAYS_PER_ERA + day_of_era - EPOCH_SHIFT
+  }
+}
+
+/// Alarm handler, called from the RTC ISR.
+pub type AlarmHandler = fn();
+
+/// Real-time clock trait.
+pub trait Rtc {
+  /// Sets the current date and time.
+  ///
+  /// Aborts if `time` isn't valid or out of the range of the RTC.
+  fn set_time(&self, time: &DateTime);
+
+  /// Returns the current date and time.
+  fn get_time(&self) -> DateTime;
+
+  /// Calls `handler` once when the clock reaches `time`, replacing any
+  /// pending alarm.
+  ///
+  /// Aborts if `time` isn't valid or out of the range of the RTC. The RTC
+  /// interrupt must be routed to the driver's `isr_handler`.
+  fn set_alarm(&self, time: &DateTime, handler: AlarmHandler);
+
+  /// Cancels the pending alarm, if any.
+  fn cancel_alarm(&self);
+
+  /// Sets the current time in seconds since the epoch.
+  fn set_epoch(&self, seconds: u32) {
+    self.set_time(&DateTime::from_epoch(seconds));
+  }
+
+  /// Returns the current time in seconds since the epoch.
+  fn get_epoch(&self) -> u32 {
+    self.get_time().to_epoch()
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::DateTime;
+
+  fn date(year: u16, month: u8, day: u8, hour: u8, minute: u8, second: u8)
+      -> DateTime {
+    DateTime {
+      year: year,
+      month: month,
+      day: day,
+      hour: hour,
+      minute: minute,
+      second: second,
+    }
+  }
+
+  #[test]
+  fn converts_from_epoch() {
+    assert!(DateTime::from_epoch(0) == date(1970, 1, 1, 0, 0, 0));
+    assert!(DateTime::from_epoch(1445171696) == date(2015, 10, 18, 12, 34, 56));
+    assert!(DateTime::from_epoch(1456790399) == date(2016, 2, 29, 23, 59, 59));
+    assert!(DateTime::from_epoch(951868800) == date(2000, 3, 1, 0, 0, 0));
+    assert!(DateTime::from_epoch(0xffffffff) == date(2106, 2, 7, 6, 28, 15));
+  }
+
+  #[test]
+  fn converts_to_epoch() {
+    assert!(date(1970, 1, 1, 0, 0, 0).to_epoch() == 0);
+    assert!(date(2015, 10, 18, 12, 34, 56).to_epoch() == 1445171696);
+    assert!(date(2016, 2, 29, 23, 59, 59).to_epoch() == 1456790399);
+    assert!(date(2105, 12, 31, 23, 59, 59).to_epoch() == 4291747199);
+  }
+
+  #[test]
+  fn round_trips_through_epoch() {
+    let mut seconds = 0u32;
+    while seconds < 4291747199 {
+      assert!(DateTime::from_epoch(seconds).to_epoch() == seconds);
+      seconds += 86400 * 17 + 3599;
+    }
+  }
+
+  #[test]
+  fn computes_weekday() {
+    assert!(date(1970, 1, 1, 0, 0, 0).weekday() == 4);
+    assert!(date(2000, 3, 1, 0, 0, 0).weekday() == 3);
+    assert!(date(2015, 10, 18, 12, 34, 56).weekday() == 0);
+    assert!(date(2016, 2, 29, 23, 59, 59).weekday() == 1);
+    assert!(date(2105, 12, 31, 23, 59, 59).weekday() == 4);
+  }
+
+  #[test]
+  fn computes_day_of_year() {
+    assert!(date(2015, 1, 1, 0, 0, 0).day_of_year() == 1);
+    assert!(date(2015, 12, 31, 0, 0, 0).day_of_year() == 365);
+    assert!(date(2016, 12, 31, 0, 0, 0).day_of_year() == 366);
+  }
+
+  #[test]
+  fn validates_dates() {
+    assert!(date(2016, 2, 29, 0, 0, 0).is_valid());
+    assert!(date(2000, 2, 29, 0, 0, 0).is_valid());
+    assert!(!date(2015, 2, 29, 0, 0, 0).is_valid());
+    assert!(!date(2100, 2, 29, 0, 0, 0).is_valid());
+    assert!(!date(2015, 4, 31, 0, 0, 0).is_valid());
+    assert!(!date(2015, 13, 1, 0, 0, 0).is_valid());
+    assert!(!date(2015, 1, 1, 24, 0, 0).is_valid());
+    assert!(!date(2015, 1, 1, 0, 60, 0).is_valid());
+    assert!(!date(2015, 1, 1, 0, 0, 60).is_valid());
+  }
+
+  #[test]
+  fn limits_years_to_the_epoch_range() {
+    assert!(date(1970, 1, 1, 0, 0, 0).is_valid());
+    assert!(date(2105, 12, 31, 23, 59, 59).is_valid());
+    assert!(!date(1969, 12, 31, 23, 59, 59).is_valid());
+    assert!(!date(2106, 1, 1, 0, 0, 0).is_valid());
+  }
+}
@@ -21,8 +21,19 @@ SPIConf is a MCU-specific struct.
 As SPI performs read and write as one operation, special care should be taken if
 `write()` and `read()` methods are used with several devices on one SPI
 peripheral. The best way is to always use `transfer()`.
+
+Buffer methods (`transfer_in_place()`, `write_all()` and `read_into()`) have
+byte-by-byte default implementations; peripherals with a FIFO override them to
+keep the bus busy between bytes.
+
+Peripherals that can feed their data register from a DMA channel also
+implement `SpiDma`, which drivers use for bulk writes such as LCD pixel data.
 */
 
+use core::result::Result;
+
+use hal::dma;
+
 /// SPI trait.
 pub trait Spi {
   /// Writes a byte over SPI.
@@ -43,4 +54,38 @@ pub trait Spi {
     self.write(value);
     self.read()
   }
+
+  /// Performs an SPI transfer of a whole buffer, replacing every written byte
+  /// with the one read at the same time.
+  fn transfer_in_place(&self, buffer: &mut [u8]) {
+    for b in buffer.iter_mut() {
+      *b = self.transfer(*b);
+    }
+  }
+
+  /// Writes all bytes from `data` over SPI, discarding the bytes read.
+  fn write_all(&self, data: &[u8]) {
+    for b in data.iter() {
+      self.transfer(*b);
+    }
+  }
+
+  /// Fills `buffer` with bytes read from SPI, writing zeroes.
+  fn read_into(&self, buffer: &mut [u8]) {
+    for b in buffer.iter_mut() {
+      *b = self.transfer(0);
+    }
+  }
+}
+
+/// SPI that can write through a DMA channel.
+pub trait SpiDma: Spi {
+  /// DMA channel type, claimed for the TX request of the peripheral.
+  type Channel;
+
+  /// Writes `data` through `channel`, blocking until it's sent.
+  ///
+  /// Received bytes are dropped, as with `Spi::write_all`.
+  fn write_dma(&self, channel: &Self::Channel, data: &[u8])
+      -> Result<(), dma::Error>;
 }
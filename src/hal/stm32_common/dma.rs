@@ -0,0 +1,312 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! DMA controllers shared by the STM32F1 and L1.
+//!
+//! Every peripheral request is wired to a single channel of DMA1 or DMA2, so
+//! channels are claimed by the request they are going to serve. Requests that
+//! share a channel can't be used at the same time.
+//!
+//! The family modules map channels to their controller registers and
+//! interrupts, and claim channels with `Channel::claim`.
+
+use core::intrinsics::abort;
+use core::ops::Drop;
+use core::option::Option::{self, Some, None};
+use core::result::Result::{self, Ok, Err};
+
+use hal::cortex_m3::irq::NoInterrupts;
+use hal::cortex_m3::nvic;
+use hal::dma;
+use util::shared::Shared;
+use util::support::get_reg_ref;
+
+/// Maximum number of bytes in a single transfer.
+pub const MAX_TRANSFER: usize = 0xFFFF;
+
+/// Available DMA controllers.
+#[allow(missing_docs)]
+#[derive(PartialEq, Clone, Copy)]
+pub enum Controller {
+  Dma1,
+  Dma2,
+}
+
+/// Peripheral requests that can be served by DMA.
+#[allow(missing_docs)]
+#[derive(Clone, Copy)]
+pub enum Request {
+  Spi1Rx,
+  Spi1Tx,
+  Spi2Rx,
+  Spi2Tx,
+  Spi3Rx,
+  Spi3Tx,
+  Usart1Rx,
+  Usart1Tx,
+  Usart2Rx,
+  Usart2Tx,
+  Usart3Rx,
+  Usart3Tx,
+  Uart4Rx,
+  Uart4Tx,
+}
+
+impl Request {
+  /// Returns the controller and channel number serving this request.
+  pub fn channel(self) -> (Controller, usize) {
+    use self::Request::*;
+    use self::Controller::*;
+    match self {
+      Spi1Rx   => (Dma1, 2),
+      Spi1Tx   => (Dma1, 3),
+      Spi2Rx   => (Dma1, 4),
+      Spi2Tx   => (Dma1, 5),
+      Spi3Rx   => (Dma2, 1),
+      Spi3Tx   => (Dma2, 2),
+      Usart1Rx => (Dma1, 5),
+      Usart1Tx => (Dma1, 4),
+      Usart2Rx => (Dma1, 6),
+      Usart2Tx => (Dma1, 7),
+      Usart3Rx => (Dma1, 3),
+      Usart3Tx => (Dma1, 2),
+      Uart4Rx  => (Dma2, 3),
+      Uart4Tx  => (Dma2, 5),
+    }
+  }
+}
+
+/// DMA1 has 7 channels and DMA2 has 5.
+const CHANNEL_COUNT: usize = 12;
+
+struct State {
+  claimed: u16,
+  callbacks: [Option<dma::Callback>; CHANNEL_COUNT],
+}
+
+static STATE: Shared<State> = Shared::new(State {
+  claimed: 0,
+  callbacks: [None; CHANNEL_COUNT],
+});
+
+/// A claimed DMA channel, released when dropped.
+pub struct Channel {
+  id: ChannelId,
+}
+
+impl Channel {
+  /// Claims the channel `id`, whose controller clock must be enabled.
+  ///
+  /// Returns `None` if the channel is already in use.
+  pub fn claim_id(id: ChannelId) -> Option<Channel> {
+    let crit = NoInterrupts::new();
+    let mut state = STATE.borrow(&crit);
+    if state.claimed & (1 << id.index()) != 0 {
+      return None;
+    }
+    state.claimed |= 1 << id.index();
+    state.callbacks[id.index()] = None;
+    Some(Channel { id: id })
+  }
+
+  /// Starts a transfer of `len` bytes from memory to a peripheral register.
+  ///
+  /// If `increment` is false, the same byte at `source` is sent `len` times.
+  ///
+  /// This is unsafe as `source` must stay valid until the transfer completes.
+  pub unsafe fn memory_to_peripheral(&self, source: *const u8,
+      increment: bool, len: usize, destination: u32) {
+    self.start(destination, source as u32, increment, len, true);
+  }
+
+  /// Starts a transfer of `len` bytes from a peripheral register to memory.
+  ///
+  /// This is unsafe as `destination` must stay valid until the transfer
+  /// completes.
+  pub unsafe fn peripheral_to_memory(&self, source: u32,
+      destination: *mut u8, len: usize) {
+    self.start(source, destination as u32, true, len, false);
+  }
+
+  fn start(&self, peripheral: u32, memory: u32, memory_increment: bool,
+      len: usize, from_memory: bool) {
+    if len > MAX_TRANSFER {
+      unsafe { abort() };
+    }
+    let ch = self.id.reg();
+    let interrupts = {
+      let crit = NoInterrupts::new();
+      STATE.borrow(&crit).callbacks[self.id.index()].is_some()
+    };
+
+    ch.ccr.set_enable(false);
+    self.id.clear_flags();
+
+    ch.cpar.set_address(peripheral);
+    ch.cmar.set_address(memory);
+    ch.cndtr.set_count(len as u32);
+    // Both sizes are left at 8 bits.
+    ch.ccr.ignoring_state()
+      .set_read_from_memory(from_memory)
+      .set_memory_increment(memory_increment)
+      .set_int_transfer_complete_enable(interrupts)
+      .set_int_transfer_error_enable(interrupts)
+      .set_enable(true);
+  }
+}
+
+impl dma::Channel for Channel {
+  fn status(&self) -> Option<Result<(), dma::Error>> {
+    let ch = self.id.reg();
+    if self.id.flag(FLAG_TRANSFER_ERROR) {
+      Some(Err(dma::Error::Bus))
+    } else if ch.ccr.enable() && ch.cndtr.count() != 0 {
+      None
+    } else {
+      Some(Ok(()))
+    }
+  }
+
+  fn set_callback(&self, callback: Option<dma::Callback>) {
+    let crit = NoInterrupts::new();
+    STATE.borrow(&crit).callbacks[self.id.index()] = callback;
+    if callback.is_some() {
+      nvic::enable_irq(self.id.irq);
+    }
+  }
+}
+
+impl Drop for Channel {
+  fn drop(&mut self) {
+    let crit = NoInterrupts::new();
+    let mut state = STATE.borrow(&crit);
+    self.id.reg().ccr.set_enable(false);
+    state.claimed &= !(1 << self.id.index());
+    state.callbacks[self.id.index()] = None;
+  }
+}
+
+/// Location of a DMA channel, provided by the family modules.
+#[derive(Clone, Copy)]
+pub struct ChannelId {
+  controller: Controller,
+  number: usize,
+  dma: &'static reg::DMA,
+  irq: usize,
+}
+
+impl ChannelId {
+  /// Describes channel `number` of `controller`, whose registers are at `dma`
+  /// and interrupt is `irq`.
+  pub fn new(controller: Controller, number: usize, dma: &'static reg::DMA,
+      irq: usize) -> ChannelId {
+    ChannelId {
+      controller: controller,
+      number: number,
+      dma: dma,
+      irq: irq,
+    }
+  }
+
+  fn index(self) -> usize {
+    match self.controller {
+      Controller::Dma1 => self.number - 1,
+      Controller::Dma2 => self.number + 6,
+    }
+  }
+
+  fn flag(self, flag: usize) -> bool {
+    self.dma.isr.flags((self.number - 1) * 4 + flag)
+  }
+
+  fn clear_flags(self) {
+    self.dma.ifcr.set_clear((self.number - 1) * 4 + FLAG_GLOBAL, true);
+  }
+
+  fn reg(self) -> &'static reg::Channel {
+    let base = self.dma as *const reg::DMA as usize;
+    get_reg_ref((base + CHANNEL_OFFSET + CHANNEL_STRIDE * (self.number - 1))
+                as *const reg::Channel)
+  }
+}
+
+/// Services the interrupt of channel `id`, running its callback.
+pub fn isr_handler(id: ChannelId) {
+  let result = if id.flag(FLAG_TRANSFER_ERROR) {
+    Err(dma::Error::Bus)
+  } else if id.flag(FLAG_TRANSFER_COMPLETE) {
+    Ok(())
+  } else {
+    return;
+  };
+  id.clear_flags();
+
+  let callback = {
+    let crit = NoInterrupts::new();
+    STATE.borrow(&crit).callbacks[id.index()]
+  };
+  match callback {
+    Some(callback) => callback(result),
+    None => (),
+  }
+}
+
+const CHANNEL_OFFSET: usize = 0x08;
+const CHANNEL_STRIDE: usize = 0x14;
+
+const FLAG_GLOBAL: usize = 0;
+const FLAG_TRANSFER_COMPLETE: usize = 1;
+const FLAG_TRANSFER_ERROR: usize = 3;
+
+/// DMA registers, the family modules declare the controllers.
+pub mod reg {
+  use volatile_cell::VolatileCell;
+  use core::ops::Drop;
+
+  ioregs!(DMA = {
+    0x00 => reg32 isr { // interrupt status, 4 flags per channel
+      27..0 => flags[28] : ro,
+    },
+    0x04 => reg32 ifcr { // interrupt flag clear
+      27..0 => clear[28] : wo,
+    },
+  });
+
+  ioregs!(Channel = {
+    0x00 => reg32 ccr { // configuration
+      0 => enable : rw,
+      1 => int_transfer_complete_enable : rw,
+      2 => int_half_transfer_enable : rw,
+      3 => int_transfer_error_enable : rw,
+      4 => read_from_memory : rw,
+      5 => circular : rw,
+      6 => peripheral_increment : rw,
+      7 => memory_increment : rw,
+      9..8 => peripheral_size : rw,
+      11..10 => memory_size : rw,
+      13..12 => priority : rw,
+      14 => memory_to_memory : rw,
+    },
+    0x04 => reg32 cndtr { // number of data
+      15..0 => count : rw,
+    },
+    0x08 => reg32 cpar { // peripheral address
+      31..0 => address : rw,
+    },
+    0x0C => reg32 cmar { // memory address
+      31..0 => address : rw,
+    },
+  });
+}
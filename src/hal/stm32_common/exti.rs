@@ -0,0 +1,123 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! External interrupt controller (EXTI) logic shared by the STM32F1, F4 and
+//! L1.
+//!
+//! Each of the 16 GPIO lines serves the pin with the same index, on the port
+//! selected through an EXTICR register. That register lives in AFIO on the F1
+//! and in SYSCFG on the F4 and L1, so the family modules pass it in along with
+//! the interrupt of each line.
+
+use core::option::Option::{self, Some, None};
+
+use hal::cortex_common::irq::NoInterrupts;
+use hal::cortex_common::nvic;
+use hal::pin::{GpioEdge, GpioHandler};
+use util::shared::Shared;
+
+/// Number of EXTI lines connected to GPIO.
+const LINE_COUNT: usize = 16;
+
+static HANDLERS: Shared<[Option<GpioHandler>; LINE_COUNT]> =
+    Shared::new([None; LINE_COUNT]);
+
+/// Sets the handler called when `line` triggers.
+pub fn set_handler(line: usize, handler: Option<GpioHandler>) {
+  let crit = NoInterrupts::new();
+  HANDLERS.borrow(&crit)[line] = handler;
+}
+
+/// Routes `line` to `port` through `exticr`, and enables its interrupt `irq`
+/// on the given edge.
+///
+/// The clock of the block holding `exticr` must be enabled.
+pub fn enable(exti: &reg::EXTI, exticr: &reg::EXTICR, line: usize, port: u8,
+    edge: GpioEdge, irq: usize) {
+  exti.imr.set_line(line, false);
+
+  exticr.exticr[line / 4].set_port(line % 4, port as u32);
+
+  exti.rtsr.set_line(line, edge != GpioEdge::Falling);
+  exti.ftsr.set_line(line, edge != GpioEdge::Rising);
+  clear(exti, line);
+  exti.imr.set_line(line, true);
+
+  nvic::enable_irq(irq);
+}
+
+/// Masks the interrupt of `line`.
+pub fn disable(exti: &reg::EXTI, line: usize) {
+  exti.imr.set_line(line, false);
+}
+
+/// Clears the pending flag of `line`.
+pub fn clear(exti: &reg::EXTI, line: usize) {
+  exti.pr.ignoring_state().set_line(line, true);
+}
+
+/// Services the interrupt of EXTI lines `first` to `last` (inclusive),
+/// calling the handlers of the lines that triggered.
+pub fn isr_handler(exti: &reg::EXTI, first: usize, last: usize) {
+  for line in first..last + 1 {
+    if !exti.pr.line(line) || !exti.imr.line(line) {
+      continue;
+    }
+    clear(exti, line);
+
+    let handler = {
+      let crit = NoInterrupts::new();
+      HANDLERS.borrow(&crit)[line]
+    };
+    match handler {
+      Some(handler) => handler(),
+      None => (),
+    }
+  }
+}
+
+/// EXTI registers, the family modules declare the instances.
+pub mod reg {
+  use volatile_cell::VolatileCell;
+  use core::ops::Drop;
+
+  ioregs!(EXTI = {
+    0x00 => reg32 imr {    // interrupt mask
+      15..0 => line[16] : rw,
+    },
+    0x04 => reg32 emr {    // event mask
+      15..0 => line[16] : rw,
+    },
+    0x08 => reg32 rtsr {   // rising trigger selection
+      15..0 => line[16] : rw,
+    },
+    0x0C => reg32 ftsr {   // falling trigger selection
+      15..0 => line[16] : rw,
+    },
+    0x10 => reg32 swier {  // software interrupt event
+      15..0 => line[16] : rw,
+    },
+    0x14 => reg32 pr {     // pending, cleared by writing 1
+      15..0 => line[16] : rw,
+    },
+  });
+
+  // The port selection of AFIO and SYSCFG, both at offset 8.
+  ioregs!(EXTICR = {
+    0x08 => reg32 exticr[4] { // port of lines 4*n to 4*n+3
+      15..0 => port[4] : rw,
+    },
+  });
+}
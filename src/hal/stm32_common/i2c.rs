@@ -0,0 +1,284 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Inter-Integrated Circuit master shared by the STM32F1, F4 and L1.
+//!
+//! Reads follow the polling sequences from the reference manuals, as the ACK
+//! and STOP bits must be programmed before the last bytes arrive.
+
+use core::result::Result;
+use core::result::Result::{Ok, Err};
+use core::intrinsics::abort;
+
+use hal::i2c;
+
+#[path="../../util/wait_for.rs"]
+#[macro_use] mod wait_for;
+
+/// Structure describing an I2C instance.
+#[derive(Clone, Copy)]
+pub struct I2c {
+  reg: &'static reg::I2C,
+}
+
+impl I2c {
+  /// Starts the I2C master at `reg` at `frequency` Hz, from a peripheral clock
+  /// running at `bus_clock` Hz.
+  ///
+  /// The peripheral clock must be enabled. Frequencies up to 100kHz use
+  /// standard mode, up to 400kHz fast mode.
+  pub fn with_peripheral(reg: &'static reg::I2C, frequency: u32,
+      bus_clock: u32) -> I2c {
+    let bus_mhz = bus_clock / 1_000_000;
+
+    reg.cr1.set_peripheral_enable(false);
+    reg.cr2.set_frequency(bus_mhz as u16);
+
+    if frequency <= 100_000 {
+      // Thigh = Tlow = CCR * Tpclk1
+      let ccr = bus_clock / (frequency << 1);
+      reg.ccr.set_fast_mode(false).set_clock_control(
+          if ccr < 4 { 4 } else { ccr as u16 });
+      reg.trise.set_rise_time(bus_mhz as u16 + 1);
+    } else if frequency <= 400_000 {
+      // Thigh = CCR * Tpclk1, Tlow = 2 * CCR * Tpclk1
+      let ccr = bus_clock / (frequency * 3);
+      reg.ccr.set_fast_mode(true).set_duty(false).set_clock_control(
+          if ccr < 1 { 1 } else { ccr as u16 });
+      reg.trise.set_rise_time((bus_mhz * 300 / 1000) as u16 + 1);
+    } else {
+      unsafe { abort() } // not supported
+    }
+
+    reg.cr1.set_peripheral_enable(true);
+
+    I2c {
+      reg: reg,
+    }
+  }
+
+  /// Waits for `done` to be true for SR1, bailing out on any error flag.
+  fn wait<F>(&self, done: F, nack: i2c::Error) -> Result<(), i2c::Error>
+      where F: Fn(&reg::I2C_sr1_Get) -> bool {
+    loop {
+      let sr1 = self.reg.sr1.get();
+      let error = if sr1.acknowledge_failure() {
+        nack
+      } else if sr1.arbitration_lost() {
+        i2c::Error::ArbitrationLost
+      } else if sr1.bus_error() {
+        i2c::Error::Bus
+      } else if done(&sr1) {
+        return Ok(());
+      } else {
+        continue;
+      };
+      self.reg.sr1
+        .set_acknowledge_failure(false)
+        .set_arbitration_lost(false)
+        .set_bus_error(false);
+      return Err(error);
+    }
+  }
+
+  fn start(&self, address: u8) -> Result<(), i2c::Error> {
+    self.reg.cr1.set_start(true);
+    try!(self.wait(|sr1| sr1.start_bit(), i2c::Error::Bus));
+    self.reg.dr.set_data(address as u16);
+    self.wait(|sr1| sr1.address_sent(), i2c::Error::AddressNack)
+  }
+
+  /// Clears ADDR, which releases SCL after the address phase.
+  fn clear_address(&self) {
+    self.reg.sr1.get();
+    self.reg.sr2.get();
+  }
+
+  /// Generates a STOP condition if we still own the bus.
+  fn stop(&self) {
+    if self.reg.sr2.master() {
+      self.reg.cr1.set_stop(true);
+    }
+    wait_for!(!self.reg.cr1.stop());
+  }
+
+  fn write_bytes(&self, address: u8, data: &[u8]) -> Result<(), i2c::Error> {
+    try!(self.start(address << 1));
+    self.clear_address();
+    for &b in data.iter() {
+      try!(self.wait(|sr1| sr1.transmit_data_empty(), i2c::Error::DataNack));
+      self.reg.dr.set_data(b as u16);
+    }
+    self.wait(|sr1| sr1.byte_transfer_finished(), i2c::Error::DataNack)
+  }
+
+  /// Reads `buffer.len()` bytes, at least one; the STOP condition is
+  /// generated here.
+  fn read_bytes(&self, address: u8, buffer: &mut [u8])
+      -> Result<(), i2c::Error> {
+    let len = buffer.len();
+
+    self.reg.cr1.set_acknowledge(len > 1).set_ack_position(len == 2);
+    try!(self.start((address << 1) | 1));
+
+    match len {
+      1 => {
+        self.clear_address();
+        self.reg.cr1.set_stop(true);
+        try!(self.wait(|sr1| sr1.read_data_not_empty(), i2c::Error::Bus));
+        buffer[0] = self.reg.dr.data() as u8;
+      },
+      2 => {
+        self.clear_address();
+        self.reg.cr1.set_acknowledge(false);
+        try!(self.wait(|sr1| sr1.byte_transfer_finished(), i2c::Error::Bus));
+        self.reg.cr1.set_stop(true);
+        buffer[0] = self.reg.dr.data() as u8;
+        buffer[1] = self.reg.dr.data() as u8;
+        self.reg.cr1.set_ack_position(false);
+      },
+      _ => {
+        self.clear_address();
+        for i in 0..len - 3 {
+          try!(self.wait(|sr1| sr1.read_data_not_empty(), i2c::Error::Bus));
+          buffer[i] = self.reg.dr.data() as u8;
+        }
+        // N-2 is in DR and N-1 in the shift register.
+        try!(self.wait(|sr1| sr1.byte_transfer_finished(), i2c::Error::Bus));
+        self.reg.cr1.set_acknowledge(false);
+        buffer[len - 3] = self.reg.dr.data() as u8;
+        try!(self.wait(|sr1| sr1.byte_transfer_finished(), i2c::Error::Bus));
+        self.reg.cr1.set_stop(true);
+        buffer[len - 2] = self.reg.dr.data() as u8;
+        buffer[len - 1] = self.reg.dr.data() as u8;
+      },
+    }
+    Ok(())
+  }
+}
+
+impl ::hal::i2c::I2c for I2c {
+  fn write(&self, address: u8, data: &[u8]) -> Result<(), i2c::Error> {
+    if data.is_empty() {
+      return Ok(());
+    }
+    let result = self.write_bytes(address, data);
+    self.stop();
+    result
+  }
+
+  fn read(&self, address: u8, buffer: &mut [u8]) -> Result<(), i2c::Error> {
+    if buffer.is_empty() {
+      return Ok(());
+    }
+    let result = self.read_bytes(address, buffer);
+    self.stop();
+    result
+  }
+
+  fn write_read(&self, address: u8, data: &[u8], buffer: &mut [u8])
+      -> Result<(), i2c::Error> {
+    if data.is_empty() {
+      return i2c::I2c::read(self, address, buffer);
+    }
+    if buffer.is_empty() {
+      return i2c::I2c::write(self, address, data);
+    }
+    let result = match self.write_bytes(address, data) {
+      Ok(()) => self.read_bytes(address, buffer),
+      err => err,
+    };
+    self.stop();
+    result
+  }
+}
+
+/// I2C registers, the family modules declare the instances.
+pub mod reg {
+  use volatile_cell::VolatileCell;
+  use core::ops::Drop;
+
+  ioregs!(I2C = {
+    0x00 => reg16 cr1 { // control 1
+      0 => peripheral_enable : rw,
+      1 => smbus_mode : rw,
+      3 => smbus_type : rw,
+      4 => arp_enable : rw,
+      5 => pec_enable : rw,
+      6 => general_call_enable : rw,
+      7 => clock_stretching_disable : rw,
+      8 => start : rw,
+      9 => stop : rw,
+      10 => acknowledge : rw,
+      11 => ack_position : rw,
+      12 => packet_error_checking : rw,
+      13 => smbus_alert : rw,
+      15 => software_reset : rw,
+    },
+    0x04 => reg16 cr2 { // control 2
+      5..0 => frequency : rw,
+      8 => int_error_enable : rw,
+      9 => int_event_enable : rw,
+      10 => int_buffer_enable : rw,
+      11 => dma_enable : rw,
+      12 => dma_last_transfer : rw,
+    },
+    0x08 => reg16 oar1 { // own address 1
+      9..0 => address : rw,
+      15 => address_mode : rw,
+    },
+    0x0C => reg16 oar2 { // own address 2
+      0 => dual_address_enable : rw,
+      7..1 => address : rw,
+    },
+    0x10 => reg16 dr { // data
+      7..0 => data : rw,
+    },
+    0x14 => reg16 sr1 { // status 1
+      0 => start_bit : ro,
+      1 => address_sent : ro,
+      2 => byte_transfer_finished : ro,
+      3 => header_sent : ro,
+      4 => stop_detection : ro,
+      6 => read_data_not_empty : ro,
+      7 => transmit_data_empty : ro,
+      8 => bus_error : rw,
+      9 => arbitration_lost : rw,
+      10 => acknowledge_failure : rw,
+      11 => overrun : rw,
+      12 => pec_error : rw,
+      14 => timeout : rw,
+      15 => smbus_alert : rw,
+    },
+    0x18 => reg16 sr2 { // status 2
+      0 => master : ro,
+      1 => busy : ro,
+      2 => transmitter : ro,
+      4 => general_call : ro,
+      5 => smbus_default : ro,
+      6 => smbus_host : ro,
+      7 => dual_address : ro,
+      15..8 => pec : ro,
+    },
+    0x1C => reg16 ccr { // clock control
+      11..0 => clock_control : rw,
+      14 => duty : rw,
+      15 => fast_mode : rw,
+    },
+    0x20 => reg16 trise { // rise time
+      5..0 => rise_time : rw,
+    },
+  });
+}
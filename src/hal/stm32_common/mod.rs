@@ -0,0 +1,41 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/*!
+Peripheral drivers shared by the STM32 families.
+
+The family modules enable the peripheral clocks and provide the register
+blocks, the logic driving them lives here.
+*/
+
+#[cfg(any(feature = "mcu_stm32f1",
+          feature = "mcu_stm32l1"))]
+pub mod dma;
+#[cfg(any(feature = "mcu_stm32f1",
+          feature = "mcu_stm32f4",
+          feature = "mcu_stm32l1"))]
+pub mod exti;
+#[cfg(any(feature = "mcu_stm32f1",
+          feature = "mcu_stm32f4",
+          feature = "mcu_stm32l1"))]
+pub mod i2c;
+#[cfg(any(feature = "mcu_stm32f1",
+          feature = "mcu_stm32f4",
+          feature = "mcu_stm32f7"))]
+pub mod pwm;
+#[cfg(any(feature = "mcu_stm32f1",
+          feature = "mcu_stm32f4",
+          feature = "mcu_stm32l1"))]
+pub mod watchdog;
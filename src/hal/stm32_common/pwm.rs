@@ -0,0 +1,143 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! PWM outputs on the STM32 general purpose timers.
+//!
+//! TIM2 to TIM5 have four channels each, which share the timer period: setting
+//! the period of a channel changes it for the other channels of its timer.
+//! Timers count microseconds, so periods are limited to 65536us on the 16-bit
+//! timers.
+//!
+//! Outputs are created with `PWM::new` of the family module, which enables the
+//! timer clock. There is no platformtree `pwm` node for the STM32 families.
+
+use core::cmp::max;
+
+use hal::pwm::PWMOutput;
+
+/// Timer capture/compare channels.
+#[allow(missing_docs)]
+#[derive(Clone, Copy)]
+pub enum Channel {
+  Channel1,
+  Channel2,
+  Channel3,
+  Channel4,
+}
+
+/// Output compare setting for PWM mode 1, with preloaded compare value.
+const OC_PWM1_PRELOAD: u32 = 0b110_1000;
+
+/// Capture/compare enable setting for an active high output.
+const CC_ENABLE: u32 = 0b0001;
+
+/// Structure describing a PWM output.
+#[derive(Clone, Copy)]
+pub struct PWM {
+  reg: &'static reg::TIM,
+  channel: Channel,
+  period_us: u32,
+  pulsewidth_us: u32,
+}
+
+impl PWM {
+  /// Starts a PWM output on `channel` of the timer at `reg`, initially low.
+  ///
+  /// The timer clock must be enabled and run at `timer_clock` Hz.
+  pub fn with_timer(reg: &'static reg::TIM, channel: Channel, period_us: u32,
+      timer_clock: u32) -> PWM {
+    reg.psc.set_prescaler(timer_clock / 1_000_000 - 1);
+    reg.cr1.set_auto_reload_preload(true);
+
+    let index = channel as usize;
+    reg.ccmr[index / 2].set_oc(index % 2, OC_PWM1_PRELOAD);
+    reg.ccer.set_cc(index, CC_ENABLE);
+
+    let pwm = PWM {
+      reg: reg,
+      channel: channel,
+      period_us: period_us,
+      pulsewidth_us: 0,
+    };
+    pwm.update_period();
+    pwm.update_pulsewidth();
+
+    // Loads the prescaler and preloaded registers before starting.
+    reg.egr.set_update(true);
+    reg.cr1.set_counter_enable(true);
+    pwm
+  }
+
+  fn update_period(&self) {
+    // A zero period is treated as one tick.
+    self.reg.arr.set_reload(max(self.period_us, 1) - 1);
+  }
+
+  fn update_pulsewidth(&self) {
+    self.reg.ccr[self.channel as usize].set_value(self.pulsewidth_us);
+  }
+}
+
+impl PWMOutput for PWM {
+  fn set_period_us(&mut self, period_us: u32) {
+    self.period_us = period_us;
+    self.update_period();
+  }
+
+  fn get_period_us(&self) -> u32 {
+    self.period_us
+  }
+
+  fn set_pulsewidth_us(&mut self, pulsewidth_us: u32) {
+    self.pulsewidth_us = pulsewidth_us;
+    self.update_pulsewidth();
+  }
+
+  fn get_pulsewidth_us(&self) -> u32 {
+    self.pulsewidth_us
+  }
+}
+
+/// Timer registers, the family modules declare the timer instances.
+pub mod reg {
+  use volatile_cell::VolatileCell;
+  use core::ops::Drop;
+
+  // ARR and CCRx only implement the low 16 bits on 16-bit timers.
+  ioregs!(TIM = {
+    0x00 => reg32 cr1 {      // control 1
+      0     => counter_enable : rw,
+      7     => auto_reload_preload : rw,
+    },
+    0x14 => reg32 egr {      // event generation
+      0     => update : wo,
+    },
+    0x18 => reg32 ccmr[2] {  // capture/compare mode, one byte per channel
+      15..0 => oc[2] : rw,
+    },
+    0x20 => reg32 ccer {     // capture/compare enable, 4 bits per channel
+      15..0 => cc[4] : rw,
+    },
+    0x28 => reg32 psc {      // prescaler
+      15..0 => prescaler : rw,
+    },
+    0x2C => reg32 arr {      // auto-reload
+      31..0 => reload : rw,
+    },
+    0x34 => reg32 ccr[4] {   // capture/compare
+      31..0 => value : rw,
+    },
+  });
+}
@@ -0,0 +1,145 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Independent and window watchdog logic shared by the STM32 families.
+//!
+//! The family modules provide the watchdog structs, which enable the WWDG
+//! clock and read the reset flags of their RCC, and call in here to program
+//! the watchdogs.
+
+use core::intrinsics::abort;
+use core::option::Option::Some;
+
+#[path="../../util/wait_for.rs"]
+#[macro_use] mod wait_for;
+
+const IWDG_KEY_START: u32 = 0xCCCC;
+const IWDG_KEY_RELOAD: u32 = 0xAAAA;
+const IWDG_KEY_UNLOCK: u32 = 0x5555;
+
+/// Largest IWDG reload value.
+const IWDG_MAX_RELOAD: u32 = 0xFFF;
+
+/// Lowest WWDG counter value, the MCU resets when the counter goes below it.
+const WWDG_MIN_COUNTER: u32 = 0x40;
+
+/// Largest WWDG counter value.
+const WWDG_MAX_COUNTER: u32 = 0x7F;
+
+/// Starts the independent watchdog with an LSI running at `lsi_ticks_per_ms`.
+///
+/// Aborts if `timeout_ms` isn't reachable.
+pub fn start_independent(reg: &reg::IWDG, lsi_ticks_per_ms: u32,
+    timeout_ms: u32) {
+  let ticks = match timeout_ms.checked_mul(lsi_ticks_per_ms) {
+    Some(ticks) => ticks,
+    _ => unsafe { abort() },
+  };
+  // The prescaler divides the LSI by 4 << prescaler.
+  let mut prescaler = 0;
+  while prescaler < 6 && ticks / (4 << prescaler) > IWDG_MAX_RELOAD + 1 {
+    prescaler += 1;
+  }
+  let reload = ticks / (4 << prescaler);
+  if reload == 0 || reload > IWDG_MAX_RELOAD + 1 {
+    unsafe { abort() };
+  }
+
+  // Starting the watchdog also starts the LSI.
+  reg.kr.ignoring_state().set_key(IWDG_KEY_START);
+  reg.kr.ignoring_state().set_key(IWDG_KEY_UNLOCK);
+  wait_for!(!reg.sr.pvu() && !reg.sr.rvu());
+  reg.pr.set_prescaler(prescaler);
+  reg.rlr.set_reload(reload - 1);
+  wait_for!(!reg.sr.pvu() && !reg.sr.rvu());
+  feed_independent(reg);
+}
+
+/// Reloads the independent watchdog counter.
+pub fn feed_independent(reg: &reg::IWDG) {
+  reg.kr.ignoring_state().set_key(IWDG_KEY_RELOAD);
+}
+
+/// Starts the window watchdog, whose clock must be enabled, from a PCLK1 at
+/// `pclk1` Hz.
+///
+/// The window is left open, so the watchdog can be fed at any time. Aborts if
+/// `timeout_ms` isn't reachable.
+pub fn start_window(reg: &reg::WWDG, pclk1: u32, timeout_ms: u32) {
+  // The counter is clocked by PCLK1 / 4096, divided by 1 << timer_base.
+  let ticks = match timeout_ms.checked_mul(pclk1 / 4096) {
+    Some(ticks) => ticks / 1000,
+    _ => unsafe { abort() },
+  };
+  let max_count = WWDG_MAX_COUNTER - WWDG_MIN_COUNTER + 1;
+  let mut timer_base = 0;
+  while timer_base < 3 && ticks >> timer_base > max_count {
+    timer_base += 1;
+  }
+  let count = ticks >> timer_base;
+  if count == 0 || count > max_count {
+    unsafe { abort() };
+  }
+
+  // Feeding is only allowed while the counter is below the window value, so
+  // it's set to the reload value, which feed_window() reads back.
+  let counter = WWDG_MIN_COUNTER - 1 + count;
+  reg.cfr.set_timer_base(timer_base).set_window(counter);
+  reg.cr.ignoring_state().set_counter(counter).set_activate(true);
+}
+
+/// Reloads the window watchdog counter.
+pub fn feed_window(reg: &reg::WWDG) {
+  reg.cr.ignoring_state()
+    .set_counter(reg.cfr.window())
+    .set_activate(true);
+}
+
+/// Watchdog registers, the family modules declare the instances.
+pub mod reg {
+  use volatile_cell::VolatileCell;
+  use core::ops::Drop;
+
+  ioregs!(IWDG = {
+    0x00 => reg32 kr {       // key
+      15..0 => key : wo,
+    },
+    0x04 => reg32 pr {       // prescaler
+      2..0  => prescaler : rw,
+    },
+    0x08 => reg32 rlr {      // reload
+      11..0 => reload : rw,
+    },
+    0x0C => reg32 sr {       // status
+      0     => pvu : ro,     // prescaler update in progress
+      1     => rvu : ro,     // reload update in progress
+    },
+  });
+
+  ioregs!(WWDG = {
+    0x00 => reg32 cr {       // control
+      6..0  => counter : rw,
+      7     => activate : rw,
+    },
+    0x04 => reg32 cfr {      // configuration
+      6..0  => window : rw,
+      8..7  => timer_base : rw,
+      9     => early_wakeup_interrupt : rw,
+    },
+    0x08 => reg32 sr {       // status
+      0     => early_wakeup_flag : rw,
+    },
+  });
+}
@@ -0,0 +1,249 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Analog to digital converters for STM32F1.
+//!
+//! Conversions are always 12-bit. The ADC clock is derived from APB2 and must
+//! not exceed 14 MHz, so `Adc::new` picks the smallest prescaler that keeps it
+//! within range.
+//!
+//! Channels 0 to 15 are the ADC input pins, 16 is the temperature sensor and
+//! 17 the internal reference (ADC1 only).
+
+use core::option::Option::{Some, None};
+use core::result::Result::{self, Ok, Err};
+
+use hal::adc::{self, Error, Resolution};
+use hal::stm32f1::init;
+use hal::stm32f1::peripheral_clock::{PeripheralClock, BusApb2};
+
+#[path="../../util/wait_for.rs"]
+#[macro_use] mod wait_for;
+
+/// Available ADC peripherals.
+#[allow(missing_docs)]
+#[derive(Clone, Copy)]
+pub enum Peripheral {
+  Adc1,
+  Adc2,
+  Adc3,
+}
+
+/// Regular conversion triggers.
+#[allow(missing_docs)]
+#[derive(Clone, Copy)]
+pub enum Trigger {
+  Tim1Cc1   = 0b000,
+  Tim1Cc2   = 0b001,
+  Tim1Cc3   = 0b010,
+  Tim2Cc2   = 0b011,
+  Tim3Trgo  = 0b100,
+  Tim4Cc4   = 0b101,
+}
+
+/// Highest channel number.
+const MAX_CHANNEL: u8 = 17;
+
+/// EXTSEL value that starts conversions from SWSTART.
+const SOFTWARE_TRIGGER: u32 = 0b111;
+
+/// Available sample times, in ADC cycles rounded up.
+const SAMPLE_TIMES: [u32; 8] = [2, 8, 14, 29, 42, 56, 72, 240];
+
+/// ADC maximum clock frequency.
+const MAX_CLOCK: u32 = 14_000_000;
+
+/// An ADC peripheral.
+#[derive(Clone, Copy)]
+pub struct Adc {
+  reg: &'static reg::ADC,
+}
+
+impl Adc {
+  /// Powers up and calibrates an ADC.
+  pub fn new(peripheral: Peripheral, config: &init::ClockConfig) -> Adc {
+    let (reg, clock) = match peripheral {
+      Peripheral::Adc1 => (&reg::ADC1, BusApb2::Adc1),
+      Peripheral::Adc2 => (&reg::ADC2, BusApb2::Adc2),
+      Peripheral::Adc3 => (&reg::ADC3, BusApb2::Adc3),
+    };
+    let clock = PeripheralClock::Apb2(clock);
+    clock.enable();
+
+    // ADCPRE divides APB2 by 2, 4, 6 or 8.
+    let apb2 = clock.frequency(config);
+    let mut prescaler = 0;
+    while prescaler < 3 && apb2 / ((prescaler + 1) * 2) > MAX_CLOCK {
+      prescaler += 1;
+    }
+    init::reg::RCC.cfgr.set_adc_prescaler(prescaler);
+
+    let adc = Adc { reg: reg };
+    reg.cr2.set_power_on(true);
+    // The ADC needs a few cycles to stabilize before calibration.
+    for _ in 0..32 {
+      unsafe { asm!("nop" :::: "volatile") };
+    }
+    reg.cr2.set_reset_calibration(true);
+    wait_for!(!reg.cr2.reset_calibration());
+    reg.cr2.set_calibration(true);
+    wait_for!(!reg.cr2.calibration());
+
+    reg.cr2
+      .set_ext_trigger(true)
+      .set_ext_select(SOFTWARE_TRIGGER);
+    match peripheral {
+      Peripheral::Adc1 => { reg.cr2.set_temp_sensor(true); },
+      _ => (),
+    }
+    adc
+  }
+
+  /// Starts a conversion of `channel` on each `trigger` event.
+  ///
+  /// Results are read with `latest`, until `stop` is called.
+  pub fn start_triggered(&self, channel: u8, trigger: Trigger)
+      -> Result<(), Error> {
+    try!(self.select(channel));
+    self.reg.cr2
+      .set_continuous(false)
+      .set_ext_select(trigger as u32);
+    Ok(())
+  }
+
+  fn select(&self, channel: u8) -> Result<(), Error> {
+    if channel > MAX_CHANNEL {
+      return Err(Error::InvalidChannel);
+    }
+    self.reg.sqr1.set_length(0);
+    self.reg.sqr3.set_sq(0, channel as u32);
+    Ok(())
+  }
+
+  fn start(&self) {
+    self.reg.sr.set_eoc(false);
+    self.reg.cr2.set_sw_start(true);
+  }
+}
+
+impl adc::Adc for Adc {
+  fn set_resolution(&self, resolution: Resolution) -> Result<(), Error> {
+    match resolution {
+      Resolution::Bits12 => Ok(()),
+      _                  => Err(Error::UnsupportedResolution),
+    }
+  }
+
+  fn resolution(&self) -> Resolution {
+    Resolution::Bits12
+  }
+
+  fn set_sample_time(&self, cycles: u32) -> Result<(), Error> {
+    let smp = match SAMPLE_TIMES.iter().position(|&t| t >= cycles) {
+      Some(smp) => smp as u32,
+      None      => return Err(Error::UnsupportedSampleTime),
+    };
+    for channel in 0..10 {
+      self.reg.smpr2.set_smp(channel, smp);
+    }
+    for channel in 0..8 {
+      self.reg.smpr1.set_smp(channel, smp);
+    }
+    Ok(())
+  }
+
+  fn read(&self, channel: u8) -> Result<u16, Error> {
+    try!(self.select(channel));
+    self.reg.cr2
+      .set_continuous(false)
+      .set_ext_select(SOFTWARE_TRIGGER);
+    self.start();
+    wait_for!(self.reg.sr.eoc());
+    Ok(self.reg.dr.data() as u16)
+  }
+
+  fn start_continuous(&self, channel: u8) -> Result<(), Error> {
+    try!(self.select(channel));
+    self.reg.cr2
+      .set_continuous(true)
+      .set_ext_select(SOFTWARE_TRIGGER);
+    self.start();
+    Ok(())
+  }
+
+  fn stop(&self) {
+    // Going back to software trigger stops triggered conversions, there is
+    // no software trigger left to restart them.
+    self.reg.cr2
+      .set_continuous(false)
+      .set_ext_select(SOFTWARE_TRIGGER);
+  }
+
+  fn latest(&self) -> u16 {
+    self.reg.dr.data() as u16
+  }
+}
+
+mod reg {
+  use volatile_cell::VolatileCell;
+  use core::ops::Drop;
+
+  ioregs!(ADC = {
+    0x00 => reg32 sr {      // status
+      0 => analog_watchdog : rw,
+      1 => eoc : rw,        // end of conversion, cleared by reading dr
+      2 => injected_eoc : rw,
+      3 => injected_start : rw,
+      4 => start : rw,
+    },
+    0x04 => reg32 cr1 {     // control 1
+      8 => scan : rw,
+      19..16 => dual_mode : rw,
+    },
+    0x08 => reg32 cr2 {     // control 2
+      0 => power_on : rw,
+      1 => continuous : rw,
+      2 => calibration : rw,
+      3 => reset_calibration : rw,
+      8 => dma : rw,
+      11 => left_align : rw,
+      19..17 => ext_select : rw,
+      20 => ext_trigger : rw,
+      22 => sw_start : rw,
+      23 => temp_sensor : rw,
+    },
+    0x0C => reg32 smpr1 {   // sample time of channels 10 to 17
+      23..0 => smp[8] : rw,
+    },
+    0x10 => reg32 smpr2 {   // sample time of channels 0 to 9
+      29..0 => smp[10] : rw,
+    },
+    0x2C => reg32 sqr1 {    // regular sequence 1
+      23..20 => length : rw,
+    },
+    0x34 => reg32 sqr3 {    // regular sequence 3, conversions 1 to 6
+      29..0 => sq[6] : rw,
+    },
+    0x4C => reg32 dr {      // regular data
+      15..0 => data : ro,
+    },
+  });
+
+  extern {
+    #[link_name="stm32f1_iomem_ADC1"] pub static ADC1: ADC;
+    #[link_name="stm32f1_iomem_ADC2"] pub static ADC2: ADC;
+    #[link_name="stm32f1_iomem_ADC3"] pub static ADC3: ADC;
+  }
+}
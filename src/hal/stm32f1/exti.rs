@@ -0,0 +1,84 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! External interrupt controller (EXTI) for STM32F1.
+//!
+//! Each of the 16 EXTI lines serves the pin with the same index, on the port
+//! selected through AFIO. Only one port can be routed to a given line, so
+//! e.g. PA3 and PB3 can't both have an interrupt enabled. The line logic is
+//! shared with the other STM32 families, see `hal::stm32_common::exti`.
+//!
+//! Lines 0 to 4 have their own interrupt, while lines 5 to 9 and 10 to 15
+//! share `isr_exti9_5` and `isr_exti15_10`. `isr_handler` must be called from
+//! these ISRs with the lines they serve.
+
+use core::option::Option;
+
+use hal::cortex_m3::nvic::Interrupt;
+use hal::pin::{GpioEdge, GpioHandler};
+use hal::stm32_common::exti as common;
+use hal::stm32f1::irq::Irq;
+use hal::stm32f1::peripheral_clock as clock;
+
+/// Sets the handler called when `line` triggers.
+pub fn set_handler(line: usize, handler: Option<GpioHandler>) {
+  common::set_handler(line, handler);
+}
+
+/// Routes `line` to `port` and enables its interrupt on the given edge.
+///
+/// Ports are numbered from 0 for port A.
+pub fn enable(line: usize, port: u8, edge: GpioEdge) {
+  clock::PeripheralClock::Apb2(clock::BusApb2::Afio).enable();
+  common::enable(&reg::EXTI, &reg::AFIO, line, port, edge,
+                 line_irq(line).number());
+}
+
+/// Masks the interrupt of `line`.
+pub fn disable(line: usize) {
+  common::disable(&reg::EXTI, line);
+}
+
+/// Clears the pending flag of `line`.
+pub fn clear(line: usize) {
+  common::clear(&reg::EXTI, line);
+}
+
+fn line_irq(line: usize) -> Irq {
+  match line {
+    0     => Irq::Exti0,
+    1     => Irq::Exti1,
+    2     => Irq::Exti2,
+    3     => Irq::Exti3,
+    4     => Irq::Exti4,
+    5...9 => Irq::Exti9To5,
+    _     => Irq::Exti15To10,
+  }
+}
+
+/// Services the interrupt of EXTI lines `first` to `last` (inclusive),
+/// calling the handlers of the lines that triggered.
+pub fn isr_handler(first: usize, last: usize) {
+  common::isr_handler(&reg::EXTI, first, last);
+}
+
+mod reg {
+  use hal::stm32_common::exti::reg::{EXTI, EXTICR};
+
+  extern {
+    #[link_name="stm32f1_iomem_EXTI"] pub static EXTI: EXTI;
+    #[link_name="stm32f1_iomem_AFIO"] pub static AFIO: EXTICR;
+  }
+}
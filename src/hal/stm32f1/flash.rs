@@ -0,0 +1,152 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Flash programming for STM32F1.
+//!
+//! Flash is programmed a half-word at a time and erased a page at a time.
+//! Pages are 1KiB on low and medium density parts, 2KiB on the others. Only
+//! the first 512KiB bank is supported. The HSI oscillator must be on while
+//! flash is written, and the core stalls on instruction fetches until the
+//! operation completes.
+
+use core::cmp::min;
+use core::intrinsics::{volatile_load, volatile_store};
+use core::result::Result::{self, Ok, Err};
+
+use hal::flash::{self, Error, Sector};
+use hal::stm32f1::init::reg::FLASH;
+
+#[path="../../util/wait_for.rs"]
+#[macro_use] mod wait_for;
+
+/// Start of flash.
+const FLASH_BASE: u32 = 0x0800_0000;
+
+/// Flash size register, in KiB.
+const FLASH_SIZE_KB: *const u16 = 0x1FFF_F7E0 as *const u16;
+
+/// Size of the first bank, the second bank of XL-density parts has its own
+/// registers.
+const MAX_SIZE: u32 = 512 * 1024;
+
+/// Largest part with 1KiB pages.
+const MAX_SMALL_PAGE_SIZE: u32 = 128 * 1024;
+
+/// Key sequence unlocking the control register.
+const KEY1: u32 = 0x4567_0123;
+const KEY2: u32 = 0xCDEF_89AB;
+
+/// Structure describing the flash.
+#[derive(Clone, Copy)]
+pub struct Flash {
+  size: u32,
+  page_size: u32,
+}
+
+impl Flash {
+  /// Returns the flash, sized from the flash size register.
+  pub fn new() -> Flash {
+    let size = unsafe { volatile_load(FLASH_SIZE_KB) } as u32 * 1024;
+    Flash {
+      size: min(size, MAX_SIZE),
+      page_size: if size > MAX_SMALL_PAGE_SIZE { 2048 } else { 1024 },
+    }
+  }
+
+  fn check_range(&self, addr: u32) -> Result<(), Error> {
+    if addr < FLASH_BASE || addr - FLASH_BASE >= self.size {
+      Err(Error::OutOfRange)
+    } else {
+      Ok(())
+    }
+  }
+}
+
+impl flash::Flash for Flash {
+  fn sector_count(&self) -> usize {
+    (self.size / self.page_size) as usize
+  }
+
+  fn sector(&self, index: usize) -> Sector {
+    Sector {
+      start: FLASH_BASE + index as u32 * self.page_size,
+      size: self.page_size,
+    }
+  }
+
+  fn erase(&self, index: usize) -> Result<(), Error> {
+    if index >= flash::Flash::sector_count(self) {
+      return Err(Error::OutOfRange);
+    }
+    unlock();
+    FLASH.cr.set_page_erase(true);
+    FLASH.ar.set_address(FLASH_BASE + index as u32 * self.page_size);
+    FLASH.cr.set_start(true);
+    let result = finish();
+    FLASH.cr.set_page_erase(false);
+    lock();
+    result
+  }
+
+  fn program_word(&self, addr: u32, value: u32) -> Result<(), Error> {
+    if addr & 3 != 0 {
+      return Err(Error::Unaligned);
+    }
+    try!(flash::Flash::program_half_word(self, addr, value as u16));
+    flash::Flash::program_half_word(self, addr + 2, (value >> 16) as u16)
+  }
+
+  fn program_half_word(&self, addr: u32, value: u16) -> Result<(), Error> {
+    if addr & 1 != 0 {
+      return Err(Error::Unaligned);
+    }
+    try!(self.check_range(addr));
+    unlock();
+    FLASH.cr.set_program(true);
+    unsafe { volatile_store(addr as *mut u16, value) };
+    let result = finish();
+    FLASH.cr.set_program(false);
+    lock();
+    result
+  }
+}
+
+fn unlock() {
+  if FLASH.cr.lock() {
+    FLASH.keyr.set_key(KEY1);
+    FLASH.keyr.set_key(KEY2);
+  }
+}
+
+fn lock() {
+  FLASH.cr.set_lock(true);
+}
+
+/// Waits for the running operation and clears its status flags.
+fn finish() -> Result<(), Error> {
+  wait_for!(!FLASH.sr.busy());
+  let status = FLASH.sr.get();
+  FLASH.sr.ignoring_state()
+    .set_programming_error(true)
+    .set_write_protection_error(true)
+    .set_end_of_operation(true);
+  if status.write_protection_error() {
+    Err(Error::WriteProtected)
+  } else if status.programming_error() {
+    Err(Error::Failed)
+  } else {
+    Ok(())
+  }
+}
@@ -362,6 +362,14 @@ impl ClockConfig {
       self.get_ahb_frequency() >> shift
   }
 
+  /// Returns the clock frequency of the APB1 timers
+  ///
+  /// Timers run at twice the APB1 frequency when it's divided.
+  pub fn get_apb1_timer_frequency(&self) -> u32 {
+      let apb1 = self.get_apb1_frequency();
+      if apb1 == self.get_ahb_frequency() { apb1 } else { apb1 * 2 }
+  }
+
   /// Returns APB2 clock frequency
   pub fn get_apb2_frequency(&self) -> u32 {
       let shift = match self.apb2_prescaler {
@@ -455,14 +463,40 @@ pub mod reg {
       4    => prefetch_buf_on : rw,
       5    => prefetch_buf_status : ro,
     },
+    0x04 => reg32 keyr {    // key
+      31..0 => key : wo,
+    },
+    0x0C => reg32 sr {      // status
+      0 => busy : ro,
+      2 => programming_error : rw,
+      4 => write_protection_error : rw,
+      5 => end_of_operation : rw,
+    },
+    0x10 => reg32 cr {      // control
+      0 => program : rw,
+      1 => page_erase : rw,
+      2 => mass_erase : rw,
+      6 => start : rw,
+      7 => lock : rw,
+    },
+    0x14 => reg32 ar {      // address
+      31..0 => address : wo,
+    },
   });
 
   ioregs!(PWR = {
     0x0 => reg32 cr {   // power control
-      31..0 => control : rw,
+      0 => low_power_deepsleep : rw,
+      1 => power_down_deepsleep : rw,
+      2 => clear_wakeup_flag : rw,
+      3 => clear_standby_flag : rw,
+      8 => backup_write_enable : rw,
     },
     0x4 => reg32 csr {  // power control/status
-      31..0 => status : rw,
+      0 => wakeup_flag : ro,
+      1 => standby_flag : ro,
+      2 => pvd_output : ro,
+      8 => wakeup_pin_enable : rw,
     },
   });
 
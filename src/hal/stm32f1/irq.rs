@@ -0,0 +1,91 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Interrupt lines and NVIC vector table for stm32f1.
+
+use hal::cortex_m3::nvic::Interrupt;
+
+#[path="../irq_list.rs"]
+#[macro_use] mod irq_list;
+
+irqs! {
+  // s.a. RM0008, table 63
+  Wwdg             => isr_wwdg,
+  Pvd              => isr_pvd,
+  Tamper           => isr_tamper,
+  Rtc              => isr_rtc,
+  Flash            => isr_flash,
+  Rcc              => isr_rcc,
+  Exti0            => isr_exti0,
+  Exti1            => isr_exti1,
+  Exti2            => isr_exti2,
+  Exti3            => isr_exti3,
+  Exti4            => isr_exti4,
+  Dma1Channel1     => isr_dma1_channel1,
+  Dma1Channel2     => isr_dma1_channel2,
+  Dma1Channel3     => isr_dma1_channel3,
+  Dma1Channel4     => isr_dma1_channel4,
+  Dma1Channel5     => isr_dma1_channel5,
+  Dma1Channel6     => isr_dma1_channel6,
+  Dma1Channel7     => isr_dma1_channel7,
+  Adc1And2         => isr_adc1_2,
+  UsbHpCanTx       => isr_usb_hp_can_tx,
+  UsbLpCanRx0      => isr_usb_lp_can_rx0,
+  CanRx1           => isr_can_rx1,
+  CanSce           => isr_can_sce,
+  Exti9To5         => isr_exti9_5,
+  Tim1Brk          => isr_tim1_brk,
+  Tim1Up           => isr_tim1_up,
+  Tim1TrgCom       => isr_tim1_trg_com,
+  Tim1Cc           => isr_tim1_cc,
+  Tim2             => isr_tim2,
+  Tim3             => isr_tim3,
+  Tim4             => isr_tim4,
+  I2c1Ev           => isr_i2c1_ev,
+  I2c1Er           => isr_i2c1_er,
+  I2c2Ev           => isr_i2c2_ev,
+  I2c2Er           => isr_i2c2_er,
+  Spi1             => isr_spi1,
+  Spi2             => isr_spi2,
+  Usart1           => isr_usart1,
+  Usart2           => isr_usart2,
+  Usart3           => isr_usart3,
+  Exti15To10       => isr_exti15_10,
+  RtcAlarm         => isr_rtc_alarm,
+  UsbWakeup        => isr_usb_wakeup,
+  Tim8Brk          => isr_tim8_brk,
+  Tim8Up           => isr_tim8_up,
+  Tim8TrgCom       => isr_tim8_trg_com,
+  Tim8Cc           => isr_tim8_cc,
+  Adc3             => isr_adc3,
+  Fsmc             => isr_fsmc,
+  Sdio             => isr_sdio,
+  Tim5             => isr_tim5,
+  Spi3             => isr_spi3,
+  Uart4            => isr_uart4,
+  Uart5            => isr_uart5,
+  Tim6             => isr_tim6,
+  Tim7             => isr_tim7,
+  Dma2Channel1     => isr_dma2_channel1,
+  Dma2Channel2     => isr_dma2_channel2,
+  Dma2Channel3     => isr_dma2_channel3,
+  Dma2Channel4And5 => isr_dma2_channel4_5,
+}
+
+impl Interrupt for Irq {
+  fn number(&self) -> usize {
+    *self as usize
+  }
+}
@@ -15,9 +15,21 @@
 
 //! HAL for STM32F1.
 
+pub mod adc;
+pub mod capture;
+pub mod dac;
+pub mod dma;
+pub mod exti;
+pub mod flash;
+pub mod i2c;
+pub mod irq;
 pub mod init;
 pub mod peripheral_clock;
 pub mod pin;
+pub mod power;
+pub mod pwm;
+pub mod rtc;
 pub mod spi;
 pub mod timer;
 pub mod usart;
+pub mod watchdog;
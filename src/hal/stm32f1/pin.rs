@@ -17,9 +17,15 @@
 //!
 //! Some pins that could be configured here may be missing from actual MCU
 //! depending on the package.
+//!
+//! Edge interrupts are served by the EXTI lines, see `stm32f1::exti`.
 
+use super::exti;
 use super::peripheral_clock;
 use core::intrinsics::abort;
+use core::option::Option;
+use core::result::Result::{self, Ok, Err};
+use hal::pin::{ConfigError, GpioEdge, GpioHandler};
 use self::Port::*;
 
 /// Available port names.
@@ -67,6 +73,8 @@ pub enum PinConf {
 pub struct Pin {
   /// Pin index.
   pub index: u8,
+  /// Port the pin belongs to
+  port: Port,
   /// GPIO register
   reg: &'static reg::GPIO,
 }
@@ -111,21 +119,41 @@ impl Pin {
       OutOpenDrainAlt50MHz => 0b11_11,
     };
 
-    let offset = (pin_index % 8) as usize * 4;
+    let pin = Pin {
+      index: pin_index,
+      port: port,
+      reg: reg,
+    };
+    pin.set_conf(conf);
+    pin
+  }
+
+  /// Returns the CNF and MODE bits of the pin.
+  fn conf(&self) -> u32 {
+    let offset = (self.index % 8) as usize * 4;
+    let cr = if self.index < 8 {
+      self.reg.crlr.crl()
+    } else {
+      self.reg.crhr.crh()
+    };
+    (cr >> offset) & 0xF
+  }
+
+  fn set_conf(&self, conf: u32) {
+    let offset = (self.index % 8) as usize * 4;
     let mask = !(0xFu32 << offset);
 
-    if pin_index < 8 {
-        let mode: u32 = reg.crlr.crl() & mask;
-        reg.crlr.set_crl(mode | (conf << offset));
+    if self.index < 8 {
+        let mode: u32 = self.reg.crlr.crl() & mask;
+        self.reg.crlr.set_crl(mode | (conf << offset));
     } else {
-        let mode: u32 = reg.crhr.crh() & mask;
-        reg.crhr.set_crh(mode | (conf << offset));
+        let mode: u32 = self.reg.crhr.crh() & mask;
+        self.reg.crhr.set_crh(mode | (conf << offset));
     }
+  }
 
-    Pin {
-      index: pin_index,
-      reg: reg,
-    }
+  fn is_output(&self) -> bool {
+    self.conf() & 0b11 != 0
   }
 }
 
@@ -155,6 +183,93 @@ impl ::hal::pin::Gpio for Pin {
   }
 }
 
+/// The F1 GPIO has no drive strength setting, and ties the other settings to
+/// the pin mode: pulls are only available to inputs, while the output type
+/// and speed only apply to outputs. Settings that don't apply to the current
+/// mode are rejected.
+impl ::hal::pin::Electrical for Pin {
+  fn set_pull(&self, pull: ::hal::pin::Pull) -> Result<(), ConfigError> {
+    use hal::pin::Pull;
+    if self.is_output() {
+      return match pull {
+        Pull::Floating => Ok(()),
+        _              => Err(ConfigError::UnsupportedPull),
+      };
+    }
+    // Analog inputs can't have pulls either.
+    if self.conf() == 0b00_00 && pull != Pull::Floating {
+      return Err(ConfigError::UnsupportedPull);
+    }
+    let bit: u32 = 1 << self.index as usize;
+    match pull {
+      Pull::Floating => {
+        if self.conf() != 0b00_00 {
+          self.set_conf(0b01_00);
+        }
+      },
+      Pull::Up => {
+        self.set_conf(0b10_00);
+        self.reg.bsrr.set_set(bit);
+      },
+      Pull::Down => {
+        self.set_conf(0b10_00);
+        self.reg.bsrr.set_reset(bit);
+      },
+    }
+    Ok(())
+  }
+
+  fn set_output_type(&self, output_type: ::hal::pin::OutputType)
+      -> Result<(), ConfigError> {
+    if !self.is_output() {
+      return Err(ConfigError::UnsupportedOutputType);
+    }
+    let conf = self.conf() & !0b01_00;
+    self.set_conf(match output_type {
+      ::hal::pin::OutputType::PushPull  => conf,
+      ::hal::pin::OutputType::OpenDrain => conf | 0b01_00,
+    });
+    Ok(())
+  }
+
+  fn set_drive_strength(&self, _strength: ::hal::pin::DriveStrength)
+      -> Result<(), ConfigError> {
+    Err(ConfigError::UnsupportedDriveStrength)
+  }
+
+  /// The output speed is used as slew rate, slow is 2 MHz and fast is 50 MHz.
+  fn set_slew_rate(&self, rate: ::hal::pin::SlewRate)
+      -> Result<(), ConfigError> {
+    if !self.is_output() {
+      return Err(ConfigError::UnsupportedSlewRate);
+    }
+    let conf = self.conf() & !0b00_11;
+    self.set_conf(match rate {
+      ::hal::pin::SlewRate::Slow => conf | 0b00_10,
+      ::hal::pin::SlewRate::Fast => conf | 0b00_11,
+    });
+    Ok(())
+  }
+}
+
+impl ::hal::pin::GpioInterrupt for Pin {
+  fn set_handler(&self, handler: Option<GpioHandler>) {
+    exti::set_handler(self.index as usize, handler);
+  }
+
+  fn enable_interrupt(&self, edge: GpioEdge) {
+    exti::enable(self.index as usize, self.port as u8, edge);
+  }
+
+  fn disable_interrupt(&self) {
+    exti::disable(self.index as usize);
+  }
+
+  fn clear_interrupt(&self) {
+    exti::clear(self.index as usize);
+  }
+}
+
 mod reg {
   use volatile_cell::VolatileCell;
   use core::ops::Drop;
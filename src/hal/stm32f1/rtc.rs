@@ -0,0 +1,216 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Real-time clock for STM32F1.
+//!
+//! The F1 RTC is a 32-bit seconds counter in the backup domain, clocked from
+//! the 32.768kHz LSE oscillator. The counter holds the time in seconds since
+//! the epoch, and is converted to a calendar date when read.
+//!
+//! Alarms use the RTC global interrupt, so `isr_handler` must be called from
+//! `isr_rtc`.
+
+use core::intrinsics::abort;
+use core::option::Option::{self, Some, None};
+
+use hal::cortex_m3::irq::NoInterrupts;
+use hal::cortex_m3::nvic;
+use hal::rtc::{self, AlarmHandler, DateTime};
+use hal::stm32f1::init;
+use hal::stm32f1::irq::Irq;
+use hal::stm32f1::peripheral_clock as clock;
+use util::shared::Shared;
+
+#[path="../../util/wait_for.rs"]
+#[macro_use] mod wait_for;
+
+/// RTC clock selection of the LSE oscillator.
+const RTC_SOURCE_LSE: u32 = 0b01;
+
+/// Prescaler reload value giving 1Hz ticks from the LSE.
+const LSE_PRESCALER: u32 = 32767;
+
+static HANDLER: Shared<Option<AlarmHandler>> = Shared::new(None);
+
+/// Structure describing the RTC.
+#[derive(Clone, Copy)]
+pub struct Rtc {
+  reg: &'static reg::RTC,
+}
+
+impl Rtc {
+  /// Enables access to the backup domain and starts the RTC on the LSE if
+  /// it's stopped.
+  ///
+  /// The time is kept if the clock was already running.
+  pub fn new() -> Rtc {
+    clock::PeripheralClock::Apb1(clock::BusApb1::Pwr).enable();
+    clock::PeripheralClock::Apb1(clock::BusApb1::Bkp).enable();
+    init::reg::PWR.cr.set_backup_write_enable(true);
+
+    let rtc = Rtc { reg: &reg::RTC };
+    let rcc = &init::reg::RCC;
+    if !rcc.bdcr.rtc_on() {
+      rcc.bdcr.set_lse_on(true);
+      wait_for!(rcc.bdcr.lse_ready());
+      rcc.bdcr.set_rtc_source(RTC_SOURCE_LSE).set_rtc_on(true);
+
+      rtc.configure(|| {
+        rtc.reg.prlh.set_value(LSE_PRESCALER >> 16);
+        rtc.reg.prll.set_value(LSE_PRESCALER & 0xFFFF);
+      });
+    }
+
+    // Registers can't be read until they're synchronised with the RTC clock.
+    rtc.reg.crl.set_rsf(false);
+    wait_for!(rtc.reg.crl.rsf());
+    rtc
+  }
+
+  /// Runs `f` in configuration mode, where the counter, prescaler and alarm
+  /// can be written.
+  fn configure<F: Fn()>(&self, f: F) {
+    wait_for!(self.reg.crl.rtoff());
+    self.reg.crl.set_cnf(true);
+    f();
+    self.reg.crl.set_cnf(false);
+    wait_for!(self.reg.crl.rtoff());
+  }
+
+  fn counter(&self) -> u32 {
+    // The halves can't be read together, so read them again if the low half
+    // wrapped in between.
+    let mut high = self.reg.cnth.value();
+    let mut low = self.reg.cntl.value();
+    while self.reg.cnth.value() != high {
+      high = self.reg.cnth.value();
+      low = self.reg.cntl.value();
+    }
+    high << 16 | low
+  }
+}
+
+impl rtc::Rtc for Rtc {
+  fn set_time(&self, time: &DateTime) {
+    if !time.is_valid() {
+      unsafe { abort() };
+    }
+    let seconds = time.to_epoch();
+    self.configure(|| {
+      self.reg.cnth.set_value(seconds >> 16);
+      self.reg.cntl.set_value(seconds & 0xFFFF);
+    });
+  }
+
+  fn get_time(&self) -> DateTime {
+    DateTime::from_epoch(self.counter())
+  }
+
+  fn get_epoch(&self) -> u32 {
+    self.counter()
+  }
+
+  fn set_alarm(&self, time: &DateTime, handler: AlarmHandler) {
+    if !time.is_valid() {
+      unsafe { abort() };
+    }
+    self.reg.crh.set_alrie(false);
+    {
+      let crit = NoInterrupts::new();
+      *HANDLER.borrow(&crit) = Some(handler);
+    }
+
+    let seconds = time.to_epoch();
+    self.configure(|| {
+      self.reg.alrh.set_value(seconds >> 16);
+      self.reg.alrl.set_value(seconds & 0xFFFF);
+    });
+
+    self.reg.crl.set_alrf(false);
+    self.reg.crh.set_alrie(true);
+    nvic::enable_irq(Irq::Rtc);
+  }
+
+  fn cancel_alarm(&self) {
+    self.reg.crh.set_alrie(false);
+    let crit = NoInterrupts::new();
+    *HANDLER.borrow(&crit) = None;
+  }
+}
+
+/// Services the RTC interrupt, calling the alarm handler once.
+pub fn isr_handler() {
+  let rtc = &reg::RTC;
+  if !rtc.crl.alrf() || !rtc.crh.alrie() {
+    return;
+  }
+  rtc.crl.set_alrf(false);
+  rtc.crh.set_alrie(false);
+
+  let handler = {
+    let crit = NoInterrupts::new();
+    let mut handler = HANDLER.borrow(&crit);
+    let current = *handler;
+    *handler = None;
+    current
+  };
+  match handler {
+    Some(handler) => handler(),
+    None => (),
+  }
+}
+
+mod reg {
+  use volatile_cell::VolatileCell;
+  use core::ops::Drop;
+
+  ioregs!(RTC = {
+    0x00 => reg32 crh {      // control high
+      0     => secie : rw,
+      1     => alrie : rw,
+      2     => owie : rw,
+    },
+    0x04 => reg32 crl {      // control low, flags are cleared by writing 0
+      0     => secf : rw,
+      1     => alrf : rw,
+      2     => owf : rw,
+      3     => rsf : rw,
+      4     => cnf : rw,
+      5     => rtoff : ro,
+    },
+    0x08 => reg32 prlh {     // prescaler load high
+      3..0  => value : wo,
+    },
+    0x0C => reg32 prll {     // prescaler load low
+      15..0 => value : wo,
+    },
+    0x18 => reg32 cnth {     // counter high
+      15..0 => value : rw,
+    },
+    0x1C => reg32 cntl {     // counter low
+      15..0 => value : rw,
+    },
+    0x20 => reg32 alrh {     // alarm high
+      15..0 => value : wo,
+    },
+    0x24 => reg32 alrl {     // alarm low
+      15..0 => value : wo,
+    },
+  });
+
+  extern {
+    #[link_name="stm32f1_iomem_RTC"] pub static RTC: RTC;
+  }
+}
@@ -16,15 +16,28 @@
 /*!
 Universal synchronous asynchronous receiver transmitter (USART).
 
+`BufferedUsart` is an interrupt-driven alternative to `Usart`. It queues data
+in static ring buffers that are serviced by `isr_handler`, which must be
+called from the USART interrupt of the peripheral.
+
+`Usart` can also move blocks of data with a DMA channel claimed for its
+request, see `write_dma` and `read_dma`.
 */
 
 use core::fmt;
-use core::result::Result;
+use core::option::Option::{Some, None};
+use core::result::Result::{self, Ok, Err};
 use core::intrinsics::abort;
 
-use drivers::chario::CharIO;
+use drivers::chario::{CharIO, CharInput, ReadError};
+use hal::cortex_m3::irq::NoInterrupts;
+use hal::cortex_m3::nvic;
+use hal::dma::Channel;
+use hal::stm32f1::dma;
+use hal::stm32f1::irq::Irq;
 use hal::uart;
 use hal::stm32f1::init;
+use util::shared::Shared;
 
 use self::UsartPeripheral::*;
 
@@ -43,6 +56,38 @@ pub enum UsartPeripheral {
   Uart5,
 }
 
+impl UsartPeripheral {
+  fn reg(self) -> &'static reg::USART {
+    match self {
+      Usart1 => &reg::USART1,
+      Usart2 => &reg::USART2,
+      Usart3 => &reg::USART3,
+      Uart4  => &reg::UART4,
+      Uart5  => &reg::UART5,
+    }
+  }
+
+  fn buffers(self) -> &'static Shared<uart::Buffers> {
+    match self {
+      Usart1 => &USART1_BUFFERS,
+      Usart2 => &USART2_BUFFERS,
+      Usart3 => &USART3_BUFFERS,
+      Uart4  => &UART4_BUFFERS,
+      Uart5  => &UART5_BUFFERS,
+    }
+  }
+
+  fn irq(self) -> Irq {
+    match self {
+      Usart1 => Irq::Usart1,
+      Usart2 => Irq::Usart2,
+      Usart3 => Irq::Usart3,
+      Uart4  => Irq::Uart4,
+      Uart5  => Irq::Uart5,
+    }
+  }
+}
+
 /// USART word length.
 #[allow(missing_docs)]
 #[repr(u8)]
@@ -81,12 +126,13 @@ impl Usart {
     use hal::stm32f1::peripheral_clock as clock;
     use hal::uart::Parity::*;
 
-    let (reg, clock) = match peripheral {
-        Usart1 => (&reg::USART1, PeripheralClock::Apb2(clock::BusApb2::Usart1)),
-        Usart2 => (&reg::USART2, PeripheralClock::Apb1(clock::BusApb1::Usart2)),
-        Usart3 => (&reg::USART3, PeripheralClock::Apb1(clock::BusApb1::Usart3)),
-        Uart4  => (&reg::UART4,  PeripheralClock::Apb1(clock::BusApb1::Uart4)),
-        Uart5  => (&reg::UART5,  PeripheralClock::Apb1(clock::BusApb1::Uart5)),
+    let reg = peripheral.reg();
+    let clock = match peripheral {
+        Usart1 => PeripheralClock::Apb2(clock::BusApb2::Usart1),
+        Usart2 => PeripheralClock::Apb1(clock::BusApb1::Usart2),
+        Usart3 => PeripheralClock::Apb1(clock::BusApb1::Usart3),
+        Uart4  => PeripheralClock::Apb1(clock::BusApb1::Uart4),
+        Uart5  => PeripheralClock::Apb1(clock::BusApb1::Uart5),
     };
 
     clock.enable();
@@ -120,6 +166,59 @@ impl Usart {
       reg: reg,
     }
   }
+
+  /// Writes `data` through a DMA channel, blocking until it's queued.
+  ///
+  /// `channel` must be claimed for the TX request of this USART.
+  pub fn write_dma(&self, channel: &dma::Channel, data: &[u8])
+      -> Result<(), ::hal::dma::Error> {
+    for chunk in data.chunks(dma::MAX_TRANSFER) {
+      unsafe { self.start_write_dma(channel, chunk) };
+      try!(channel.wait());
+    }
+    Ok(())
+  }
+
+  /// Starts writing `data` through a DMA channel and returns immediately.
+  ///
+  /// `channel` must be claimed for the TX request of this USART. Completion
+  /// is reported by the channel.
+  ///
+  /// This is unsafe as `data` must stay valid until the transfer completes.
+  pub unsafe fn start_write_dma(&self, channel: &dma::Channel, data: &[u8]) {
+    self.reg.cr3.set_dma_transmitter_enable(true);
+    channel.memory_to_peripheral(data.as_ptr(), true, data.len(),
+        self.data_address());
+  }
+
+  /// Fills `buffer` through a DMA channel, blocking until it's full.
+  ///
+  /// `channel` must be claimed for the RX request of this USART.
+  pub fn read_dma(&self, channel: &dma::Channel, buffer: &mut [u8])
+      -> Result<(), ::hal::dma::Error> {
+    for chunk in buffer.chunks_mut(dma::MAX_TRANSFER) {
+      unsafe { self.start_read_dma(channel, chunk) };
+      try!(channel.wait());
+    }
+    Ok(())
+  }
+
+  /// Starts filling `buffer` through a DMA channel and returns immediately.
+  ///
+  /// `channel` must be claimed for the RX request of this USART. Completion
+  /// is reported by the channel.
+  ///
+  /// This is unsafe as `buffer` must stay valid until the transfer completes.
+  pub unsafe fn start_read_dma(&self, channel: &dma::Channel,
+                               buffer: &mut [u8]) {
+    self.reg.cr3.set_dma_receiver_enable(true);
+    channel.peripheral_to_memory(self.data_address(), buffer.as_mut_ptr(),
+        buffer.len());
+  }
+
+  fn data_address(&self) -> u32 {
+    &self.reg.dr as *const reg::USART_dr as u32
+  }
 }
 
 impl CharIO for Usart {
@@ -129,6 +228,28 @@ impl CharIO for Usart {
   }
 }
 
+impl CharInput for Usart {
+  fn try_getc(&self) -> Result<Option<char>, ReadError> {
+    // Error flags are cleared by reading SR followed by DR, so the data
+    // register is always read, even if the character is to be dropped.
+    let sr = self.reg.sr.get();
+    if sr.error_framing() || sr.error_parity() || sr.error_overrun() {
+      let _ = self.reg.dr.data();
+      return Err(if sr.error_framing() {
+        ReadError::Framing
+      } else if sr.error_parity() {
+        ReadError::Parity
+      } else {
+        ReadError::Overrun
+      });
+    }
+    if !sr.read_data_not_empty() {
+      return Ok(None);
+    }
+    Ok(Some(self.reg.dr.data() as u8 as char))
+  }
+}
+
 impl fmt::Write for Usart {
   fn write_str(&mut self, s: &str) -> fmt::Result {
     use core::str::StrExt;
@@ -136,10 +257,115 @@ impl fmt::Write for Usart {
       wait_for!(self.reg.sr.transmit_data_empty());
       self.reg.dr.set_data(b as u16);
     }
-    Result::Ok(())
+    Ok(())
   }
 }
 
+/// Interrupt-driven USART.
+///
+/// `putc` only blocks when the transmit buffer is full, so it must not be
+/// called with interrupts disabled.
+pub struct BufferedUsart {
+  usart: Usart,
+  buffers: &'static Shared<uart::Buffers>,
+}
+
+impl BufferedUsart {
+  /// Create a new USART port, enabling its receive interrupts.
+  pub fn new(peripheral: UsartPeripheral, baudrate: u32, word_len: WordLen,
+             parity: uart::Parity, stop_bits: StopBit,
+             config: &init::ClockConfig) -> BufferedUsart {
+    let buffered = BufferedUsart {
+      usart: Usart::new(peripheral, baudrate, word_len, parity, stop_bits,
+                        config),
+      buffers: peripheral.buffers(),
+    };
+
+    {
+      let crit = NoInterrupts::new();
+      let mut buffers = buffered.buffers.borrow(&crit);
+      buffers.tx.clear();
+      buffers.rx.clear();
+      buffers.error = None;
+    }
+
+    buffered.usart.reg.cr1
+      .set_int_read_data_not_empty_enable(true)
+      .set_int_pe_enable(true);
+    nvic::enable_irq(peripheral.irq());
+
+    buffered
+  }
+}
+
+impl CharIO for BufferedUsart {
+  fn putc(&self, value: char) {
+    loop {
+      let crit = NoInterrupts::new();
+      let mut buffers = self.buffers.borrow(&crit);
+      if buffers.tx.push(value as u8) {
+        self.usart.reg.cr1.set_int_transmission_data_empty_enable(true);
+        return;
+      }
+      // Buffer is full, let the ISR drain it.
+    }
+  }
+}
+
+impl CharInput for BufferedUsart {
+  fn try_getc(&self) -> Result<Option<char>, ReadError> {
+    let crit = NoInterrupts::new();
+    let mut buffers = self.buffers.borrow(&crit);
+    match buffers.error.take() {
+      Some(err) => Err(err),
+      None      => Ok(buffers.rx.pop().map(|c| c as char)),
+    }
+  }
+}
+
+/// Services the interrupt of a `BufferedUsart`.
+///
+/// Call it from the USART interrupt of the corresponding peripheral.
+pub fn isr_handler(peripheral: UsartPeripheral) {
+  let reg = peripheral.reg();
+  let crit = NoInterrupts::new();
+  let mut buffers = peripheral.buffers().borrow(&crit);
+
+  // Reading SR followed by DR clears both RXNE and the error flags.
+  let sr = reg.sr.get();
+  if sr.error_framing() || sr.error_parity() {
+    let _ = reg.dr.data();
+    buffers.error = Some(if sr.error_framing() {
+      ReadError::Framing
+    } else {
+      ReadError::Parity
+    });
+  } else if sr.read_data_not_empty() || sr.error_overrun() {
+    if sr.error_overrun() {
+      buffers.error = Some(ReadError::Overrun);
+    }
+    let c = reg.dr.data() as u8;
+    if !buffers.rx.push(c) {
+      buffers.error = Some(ReadError::Overrun);
+    }
+  }
+
+  if sr.transmit_data_empty() && reg.cr1.int_transmission_data_empty_enable() {
+    match buffers.tx.pop() {
+      Some(c) => reg.dr.set_data(c as u16),
+      None    => {
+        reg.cr1.set_int_transmission_data_empty_enable(false);
+      },
+    }
+  }
+}
+
+static USART1_BUFFERS: Shared<uart::Buffers> = Shared::new(uart::Buffers::new());
+static USART2_BUFFERS: Shared<uart::Buffers> = Shared::new(uart::Buffers::new());
+static USART3_BUFFERS: Shared<uart::Buffers> = Shared::new(uart::Buffers::new());
+static UART4_BUFFERS:  Shared<uart::Buffers> = Shared::new(uart::Buffers::new());
+static UART5_BUFFERS:  Shared<uart::Buffers> = Shared::new(uart::Buffers::new());
+
 mod reg {
   use volatile_cell::VolatileCell;
   use core::ops::Drop;
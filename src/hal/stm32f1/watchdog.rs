@@ -0,0 +1,110 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Independent and window watchdogs for STM32F1.
+//!
+//! The independent watchdog (IWDG) runs from the ~40kHz LSI oscillator, whose
+//! frequency varies from 30kHz to 60kHz, so timeouts are approximate. The
+//! window watchdog (WWDG) runs from PCLK1 and is limited to short timeouts,
+//! 58ms at a 36MHz PCLK1. Neither can be stopped until the next reset.
+//!
+//! The watchdog logic is shared with the other STM32 families, see
+//! `hal::stm32_common::watchdog`.
+
+use hal::stm32_common::watchdog as common;
+use hal::stm32f1::init::{self, ClockConfig};
+use hal::stm32f1::peripheral_clock as clock;
+use hal::watchdog;
+
+/// Nominal LSI frequency, in ticks per millisecond.
+const LSI_TICKS_PER_MS: u32 = 40;
+
+/// Structure describing the independent watchdog.
+#[derive(Clone, Copy)]
+pub struct IndependentWatchdog {
+  reg: &'static common::reg::IWDG,
+}
+
+impl IndependentWatchdog {
+  /// Returns the independent watchdog, which is left as is.
+  pub fn new() -> IndependentWatchdog {
+    IndependentWatchdog { reg: &reg::IWDG }
+  }
+}
+
+impl watchdog::Watchdog for IndependentWatchdog {
+  fn start(&self, timeout_ms: u32) {
+    common::start_independent(self.reg, LSI_TICKS_PER_MS, timeout_ms);
+  }
+
+  fn feed(&self) {
+    common::feed_independent(self.reg);
+  }
+
+  fn caused_reset(&self) -> bool {
+    init::reg::RCC.csr.independent_watchdog_reset()
+  }
+
+  fn clear_reset_flags(&self) {
+    init::reg::RCC.csr.set_remove_reset(true);
+  }
+}
+
+/// Structure describing the window watchdog.
+///
+/// The window is left open, so the watchdog can be fed at any time.
+#[derive(Clone, Copy)]
+pub struct WindowWatchdog {
+  reg: &'static common::reg::WWDG,
+  pclk1: u32,
+}
+
+impl WindowWatchdog {
+  /// Returns the window watchdog, which is left as is.
+  pub fn new(clock_config: &ClockConfig) -> WindowWatchdog {
+    WindowWatchdog {
+      reg: &reg::WWDG,
+      pclk1: clock_config.get_apb1_frequency(),
+    }
+  }
+}
+
+impl watchdog::Watchdog for WindowWatchdog {
+  fn start(&self, timeout_ms: u32) {
+    clock::PeripheralClock::Apb1(clock::BusApb1::Wwdg).enable();
+    common::start_window(self.reg, self.pclk1, timeout_ms);
+  }
+
+  fn feed(&self) {
+    common::feed_window(self.reg);
+  }
+
+  fn caused_reset(&self) -> bool {
+    init::reg::RCC.csr.window_watchdog_reset()
+  }
+
+  fn clear_reset_flags(&self) {
+    init::reg::RCC.csr.set_remove_reset(true);
+  }
+}
+
+mod reg {
+  use hal::stm32_common::watchdog::reg::{IWDG, WWDG};
+
+  extern {
+    #[link_name="stm32f1_iomem_IWDG"] pub static IWDG: IWDG;
+    #[link_name="stm32f1_iomem_WWDG"] pub static WWDG: WWDG;
+  }
+}
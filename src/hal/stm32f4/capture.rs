@@ -0,0 +1,195 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Input capture on the STM32F4 general purpose timers.
+//!
+//! TIM2 to TIM5 have four capture channels each, sharing the timer counter.
+//! Timers count microseconds and are limited to 16 bits, so measured periods
+//! must be shorter than 65536us. A timer can't be used for both PWM and
+//! capture.
+//!
+//! Input pins must be configured as inputs, and `isr_handler` must be called
+//! from the interrupt of each timer used for capture.
+
+use core::option::Option::{self, Some, None};
+
+use hal::capture::{self, Callback, EdgeTracker, Measurement};
+use hal::cortex_m4::irq::NoInterrupts;
+use hal::cortex_m4::nvic;
+use hal::stm32f4::init;
+use hal::stm32f4::peripheral_clock::PeripheralClock;
+use hal::stm32f4::pwm::{TimerPeripheral, Channel};
+use util::shared::Shared;
+
+/// Timer tick frequency.
+const TICK_HZ: u32 = 1_000_000;
+
+/// Capture/compare mode setting for input capture from the channel's own
+/// input.
+const IC_DIRECT: u32 = 0b01;
+
+const CC_ENABLE: u32 = 0b0001;
+const CC_FALLING: u32 = 0b0010;
+
+static TRACKERS: Shared<[EdgeTracker; 16]> =
+    Shared::new([EdgeTracker::new(0xffff); 16]);
+
+/// Structure describing a capture channel.
+#[derive(Clone, Copy)]
+pub struct Capture {
+  timer: TimerPeripheral,
+  channel: Channel,
+}
+
+impl Capture {
+  /// Starts capturing edges on `channel` of `timer`.
+  ///
+  /// Setting up another channel of the timer restarts the counter, which
+  /// spoils the measurements in progress.
+  pub fn new(timer: TimerPeripheral, channel: Channel) -> Capture {
+    let (reg, clock, irq) = peripheral(timer);
+    clock.enable();
+
+    let timer_clock = init::apb_low_timer_clock();
+    reg.psc.set_prescaler(timer_clock / TICK_HZ - 1);
+    reg.arr.set_reload(0xffff);
+    reg.egr.set_update(true);
+    reg.cr1.set_counter_enable(true);
+
+    let capture = Capture {
+      timer: timer,
+      channel: channel,
+    };
+    {
+      let crit = NoInterrupts::new();
+      TRACKERS.borrow(&crit)[capture.index()] = EdgeTracker::new(0xffff);
+    }
+
+    let index = channel as usize;
+    reg.ccer.set_cc(index, 0);
+    reg.ccmr[index / 2].set_ic(index % 2, IC_DIRECT);
+    reg.ccer.set_cc(index, CC_ENABLE);
+    reg.dier.set_ccie(index, true);
+    nvic::enable_irq(irq);
+    capture
+  }
+
+  fn index(&self) -> usize {
+    self.timer as usize * 4 + self.channel as usize
+  }
+}
+
+impl capture::InputCapture for Capture {
+  fn tick_hz(&self) -> u32 {
+    TICK_HZ
+  }
+
+  fn measurement(&self) -> Option<Measurement> {
+    let crit = NoInterrupts::new();
+    TRACKERS.borrow(&crit)[self.index()].measurement()
+  }
+
+  fn set_callback(&self, callback: Option<Callback>) {
+    let crit = NoInterrupts::new();
+    TRACKERS.borrow(&crit)[self.index()].set_callback(callback);
+  }
+}
+
+fn peripheral(timer: TimerPeripheral)
+    -> (&'static reg::TIM, PeripheralClock, usize) {
+  match timer {
+    TimerPeripheral::Timer2 => (&reg::TIM2, PeripheralClock::TIM2Clock, 28),
+    TimerPeripheral::Timer3 => (&reg::TIM3, PeripheralClock::TIM3Clock, 29),
+    TimerPeripheral::Timer4 => (&reg::TIM4, PeripheralClock::TIM4Clock, 30),
+    TimerPeripheral::Timer5 => (&reg::TIM5, PeripheralClock::TIM5Clock, 50),
+  }
+}
+
+/// Records the captured edges of `timer`, must be called from its interrupt.
+///
+/// Capture alternates between rising and falling edges, so edges closer than
+/// the interrupt latency are missed.
+pub fn isr_handler(timer: TimerPeripheral) {
+  let (reg, _, _) = peripheral(timer);
+  for channel in 0..4 {
+    if !reg.sr.ccif(channel) || !reg.dier.ccie(channel) {
+      continue;
+    }
+    // Reading the captured value clears the flag.
+    let count = reg.ccr[channel].value();
+
+    let rising = reg.ccer.cc(channel) & CC_FALLING == 0;
+    reg.ccer.set_cc(channel, CC_ENABLE |
+                    if rising { CC_FALLING } else { 0 });
+
+    let completed = {
+      let crit = NoInterrupts::new();
+      let mut trackers = TRACKERS.borrow(&crit);
+      let tracker = &mut trackers[timer as usize * 4 + channel];
+      if rising {
+        tracker.rising(count)
+      } else {
+        tracker.falling(count);
+        None
+      }
+    };
+    match completed {
+      Some((callback, measurement)) => callback(measurement),
+      None => (),
+    }
+  }
+}
+
+mod reg {
+  use volatile_cell::VolatileCell;
+  use core::ops::Drop;
+
+  ioregs!(TIM = {
+    0x00 => reg32 cr1 {      // control 1
+      0     => counter_enable : rw,
+    },
+    0x0C => reg32 dier {     // DMA/interrupt enable
+      4..1  => ccie[4] : rw,
+    },
+    0x10 => reg32 sr {       // status
+      4..1  => ccif[4] : ro,
+    },
+    0x14 => reg32 egr {      // event generation
+      0     => update : wo,
+    },
+    0x18 => reg32 ccmr[2] {  // capture/compare mode, one byte per channel
+      15..0 => ic[2] : rw,
+    },
+    0x20 => reg32 ccer {     // capture/compare enable, 4 bits per channel
+      15..0 => cc[4] : rw,
+    },
+    0x28 => reg32 psc {      // prescaler
+      15..0 => prescaler : rw,
+    },
+    0x2C => reg32 arr {      // auto-reload
+      31..0 => reload : rw,
+    },
+    0x34 => reg32 ccr[4] {   // capture/compare
+      31..0 => value : ro,
+    },
+  });
+
+  extern {
+    #[link_name="stm32f4_iomem_TIM2"] pub static TIM2: TIM;
+    #[link_name="stm32f4_iomem_TIM3"] pub static TIM3: TIM;
+    #[link_name="stm32f4_iomem_TIM4"] pub static TIM4: TIM;
+    #[link_name="stm32f4_iomem_TIM5"] pub static TIM5: TIM;
+  }
+}
@@ -0,0 +1,236 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Digital to analog converter for STM32F4.
+//!
+//! The DAC has two 12-bit channels, output on PA4 and PA5.
+//!
+//! Waveforms are clocked by TIM6, so `isr_handler` must be called from the
+//! TIM6_DAC interrupt. As both channels share the timer, only one of them can
+//! play a waveform at a time.
+
+use core::option::Option::{self, Some, None};
+
+use hal::cortex_m4::irq::NoInterrupts;
+use hal::cortex_m4::nvic;
+use hal::dac::{self, Waveform};
+use hal::stm32f4::peripheral_clock::PeripheralClock;
+use util::shared::Shared;
+
+/// Largest raw code.
+const MAX_VALUE: u16 = 0xFFF;
+
+/// TIM6_DAC interrupt number.
+const TIM6_IRQ: usize = 54;
+
+/// Trigger selection of the TIM6 TRGO output.
+const TSEL_TIM6: u32 = 0b000;
+
+/// Master mode sending the update event on TRGO.
+const MMS_UPDATE: u16 = 0b010;
+
+/// DAC output channels.
+#[allow(missing_docs)]
+#[derive(PartialEq, Clone, Copy)]
+pub enum Channel {
+  Channel1,
+  Channel2,
+}
+
+static WAVEFORM: Shared<Option<(Waveform, Channel)>> = Shared::new(None);
+
+/// Structure describing a DAC channel.
+#[derive(Clone, Copy)]
+pub struct Dac {
+  channel: Channel,
+}
+
+impl Dac {
+  /// Enables `channel` with its output buffer, and sets the output to 0.
+  ///
+  /// The output pin must be configured as an analog input.
+  pub fn new(channel: Channel) -> Dac {
+    PeripheralClock::DACClock.enable();
+
+    let dac = Dac { channel: channel };
+    dac.write(0);
+    dac.enable(false);
+    dac
+  }
+
+  /// Plays `samples` in a loop, one on each TIM6 update.
+  ///
+  /// TIM6 counts at the APB1 timer clock divided by `prescaler` and updates
+  /// every `period` counts. Samples are loaded by the update event itself, so
+  /// the output doesn't jitter with the interrupt latency.
+  pub fn start_waveform(&self, prescaler: u16, period: u16,
+      samples: &'static [u16]) {
+    self.stop_waveform();
+    let mut waveform = Waveform::new(samples);
+    self.write(waveform.next_sample());
+    {
+      let crit = NoInterrupts::new();
+      *WAVEFORM.borrow(&crit) = Some((waveform, self.channel));
+    }
+    self.enable(true);
+
+    PeripheralClock::TIM6Clock.enable();
+    let tim = &reg::TIM6;
+    tim.cr1.set_counter_enable(false);
+    tim.psc.set_prescaler(prescaler - 1);
+    tim.arr.set_reload(period - 1);
+    tim.cr2.set_master_mode(MMS_UPDATE);
+    // Loads the prescaler and the first sample.
+    tim.egr.set_update(true);
+    tim.sr.ignoring_state().set_update(false);
+    tim.dier.set_update_interrupt(true);
+    nvic::enable_irq(TIM6_IRQ);
+    tim.cr1.set_counter_enable(true);
+  }
+
+  /// Stops the running waveform, leaving its output at the last sample.
+  pub fn stop_waveform(&self) {
+    let crit = NoInterrupts::new();
+    let mut state = WAVEFORM.borrow(&crit);
+    let channel = match *state {
+      Some((_, channel)) => channel,
+      None => return,
+    };
+    *state = None;
+
+    let tim = &reg::TIM6;
+    nvic::disable_irq(TIM6_IRQ);
+    tim.cr1.set_counter_enable(false);
+    tim.dier.set_update_interrupt(false);
+    Dac { channel: channel }.enable(false);
+  }
+
+  fn enable(&self, triggered: bool) {
+    let cr = &reg::DAC.cr;
+    // The trigger can only be changed while the channel is disabled.
+    match self.channel {
+      Channel::Channel1 => {
+        cr.set_en1(false);
+        cr.set_ten1(triggered).set_tsel1(TSEL_TIM6).set_en1(true);
+      },
+      Channel::Channel2 => {
+        cr.set_en2(false);
+        cr.set_ten2(triggered).set_tsel2(TSEL_TIM6).set_en2(true);
+      },
+    }
+  }
+
+  fn write(&self, value: u16) {
+    match self.channel {
+      Channel::Channel1 => { reg::DAC.dhr12r1.set_data(value as u32); },
+      Channel::Channel2 => { reg::DAC.dhr12r2.set_data(value as u32); },
+    }
+  }
+}
+
+impl dac::Dac for Dac {
+  fn bits(&self) -> u32 {
+    12
+  }
+
+  fn set_raw(&self, value: u16) {
+    self.write(if value > MAX_VALUE { MAX_VALUE } else { value });
+  }
+
+  fn raw(&self) -> u16 {
+    (match self.channel {
+      Channel::Channel1 => reg::DAC.dor1.data(),
+      Channel::Channel2 => reg::DAC.dor2.data(),
+    }) as u16
+  }
+}
+
+/// Loads the next waveform sample, must be called from the TIM6_DAC
+/// interrupt.
+pub fn isr_handler() {
+  reg::TIM6.sr.ignoring_state().set_update(false);
+
+  let crit = NoInterrupts::new();
+  let mut state = WAVEFORM.borrow(&crit);
+  match *state {
+    Some((ref mut waveform, channel)) =>
+      Dac { channel: channel }.write(waveform.next_sample()),
+    None => (),
+  }
+}
+
+mod reg {
+  use volatile_cell::VolatileCell;
+  use core::ops::Drop;
+
+  ioregs!(DAC = {
+    0x00 => reg32 cr {       // control
+      0      => en1 : rw,
+      1      => boff1 : rw,
+      2      => ten1 : rw,
+      5..3   => tsel1 : rw,
+      16     => en2 : rw,
+      17     => boff2 : rw,
+      18     => ten2 : rw,
+      21..19 => tsel2 : rw,
+    },
+    0x04 => reg32 swtrigr {  // software trigger
+      0      => swtrig1 : wo,
+      1      => swtrig2 : wo,
+    },
+    0x08 => reg32 dhr12r1 {  // channel 1 12-bit right-aligned data
+      11..0  => data : rw,
+    },
+    0x14 => reg32 dhr12r2 {  // channel 2 12-bit right-aligned data
+      11..0  => data : rw,
+    },
+    0x2C => reg32 dor1 {     // channel 1 output
+      11..0  => data : ro,
+    },
+    0x30 => reg32 dor2 {     // channel 2 output
+      11..0  => data : ro,
+    },
+  });
+
+  ioregs!(BasicTimer = {
+    0x00 => reg16 cr1 {      // control 1
+      0     => counter_enable : rw,
+      7     => auto_reload_enable : rw,
+    },
+    0x04 => reg16 cr2 {      // control 2
+      6..4  => master_mode : rw,
+    },
+    0x0C => reg16 dier {     // DMA/interrupt enable
+      0     => update_interrupt : rw,
+    },
+    0x10 => reg16 sr {       // status
+      0     => update : rw,
+    },
+    0x14 => reg16 egr {      // event generation
+      0     => update : wo,
+    },
+    0x28 => reg16 psc {      // prescaler
+      15..0 => prescaler : rw,
+    },
+    0x2C => reg16 arr {      // auto-reload
+      15..0 => reload : rw,
+    },
+  });
+
+  extern {
+    #[link_name="stm32f4_iomem_DAC"] pub static DAC: DAC;
+    #[link_name="stm32f4_iomem_TIM6"] pub static TIM6: BasicTimer;
+  }
+}
@@ -0,0 +1,399 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! DMA controllers for STM32F4.
+//!
+//! DMA1 and DMA2 have eight streams each, and every stream selects one of
+//! eight request channels. A peripheral request is served by the first stream
+//! and channel the reference manual (9.3.3) lists for it, so streams are
+//! claimed by the request they are going to serve. Requests that share a
+//! stream can't be used at the same time.
+//!
+//! Streams run in direct mode, moving one byte per request. Callbacks are run
+//! from `isr_handler`, which must be called from the `DMAx_Streamy` interrupt
+//! of the stream.
+
+use core::intrinsics::abort;
+use core::ops::Drop;
+use core::option::Option::{self, Some, None};
+use core::result::Result::{self, Ok, Err};
+
+use hal::cortex_m4::irq::NoInterrupts;
+use hal::cortex_m4::nvic;
+use hal::dma;
+use hal::stm32f4::peripheral_clock::PeripheralClock;
+use util::shared::Shared;
+use util::support::get_reg_ref;
+
+#[path="../../util/wait_for.rs"]
+#[macro_use] mod wait_for;
+
+/// Maximum number of bytes in a single transfer.
+pub const MAX_TRANSFER: usize = 0xFFFF;
+
+/// Available DMA controllers.
+#[allow(missing_docs)]
+#[derive(PartialEq, Clone, Copy)]
+pub enum Controller {
+  Dma1,
+  Dma2,
+}
+
+/// Peripheral requests that can be served by DMA.
+#[allow(missing_docs)]
+#[derive(Clone, Copy)]
+pub enum Request {
+  Spi1Rx,
+  Spi1Tx,
+  Spi2Rx,
+  Spi2Tx,
+  Spi3Rx,
+  Spi3Tx,
+  Usart1Rx,
+  Usart1Tx,
+  Usart2Rx,
+  Usart2Tx,
+  Usart3Rx,
+  Usart3Tx,
+  Uart4Rx,
+  Uart4Tx,
+  Uart5Rx,
+  Uart5Tx,
+  Usart6Rx,
+  Usart6Tx,
+  I2c1Rx,
+  I2c1Tx,
+  I2c2Rx,
+  I2c2Tx,
+  Dac1,
+  Dac2,
+  Adc1,
+}
+
+impl Request {
+  /// Returns the controller, stream and channel serving this request.
+  pub fn stream(self) -> (Controller, usize, u32) {
+    use self::Request::*;
+    use self::Controller::*;
+    match self {
+      Spi1Rx   => (Dma2, 0, 3),
+      Spi1Tx   => (Dma2, 3, 3),
+      Spi2Rx   => (Dma1, 3, 0),
+      Spi2Tx   => (Dma1, 4, 0),
+      Spi3Rx   => (Dma1, 0, 0),
+      Spi3Tx   => (Dma1, 5, 0),
+      Usart1Rx => (Dma2, 2, 4),
+      Usart1Tx => (Dma2, 7, 4),
+      Usart2Rx => (Dma1, 5, 4),
+      Usart2Tx => (Dma1, 6, 4),
+      Usart3Rx => (Dma1, 1, 4),
+      Usart3Tx => (Dma1, 3, 4),
+      Uart4Rx  => (Dma1, 2, 4),
+      Uart4Tx  => (Dma1, 4, 4),
+      Uart5Rx  => (Dma1, 0, 4),
+      Uart5Tx  => (Dma1, 7, 4),
+      Usart6Rx => (Dma2, 1, 5),
+      Usart6Tx => (Dma2, 6, 5),
+      I2c1Rx   => (Dma1, 0, 1),
+      I2c1Tx   => (Dma1, 6, 1),
+      I2c2Rx   => (Dma1, 2, 7),
+      I2c2Tx   => (Dma1, 7, 7),
+      Dac1     => (Dma1, 5, 7),
+      Dac2     => (Dma1, 6, 7),
+      Adc1     => (Dma2, 0, 0),
+    }
+  }
+}
+
+/// Each controller has 8 streams.
+const STREAM_COUNT: usize = 16;
+
+struct State {
+  claimed: u16,
+  callbacks: [Option<dma::Callback>; STREAM_COUNT],
+}
+
+static STATE: Shared<State> = Shared::new(State {
+  claimed: 0,
+  callbacks: [None; STREAM_COUNT],
+});
+
+/// A claimed DMA stream, released when dropped.
+pub struct Channel {
+  id: StreamId,
+  channel: u32,
+}
+
+impl Channel {
+  /// Claims the stream serving `request`.
+  ///
+  /// Returns `None` if the stream is already in use.
+  pub fn claim(request: Request) -> Option<Channel> {
+    let (controller, number, channel) = request.stream();
+    let id = StreamId {
+      controller: controller,
+      number: number,
+    };
+
+    let crit = NoInterrupts::new();
+    let mut state = STATE.borrow(&crit);
+    if state.claimed & (1 << id.index()) != 0 {
+      return None;
+    }
+    state.claimed |= 1 << id.index();
+    state.callbacks[id.index()] = None;
+
+    match controller {
+      Controller::Dma1 => PeripheralClock::DMA1Clock,
+      Controller::Dma2 => PeripheralClock::DMA2Clock,
+    }.enable();
+
+    Some(Channel {
+      id: id,
+      channel: channel,
+    })
+  }
+
+  /// Starts a transfer of `len` bytes from memory to a peripheral register.
+  ///
+  /// If `increment` is false, the same byte at `source` is sent `len` times.
+  ///
+  /// This is unsafe as `source` must stay valid until the transfer completes.
+  pub unsafe fn memory_to_peripheral(&self, source: *const u8,
+      increment: bool, len: usize, destination: u32) {
+    self.start(destination, source as u32, increment, len, DIR_TO_PERIPHERAL);
+  }
+
+  /// Starts a transfer of `len` bytes from a peripheral register to memory.
+  ///
+  /// This is unsafe as `destination` must stay valid until the transfer
+  /// completes.
+  pub unsafe fn peripheral_to_memory(&self, source: u32,
+      destination: *mut u8, len: usize) {
+    self.start(source, destination as u32, true, len, DIR_TO_MEMORY);
+  }
+
+  fn start(&self, peripheral: u32, memory: u32, memory_increment: bool,
+      len: usize, direction: u32) {
+    if len > MAX_TRANSFER {
+      unsafe { abort() };
+    }
+    let stream = self.id.reg();
+    let interrupts = {
+      let crit = NoInterrupts::new();
+      STATE.borrow(&crit).callbacks[self.id.index()].is_some()
+    };
+
+    // The stream can only be configured once it has actually stopped.
+    stream.cr.set_enable(false);
+    wait_for!(!stream.cr.enable());
+    self.id.clear_flags();
+
+    stream.par.set_address(peripheral);
+    stream.m0ar.set_address(memory);
+    stream.ndtr.set_count(len as u32);
+    // Both sizes are left at 8 bits.
+    stream.cr.ignoring_state()
+      .set_channel(self.channel)
+      .set_direction(direction)
+      .set_memory_increment(memory_increment)
+      .set_int_transfer_complete_enable(interrupts)
+      .set_int_transfer_error_enable(interrupts)
+      .set_enable(true);
+  }
+}
+
+impl dma::Channel for Channel {
+  fn status(&self) -> Option<Result<(), dma::Error>> {
+    if self.id.flag(FLAG_TRANSFER_ERROR) {
+      Some(Err(dma::Error::Bus))
+    } else if self.id.reg().cr.enable() {
+      // The stream disables itself once the transfer is complete.
+      None
+    } else {
+      Some(Ok(()))
+    }
+  }
+
+  fn set_callback(&self, callback: Option<dma::Callback>) {
+    let crit = NoInterrupts::new();
+    STATE.borrow(&crit).callbacks[self.id.index()] = callback;
+    if callback.is_some() {
+      nvic::enable_irq(self.id.irq());
+    }
+  }
+}
+
+impl Drop for Channel {
+  fn drop(&mut self) {
+    let crit = NoInterrupts::new();
+    let mut state = STATE.borrow(&crit);
+    self.id.reg().cr.set_enable(false);
+    state.claimed &= !(1 << self.id.index());
+    state.callbacks[self.id.index()] = None;
+  }
+}
+
+#[derive(Clone, Copy)]
+struct StreamId {
+  controller: Controller,
+  number: usize,
+}
+
+impl StreamId {
+  fn index(self) -> usize {
+    match self.controller {
+      Controller::Dma1 => self.number,
+      Controller::Dma2 => self.number + 8,
+    }
+  }
+
+  fn irq(self) -> usize {
+    match (self.controller, self.number) {
+      (Controller::Dma1, 7) => 47,
+      (Controller::Dma1, n) => 11 + n,
+      (Controller::Dma2, n) if n < 5 => 56 + n,
+      (Controller::Dma2, n) => 63 + n,
+    }
+  }
+
+  /// Returns the offset of the stream flags in its ISR and IFCR registers.
+  fn flag_offset(self) -> usize {
+    [0, 6, 16, 22][self.number % 4]
+  }
+
+  fn flag(self, flag: usize) -> bool {
+    let dma = self.controller_reg();
+    let flags = if self.number < 4 {
+      dma.lisr.flags()
+    } else {
+      dma.hisr.flags()
+    };
+    flags & (1 << (self.flag_offset() + flag)) != 0
+  }
+
+  fn clear_flags(self) {
+    let dma = self.controller_reg();
+    let mask = FLAG_MASK << self.flag_offset();
+    if self.number < 4 {
+      dma.lifcr.set_clear(mask);
+    } else {
+      dma.hifcr.set_clear(mask);
+    }
+  }
+
+  fn controller_reg(self) -> &'static reg::DMA {
+    match self.controller {
+      Controller::Dma1 => &reg::DMA1,
+      Controller::Dma2 => &reg::DMA2,
+    }
+  }
+
+  fn reg(self) -> &'static reg::Stream {
+    let base = self.controller_reg() as *const reg::DMA as usize;
+    get_reg_ref((base + STREAM_OFFSET + STREAM_STRIDE * self.number)
+                as *const reg::Stream)
+  }
+}
+
+/// Services the interrupt of a DMA stream, running its callback.
+pub fn isr_handler(controller: Controller, number: usize) {
+  let id = StreamId {
+    controller: controller,
+    number: number,
+  };
+
+  let result = if id.flag(FLAG_TRANSFER_ERROR) {
+    Err(dma::Error::Bus)
+  } else if id.flag(FLAG_TRANSFER_COMPLETE) {
+    Ok(())
+  } else {
+    return;
+  };
+  id.clear_flags();
+
+  let callback = {
+    let crit = NoInterrupts::new();
+    STATE.borrow(&crit).callbacks[id.index()]
+  };
+  match callback {
+    Some(callback) => callback(result),
+    None => (),
+  }
+}
+
+const STREAM_OFFSET: usize = 0x10;
+const STREAM_STRIDE: usize = 0x18;
+
+const DIR_TO_MEMORY: u32 = 0b00;
+const DIR_TO_PERIPHERAL: u32 = 0b01;
+
+const FLAG_TRANSFER_ERROR: usize = 3;
+const FLAG_TRANSFER_COMPLETE: usize = 5;
+/// FEIF, DMEIF, TEIF, HTIF and TCIF.
+const FLAG_MASK: u32 = 0b11_1101;
+
+mod reg {
+  use volatile_cell::VolatileCell;
+  use core::ops::Drop;
+
+  ioregs!(DMA = {
+    0x00 => reg32 lisr { // interrupt status, streams 0 to 3
+      31..0 => flags : ro,
+    },
+    0x04 => reg32 hisr { // interrupt status, streams 4 to 7
+      31..0 => flags : ro,
+    },
+    0x08 => reg32 lifcr { // interrupt flag clear, streams 0 to 3
+      31..0 => clear : wo,
+    },
+    0x0C => reg32 hifcr { // interrupt flag clear, streams 4 to 7
+      31..0 => clear : wo,
+    },
+  });
+
+  ioregs!(Stream = {
+    0x00 => reg32 cr { // configuration
+      0 => enable : rw,
+      1 => int_direct_mode_error_enable : rw,
+      2 => int_transfer_error_enable : rw,
+      3 => int_half_transfer_enable : rw,
+      4 => int_transfer_complete_enable : rw,
+      5 => peripheral_flow_control : rw,
+      7..6 => direction : rw,
+      8 => circular : rw,
+      9 => peripheral_increment : rw,
+      10 => memory_increment : rw,
+      12..11 => peripheral_size : rw,
+      14..13 => memory_size : rw,
+      17..16 => priority : rw,
+      27..25 => channel : rw,
+    },
+    0x04 => reg32 ndtr { // number of data
+      15..0 => count : rw,
+    },
+    0x08 => reg32 par { // peripheral address
+      31..0 => address : rw,
+    },
+    0x0C => reg32 m0ar { // memory 0 address
+      31..0 => address : rw,
+    },
+  });
+
+  extern {
+    #[link_name="stm32f4_iomem_DMA1"] pub static DMA1: DMA;
+    #[link_name="stm32f4_iomem_DMA2"] pub static DMA2: DMA;
+  }
+}
@@ -0,0 +1,199 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Flash programming for STM32F4.
+//!
+//! Sectors are 16KiB, 64KiB or 128KiB, 2MiB parts have a second bank with the
+//! same layout. Words are programmed with a 32 bit parallelism, which needs a
+//! supply voltage of 2.7V to 3.6V. The core stalls on instruction fetches
+//! until the operation completes.
+
+use core::intrinsics::{volatile_load, volatile_store};
+use core::result::Result::{self, Ok, Err};
+
+use hal::flash::{self, Error, Sector};
+use hal::stm32f4::init::reg::FLASH;
+
+#[path="../../util/wait_for.rs"]
+#[macro_use] mod wait_for;
+
+/// Start of flash.
+const FLASH_BASE: u32 = 0x0800_0000;
+
+/// Flash size register, in KiB.
+const FLASH_SIZE_KB: *const u16 = 0x1FFF_7A22 as *const u16;
+
+/// Sizes of the sectors of a bank, in KiB.
+const BANK_SECTORS: [u32; 12] = [16, 16, 16, 16, 64, 128, 128, 128, 128, 128,
+                                 128, 128];
+
+/// Size of a bank.
+const BANK_SIZE: u32 = 1024 * 1024;
+
+/// Sector numbers of the second bank start at 16.
+const SECOND_BANK_SNB: u32 = 16;
+
+/// Key sequence unlocking the control register.
+const KEY1: u32 = 0x4567_0123;
+const KEY2: u32 = 0xCDEF_89AB;
+
+// Control register bits.
+const CR_PG: u32 = 1 << 0;
+const CR_SER: u32 = 1 << 1;
+const CR_SNB_SHIFT: u32 = 3;
+const CR_PSIZE_X16: u32 = 0b01 << 8;
+const CR_PSIZE_X32: u32 = 0b10 << 8;
+const CR_STRT: u32 = 1 << 16;
+const CR_LOCK: u32 = 1 << 31;
+
+// Access control register bits.
+const ACR_DCEN: u32 = 1 << 10;
+const ACR_DCRST: u32 = 1 << 12;
+
+// Status register bits.
+const SR_EOP: u32 = 1 << 0;
+const SR_OPERR: u32 = 1 << 1;
+const SR_WRPERR: u32 = 1 << 4;
+const SR_PGAERR: u32 = 1 << 5;
+const SR_PGPERR: u32 = 1 << 6;
+const SR_PGSERR: u32 = 1 << 7;
+const SR_BSY: u32 = 1 << 16;
+const SR_ERRORS: u32 = SR_OPERR | SR_WRPERR | SR_PGAERR | SR_PGPERR |
+    SR_PGSERR;
+
+/// Structure describing the flash.
+#[derive(Clone, Copy)]
+pub struct Flash {
+  size: u32,
+}
+
+impl Flash {
+  /// Returns the flash, sized from the flash size register.
+  pub fn new() -> Flash {
+    Flash {
+      size: unsafe { volatile_load(FLASH_SIZE_KB) } as u32 * 1024,
+    }
+  }
+
+  fn check_range(&self, addr: u32) -> Result<(), Error> {
+    if addr < FLASH_BASE || addr - FLASH_BASE >= self.size {
+      Err(Error::OutOfRange)
+    } else {
+      Ok(())
+    }
+  }
+
+  /// Programs `value` at `addr` with the `psize` parallelism.
+  fn program_unit<T>(&self, addr: u32, value: T, psize: u32)
+      -> Result<(), Error> {
+    try!(self.check_range(addr));
+    unlock();
+    FLASH.set_CR(psize | CR_PG);
+    unsafe { volatile_store(addr as *mut T, value) };
+    let result = finish();
+    FLASH.set_CR(CR_LOCK);
+    flush_data_cache();
+    result
+  }
+}
+
+impl flash::Flash for Flash {
+  fn sector_count(&self) -> usize {
+    let mut count = 0;
+    let mut end = 0;
+    while count < 2 * BANK_SECTORS.len() && end < self.size {
+      end += BANK_SECTORS[count % BANK_SECTORS.len()] * 1024;
+      count += 1;
+    }
+    count
+  }
+
+  fn sector(&self, index: usize) -> Sector {
+    let bank = (index / BANK_SECTORS.len()) as u32;
+    let in_bank = index % BANK_SECTORS.len();
+    let mut start = FLASH_BASE + bank * BANK_SIZE;
+    for i in 0..in_bank {
+      start += BANK_SECTORS[i] * 1024;
+    }
+    Sector {
+      start: start,
+      size: BANK_SECTORS[in_bank] * 1024,
+    }
+  }
+
+  fn erase(&self, index: usize) -> Result<(), Error> {
+    if index >= flash::Flash::sector_count(self) {
+      return Err(Error::OutOfRange);
+    }
+    let bank = (index / BANK_SECTORS.len()) as u32;
+    let snb = bank * SECOND_BANK_SNB + (index % BANK_SECTORS.len()) as u32;
+    unlock();
+    FLASH.set_CR(CR_PSIZE_X32 | (snb << CR_SNB_SHIFT) | CR_SER);
+    FLASH.set_CR(CR_PSIZE_X32 | (snb << CR_SNB_SHIFT) | CR_SER | CR_STRT);
+    let result = finish();
+    FLASH.set_CR(CR_LOCK);
+    flush_data_cache();
+    result
+  }
+
+  fn program_word(&self, addr: u32, value: u32) -> Result<(), Error> {
+    if addr & 3 != 0 {
+      return Err(Error::Unaligned);
+    }
+    self.program_unit(addr, value, CR_PSIZE_X32)
+  }
+
+  fn program_half_word(&self, addr: u32, value: u16) -> Result<(), Error> {
+    if addr & 1 != 0 {
+      return Err(Error::Unaligned);
+    }
+    self.program_unit(addr, value, CR_PSIZE_X16)
+  }
+}
+
+fn unlock() {
+  if FLASH.CR() & CR_LOCK != 0 {
+    FLASH.set_KEYR(KEY1);
+    FLASH.set_KEYR(KEY2);
+  }
+}
+
+/// Resets the data cache, which can hold lines read before the flash changed.
+///
+/// The cache can only be reset while it's disabled.
+fn flush_data_cache() {
+  let acr = FLASH.ACR();
+  if acr & ACR_DCEN == 0 {
+    return;
+  }
+  FLASH.set_ACR(acr & !ACR_DCEN);
+  FLASH.set_ACR((acr & !ACR_DCEN) | ACR_DCRST);
+  FLASH.set_ACR(acr & !ACR_DCEN);
+  FLASH.set_ACR(acr);
+}
+
+/// Waits for the running operation and clears its status flags.
+fn finish() -> Result<(), Error> {
+  wait_for!(FLASH.SR() & SR_BSY == 0);
+  let status = FLASH.SR();
+  FLASH.set_SR(SR_EOP | SR_ERRORS);
+  if status & SR_WRPERR != 0 {
+    Err(Error::WriteProtected)
+  } else if status & SR_ERRORS != 0 {
+    Err(Error::Failed)
+  } else {
+    Ok(())
+  }
+}
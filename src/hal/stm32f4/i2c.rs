@@ -0,0 +1,58 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Inter-Integrated Circuit master for STM32F4.
+//!
+//! The driver is shared with the other STM32 families, see
+//! `hal::stm32_common::i2c`.
+
+use hal::stm32f4::init;
+
+pub use hal::stm32_common::i2c::I2c;
+
+/// Available I2C peripherals.
+#[allow(missing_docs)]
+#[repr(u8)]
+#[derive(Clone, Copy)]
+pub enum Peripheral {
+  I2c1,
+  I2c2,
+}
+
+impl I2c {
+  /// Create a new I2C master running at `frequency` Hz.
+  ///
+  /// Frequencies up to 100kHz use standard mode, up to 400kHz fast mode.
+  pub fn new(peripheral: Peripheral, frequency: u32) -> I2c {
+    use hal::stm32f4::peripheral_clock::PeripheralClock::*;
+
+    let (reg, clock) = match peripheral {
+      Peripheral::I2c1 => (&reg::I2C1, I2C1Clock),
+      Peripheral::I2c2 => (&reg::I2C2, I2C2Clock),
+    };
+
+    clock.enable();
+    I2c::with_peripheral(reg, frequency, init::apb_low_clock())
+  }
+}
+
+mod reg {
+  use hal::stm32_common::i2c::reg::I2C;
+
+  extern {
+    #[link_name="stm32f4_iomem_I2C1"] pub static I2C1: I2C;
+    #[link_name="stm32f4_iomem_I2C2"] pub static I2C2: I2C;
+  }
+}
@@ -101,6 +101,14 @@ pub fn apb_low_clock() -> u32 {
   unsafe { APBLowClock }
 }
 
+/// Returns the clock frequency of the timers on the low speed APB.
+///
+/// Timers run at twice the APB frequency when it's divided.
+pub fn apb_low_timer_clock() -> u32 {
+  let apb = apb_low_clock();
+  if apb == system_clock() { apb } else { apb * 2 }
+}
+
 impl SysConf {
   /// Performs the MCU initialization.
   pub fn setup(&self) {
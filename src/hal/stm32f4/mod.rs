@@ -15,7 +15,18 @@
 
 //! HAL for STM32F4.
 
+pub mod adc;
+pub mod capture;
+pub mod dac;
+pub mod dma;
+pub mod exti;
+pub mod flash;
+pub mod i2c;
 pub mod init;
 pub mod peripheral_clock;
 pub mod pin;
+pub mod power;
+pub mod pwm;
+pub mod rtc;
 pub mod timer;
+pub mod watchdog;
@@ -17,10 +17,18 @@
 //!
 //! Some pins that could be configured here may be missing from actual MCU
 //! depending on the package.
+//!
+//! Edge interrupts are served by the EXTI lines, see `stm32f4::exti`.
 
 use hal::pin::{Gpio, GpioDirection, GpioLevel};
+use hal::pin::{GpioEdge, GpioHandler, GpioInterrupt};
+use hal::pin::{ConfigError, Electrical, Pull, OutputType};
+use hal::pin::{DriveStrength, SlewRate};
+use super::exti;
 use super::peripheral_clock;
 use core::intrinsics::abort;
+use core::option::Option;
+use core::result::Result::{self, Ok, Err};
 
 use self::Port::*;
 
@@ -165,6 +173,63 @@ impl Gpio for Pin {
   }
 }
 
+impl GpioInterrupt for Pin {
+  fn set_handler(&self, handler: Option<GpioHandler>) {
+    exti::set_handler(self.pin as usize, handler);
+  }
+
+  fn enable_interrupt(&self, edge: GpioEdge) {
+    exti::enable(self.pin as usize, self.port as u8, edge);
+  }
+
+  fn disable_interrupt(&self) {
+    exti::disable(self.pin as usize);
+  }
+
+  fn clear_interrupt(&self) {
+    exti::clear(self.pin as usize);
+  }
+}
+
+/// The F4 GPIO has no drive strength setting, the output speed is used as
+/// slew rate.
+impl Electrical for Pin {
+  fn set_pull(&self, pull: Pull) -> Result<(), ConfigError> {
+    use self::reg::GPIO_pupdr_pupd as RegPull;
+    self.get_reg().pupdr.set_pupd(self.pin as usize, match pull {
+      Pull::Floating => RegPull::None,
+      Pull::Up       => RegPull::PullUp,
+      Pull::Down     => RegPull::PullDown,
+    });
+    Ok(())
+  }
+
+  fn set_output_type(&self, output_type: OutputType)
+      -> Result<(), ConfigError> {
+    use self::reg::GPIO_otyper_ot as RegType;
+    self.get_reg().otyper.set_ot(self.pin as usize, match output_type {
+      OutputType::PushPull  => RegType::PushPull,
+      OutputType::OpenDrain => RegType::OpenDrain,
+    });
+    Ok(())
+  }
+
+  fn set_drive_strength(&self, _strength: DriveStrength)
+      -> Result<(), ConfigError> {
+    Err(ConfigError::UnsupportedDriveStrength)
+  }
+
+  /// Slow is 2 MHz and fast is 100 MHz.
+  fn set_slew_rate(&self, rate: SlewRate) -> Result<(), ConfigError> {
+    use self::reg::GPIO_ospeedr_ospeed as RegSpeed;
+    self.get_reg().ospeedr.set_ospeed(self.pin as usize, match rate {
+      SlewRate::Slow => RegSpeed::Low,
+      SlewRate::Fast => RegSpeed::High,
+    });
+    Ok(())
+  }
+}
+
 #[allow(dead_code)]
 mod reg {
   use core::ops::Drop;
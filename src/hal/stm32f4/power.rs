@@ -0,0 +1,77 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Power modes for STM32F4.
+//!
+//! `DeepSleep` is the Stop mode, with the regulator in low power mode. Any
+//! EXTI line, including the RTC alarm on line 17, wakes the core up, which
+//! then runs from the HSI: `SysConf::setup` must be called again to restore
+//! the clocks. `Standby` is left through a reset, triggered by NRST, the IWDG,
+//! an RTC alarm or a rising edge on the WKUP pin if enabled.
+
+use hal::cortex_m4::scb;
+use hal::power::Mode;
+use hal::stm32f4::init::reg::PWR;
+use hal::stm32f4::peripheral_clock::PeripheralClock;
+use util::support::wfi;
+
+const CR_LPDS: u32 = 1 << 0;
+const CR_PDDS: u32 = 1 << 1;
+const CR_CWUF: u32 = 1 << 2;
+const CR_CSBF: u32 = 1 << 3;
+
+const CSR_SBF: u32 = 1 << 1;
+const CSR_EWUP: u32 = 1 << 8;
+
+/// Enters `mode` until a wake-up event.
+pub fn enter(mode: Mode) {
+  PeripheralClock::PWRClock.enable();
+  let low_power_regulator = PWR.CR() & CR_LPDS;
+  match mode {
+    Mode::Sleep => scb::set_sleepdeep(false),
+    Mode::DeepSleep => {
+      PWR.set_CR(PWR.CR() & !CR_PDDS | CR_LPDS);
+      scb::set_sleepdeep(true);
+    },
+    Mode::Standby => {
+      PWR.set_CR(PWR.CR() | CR_PDDS | CR_CWUF);
+      scb::set_sleepdeep(true);
+    },
+  }
+  wfi();
+  scb::set_sleepdeep(false);
+  PWR.set_CR(PWR.CR() & !CR_LPDS | low_power_regulator);
+}
+
+/// Sets whether a rising edge on the WKUP pin (PA0) wakes the MCU up from
+/// `Standby`.
+pub fn set_wakeup_pin(enable: bool) {
+  PeripheralClock::PWRClock.enable();
+  let csr = PWR.CSR() & !CSR_EWUP;
+  PWR.set_CSR(if enable { csr | CSR_EWUP } else { csr });
+}
+
+/// Returns true if the MCU was reset by waking up from `Standby`.
+///
+/// The flag is kept until the next `Standby`, or until it's cleared by
+/// `clear_standby_flag`.
+pub fn woke_from_standby() -> bool {
+  PWR.CSR() & CSR_SBF != 0
+}
+
+/// Clears the flag returned by `woke_from_standby`.
+pub fn clear_standby_flag() {
+  PWR.set_CR(PWR.CR() | CR_CSBF);
+}
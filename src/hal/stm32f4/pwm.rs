@@ -0,0 +1,65 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! PWM outputs on the STM32F4 general purpose timers.
+//!
+//! The timer logic is shared with the other STM32 families, see
+//! `hal::stm32_common::pwm`. TIM3 and TIM4 are 16-bit wide, so their periods
+//! are limited to 65536us.
+//!
+//! Output pins must be configured in their alternate function mode.
+
+use hal::stm32f4::init;
+use hal::stm32f4::peripheral_clock::PeripheralClock;
+
+pub use hal::stm32_common::pwm::{PWM, Channel};
+
+/// Timers that can drive PWM outputs.
+#[allow(missing_docs)]
+#[derive(Clone, Copy)]
+pub enum TimerPeripheral {
+  Timer2,
+  Timer3,
+  Timer4,
+  Timer5,
+}
+
+impl PWM {
+  /// Creates a PWM output on `channel` of `timer`, initially low.
+  pub fn new(timer: TimerPeripheral, channel: Channel, period_us: u32)
+      -> PWM {
+    use self::TimerPeripheral::*;
+    let (reg, clock) = match timer {
+      Timer2 => (&reg::TIM2, PeripheralClock::TIM2Clock),
+      Timer3 => (&reg::TIM3, PeripheralClock::TIM3Clock),
+      Timer4 => (&reg::TIM4, PeripheralClock::TIM4Clock),
+      Timer5 => (&reg::TIM5, PeripheralClock::TIM5Clock),
+    };
+    clock.enable();
+
+    PWM::with_timer(reg, channel, period_us, init::apb_low_timer_clock())
+  }
+}
+
+mod reg {
+  use hal::stm32_common::pwm::reg::TIM;
+
+  extern {
+    #[link_name="stm32f4_iomem_TIM2"] pub static TIM2: TIM;
+    #[link_name="stm32f4_iomem_TIM3"] pub static TIM3: TIM;
+    #[link_name="stm32f4_iomem_TIM4"] pub static TIM4: TIM;
+    #[link_name="stm32f4_iomem_TIM5"] pub static TIM5: TIM;
+  }
+}
@@ -0,0 +1,292 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Real-time clock for STM32F4.
+//!
+//! The RTC keeps the calendar in BCD registers in the backup domain, clocked
+//! from the 32.768kHz LSE oscillator. Only two year digits are stored, so
+//! years must be between 2000 and 2099.
+//!
+//! Alarms use alarm A, which compares the day of the month and the time, so an
+//! alarm set more than a month ahead triggers early. The alarm is routed
+//! through EXTI line 17, and `isr_handler` must be called from the RTC alarm
+//! interrupt.
+
+use core::intrinsics::abort;
+use core::option::Option::{self, Some, None};
+
+use hal::cortex_m4::irq::NoInterrupts;
+use hal::cortex_m4::nvic;
+use hal::rtc::{self, AlarmHandler, DateTime};
+use hal::stm32f4::init::reg::{RCC, PWR};
+use hal::stm32f4::peripheral_clock::PeripheralClock;
+use util::shared::Shared;
+
+#[path="../../util/wait_for.rs"]
+#[macro_use] mod wait_for;
+
+/// RTC alarm interrupt number.
+const IRQ: usize = 41;
+
+/// EXTI line of the RTC alarm.
+const EXTI_LINE: u32 = 1 << 17;
+
+const PWR_CR_DBP: u32 = 1 << 8;
+
+const BDCR_LSEON: u32 = 1 << 0;
+const BDCR_LSERDY: u32 = 1 << 1;
+const BDCR_RTCSEL_MASK: u32 = 0b11 << 8;
+const BDCR_RTCSEL_LSE: u32 = 0b01 << 8;
+const BDCR_RTCEN: u32 = 1 << 15;
+
+/// Prescalers giving 1Hz ticks from the LSE.
+const PREDIV_A: u32 = 127;
+const PREDIV_S: u32 = 255;
+
+static HANDLER: Shared<Option<AlarmHandler>> = Shared::new(None);
+
+fn to_bcd(value: u8) -> u32 {
+  ((value / 10) << 4 | value % 10) as u32
+}
+
+fn from_bcd(bcd: u32) -> u8 {
+  ((bcd >> 4) * 10 + (bcd & 0xF)) as u8
+}
+
+/// Aborts unless `time` is valid and within the two digit years.
+fn check_time(time: &DateTime) {
+  if !time.is_valid() || time.year < 2000 || time.year > 2099 {
+    unsafe { abort() };
+  }
+}
+
+/// Structure describing the RTC.
+#[derive(Clone, Copy)]
+pub struct Rtc {
+  reg: &'static reg::RTC,
+}
+
+impl Rtc {
+  /// Enables access to the backup domain and starts the RTC on the LSE if
+  /// it's stopped.
+  ///
+  /// The time is kept if the clock was already running.
+  pub fn new() -> Rtc {
+    PeripheralClock::PWRClock.enable();
+    PWR.set_CR(PWR.CR() | PWR_CR_DBP);
+
+    let rtc = Rtc { reg: &reg::RTC };
+    if RCC.BDCR() & BDCR_RTCEN == 0 {
+      RCC.set_BDCR(RCC.BDCR() | BDCR_LSEON);
+      wait_for!(RCC.BDCR() & BDCR_LSERDY != 0);
+      RCC.set_BDCR(RCC.BDCR() & !BDCR_RTCSEL_MASK | BDCR_RTCSEL_LSE |
+                   BDCR_RTCEN);
+
+      rtc.configure(|| {
+        // The synchronous prescaler must be written first.
+        rtc.reg.prer.set_prediv_s(PREDIV_S);
+        rtc.reg.prer.set_prediv_a(PREDIV_A);
+        rtc.reg.cr.set_fmt(false);
+      });
+    }
+    rtc
+  }
+
+  /// Runs `f` with the write protection removed.
+  fn unlocked<F: Fn()>(&self, f: F) {
+    self.reg.wpr.ignoring_state().set_key(0xCA);
+    self.reg.wpr.ignoring_state().set_key(0x53);
+    f();
+    self.reg.wpr.ignoring_state().set_key(0xFF);
+  }
+
+  /// Runs `f` in initialisation mode, where the calendar and prescalers can be
+  /// written.
+  fn configure<F: Fn()>(&self, f: F) {
+    self.unlocked(|| {
+      self.reg.isr.set_init(true);
+      wait_for!(self.reg.isr.initf());
+      f();
+      self.reg.isr.set_init(false);
+      // The shadow registers are resynchronised with the new calendar.
+      self.reg.isr.set_rsf(false);
+    });
+  }
+}
+
+impl rtc::Rtc for Rtc {
+  fn set_time(&self, time: &DateTime) {
+    check_time(time);
+    // The RTC numbers days from Monday (1) to Sunday (7).
+    let weekday = match time.weekday() {
+      0 => 7,
+      d => d,
+    };
+    self.configure(|| {
+      self.reg.tr.ignoring_state()
+        .set_hours(to_bcd(time.hour))
+        .set_minutes(to_bcd(time.minute))
+        .set_seconds(to_bcd(time.second));
+      self.reg.dr.ignoring_state()
+        .set_year(to_bcd((time.year % 100) as u8))
+        .set_weekday(weekday as u32)
+        .set_month(to_bcd(time.month))
+        .set_day(to_bcd(time.day));
+    });
+  }
+
+  fn get_time(&self) -> DateTime {
+    wait_for!(self.reg.isr.rsf());
+    // Reading the time locks the date shadow register until it's read.
+    let tr = self.reg.tr.get();
+    let dr = self.reg.dr.get();
+
+    DateTime {
+      year: 2000 + from_bcd(dr.year()) as u16,
+      month: from_bcd(dr.month()),
+      day: from_bcd(dr.day()),
+      hour: from_bcd(tr.hours()),
+      minute: from_bcd(tr.minutes()),
+      second: from_bcd(tr.seconds()),
+    }
+  }
+
+  fn set_alarm(&self, time: &DateTime, handler: AlarmHandler) {
+    check_time(time);
+    {
+      let crit = NoInterrupts::new();
+      *HANDLER.borrow(&crit) = Some(handler);
+    }
+
+    self.unlocked(|| {
+      self.reg.cr.set_alrae(false).set_alraie(false);
+      wait_for!(self.reg.isr.alrawf());
+      self.reg.alrmar.ignoring_state()
+        .set_day(to_bcd(time.day))
+        .set_hours(to_bcd(time.hour))
+        .set_minutes(to_bcd(time.minute))
+        .set_seconds(to_bcd(time.second));
+      self.reg.isr.set_alraf(false);
+      self.reg.cr.set_alrae(true).set_alraie(true);
+    });
+
+    let exti = &reg::EXTI;
+    exti.rtsr.set_lines(exti.rtsr.lines() | EXTI_LINE);
+    exti.pr.ignoring_state().set_lines(EXTI_LINE);
+    exti.imr.set_lines(exti.imr.lines() | EXTI_LINE);
+    nvic::enable_irq(IRQ);
+  }
+
+  fn cancel_alarm(&self) {
+    self.unlocked(|| {
+      self.reg.cr.set_alrae(false).set_alraie(false);
+    });
+    let crit = NoInterrupts::new();
+    *HANDLER.borrow(&crit) = None;
+  }
+}
+
+/// Services the RTC alarm interrupt, calling the alarm handler once.
+pub fn isr_handler() {
+  let rtc = Rtc { reg: &reg::RTC };
+  reg::EXTI.pr.ignoring_state().set_lines(EXTI_LINE);
+  if !rtc.reg.isr.alraf() {
+    return;
+  }
+  rtc.reg.isr.set_alraf(false);
+  rtc.unlocked(|| {
+    rtc.reg.cr.set_alrae(false).set_alraie(false);
+  });
+
+  let handler = {
+    let crit = NoInterrupts::new();
+    let mut handler = HANDLER.borrow(&crit);
+    let current = *handler;
+    *handler = None;
+    current
+  };
+  match handler {
+    Some(handler) => handler(),
+    None => (),
+  }
+}
+
+mod reg {
+  use volatile_cell::VolatileCell;
+  use core::ops::Drop;
+
+  ioregs!(RTC = {
+    0x00 => reg32 tr {       // time, BCD
+      6..0   => seconds : rw,
+      14..8  => minutes : rw,
+      21..16 => hours : rw,
+      22     => pm : rw,
+    },
+    0x04 => reg32 dr {       // date, BCD
+      5..0   => day : rw,
+      12..8  => month : rw,
+      15..13 => weekday : rw,
+      23..16 => year : rw,
+    },
+    0x08 => reg32 cr {       // control
+      6      => fmt : rw,
+      8      => alrae : rw,
+      12     => alraie : rw,
+    },
+    0x0C => reg32 isr {      // initialisation and status
+      0      => alrawf : ro,
+      5      => rsf : rw,
+      6      => initf : ro,
+      7      => init : rw,
+      8      => alraf : rw,
+    },
+    0x10 => reg32 prer {     // prescalers
+      14..0  => prediv_s : rw,
+      22..16 => prediv_a : rw,
+    },
+    0x1C => reg32 alrmar {   // alarm A, BCD
+      6..0   => seconds : rw,
+      7      => mask_seconds : rw,
+      14..8  => minutes : rw,
+      15     => mask_minutes : rw,
+      21..16 => hours : rw,
+      22     => pm : rw,
+      23     => mask_hours : rw,
+      29..24 => day : rw,
+      30     => weekday_select : rw,
+      31     => mask_day : rw,
+    },
+    0x24 => reg32 wpr {      // write protection
+      7..0   => key : wo,
+    },
+  });
+
+  ioregs!(EXTI = {
+    0x00 => reg32 imr {      // interrupt mask
+      22..0 => lines : rw,
+    },
+    0x08 => reg32 rtsr {     // rising trigger selection
+      22..0 => lines : rw,
+    },
+    0x14 => reg32 pr {       // pending, cleared by writing 1
+      22..0 => lines : rw,
+    },
+  });
+
+  extern {
+    #[link_name="stm32f4_iomem_RTC"] pub static RTC: RTC;
+    #[link_name="stm32f4_iomem_EXTI"] pub static EXTI: EXTI;
+  }
+}
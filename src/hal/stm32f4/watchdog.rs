@@ -0,0 +1,114 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Independent and window watchdogs for STM32F4.
+//!
+//! The independent watchdog (IWDG) runs from the ~32kHz LSI oscillator, whose
+//! frequency varies from 17kHz to 47kHz, so timeouts are approximate. The
+//! window watchdog (WWDG) runs from PCLK1 and is limited to short timeouts,
+//! 49ms at a 42MHz PCLK1. Neither can be stopped until the next reset.
+//!
+//! The watchdog logic is shared with the other STM32 families, see
+//! `hal::stm32_common::watchdog`.
+
+use hal::stm32_common::watchdog as common;
+use hal::stm32f4::init;
+use hal::stm32f4::peripheral_clock::PeripheralClock;
+use hal::watchdog;
+
+/// Nominal LSI frequency, in ticks per millisecond.
+const LSI_TICKS_PER_MS: u32 = 32;
+
+const CSR_RMVF: u32 = 1 << 24;
+const CSR_IWDGRSTF: u32 = 1 << 29;
+const CSR_WWDGRSTF: u32 = 1 << 30;
+
+/// Structure describing the independent watchdog.
+#[derive(Clone, Copy)]
+pub struct IndependentWatchdog {
+  reg: &'static common::reg::IWDG,
+}
+
+impl IndependentWatchdog {
+  /// Returns the independent watchdog, which is left as is.
+  pub fn new() -> IndependentWatchdog {
+    IndependentWatchdog { reg: &reg::IWDG }
+  }
+}
+
+impl watchdog::Watchdog for IndependentWatchdog {
+  fn start(&self, timeout_ms: u32) {
+    common::start_independent(self.reg, LSI_TICKS_PER_MS, timeout_ms);
+  }
+
+  fn feed(&self) {
+    common::feed_independent(self.reg);
+  }
+
+  fn caused_reset(&self) -> bool {
+    init::reg::RCC.CSR() & CSR_IWDGRSTF != 0
+  }
+
+  fn clear_reset_flags(&self) {
+    init::reg::RCC.set_CSR(init::reg::RCC.CSR() | CSR_RMVF);
+  }
+}
+
+/// Structure describing the window watchdog.
+///
+/// The window is left open, so the watchdog can be fed at any time.
+#[derive(Clone, Copy)]
+pub struct WindowWatchdog {
+  reg: &'static common::reg::WWDG,
+  pclk1: u32,
+}
+
+impl WindowWatchdog {
+  /// Returns the window watchdog, which is left as is.
+  pub fn new() -> WindowWatchdog {
+    WindowWatchdog {
+      reg: &reg::WWDG,
+      pclk1: init::apb_low_clock(),
+    }
+  }
+}
+
+impl watchdog::Watchdog for WindowWatchdog {
+  fn start(&self, timeout_ms: u32) {
+    PeripheralClock::WWDGClock.enable();
+    common::start_window(self.reg, self.pclk1, timeout_ms);
+  }
+
+  fn feed(&self) {
+    common::feed_window(self.reg);
+  }
+
+  fn caused_reset(&self) -> bool {
+    init::reg::RCC.CSR() & CSR_WWDGRSTF != 0
+  }
+
+  fn clear_reset_flags(&self) {
+    init::reg::RCC.set_CSR(init::reg::RCC.CSR() | CSR_RMVF);
+  }
+}
+
+mod reg {
+  use hal::stm32_common::watchdog::reg::{IWDG, WWDG};
+
+  extern {
+    #[link_name="stm32f4_iomem_IWDG"] pub static IWDG: IWDG;
+    #[link_name="stm32f4_iomem_WWDG"] pub static WWDG: WWDG;
+  }
+}
@@ -18,4 +18,5 @@
 pub mod init;
 pub mod peripheral_clock;
 pub mod pin;
+pub mod pwm;
 pub mod timer;
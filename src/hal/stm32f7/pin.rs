@@ -20,8 +20,11 @@
 //! depending on the package.
 
 use hal::pin::{Gpio, GpioDirection, GpioLevel};
+use hal::pin::{ConfigError, Electrical, Pull, OutputType};
+use hal::pin::{DriveStrength, SlewRate};
 use super::peripheral_clock;
 use core::intrinsics::abort;
+use core::result::Result::{self, Ok, Err};
 
 use self::Port::*;
 
@@ -172,6 +175,45 @@ impl Gpio for Pin {
   }
 }
 
+/// The F7 GPIO has no drive strength setting, the output speed is used as
+/// slew rate.
+impl Electrical for Pin {
+  fn set_pull(&self, pull: Pull) -> Result<(), ConfigError> {
+    use self::reg::GPIO_pupdr_pupd as RegPull;
+    self.get_reg().pupdr.set_pupd(self.pin as usize, match pull {
+      Pull::Floating => RegPull::None,
+      Pull::Up       => RegPull::PullUp,
+      Pull::Down     => RegPull::PullDown,
+    });
+    Ok(())
+  }
+
+  fn set_output_type(&self, output_type: OutputType)
+      -> Result<(), ConfigError> {
+    use self::reg::GPIO_otyper_ot as RegType;
+    self.get_reg().otyper.set_ot(self.pin as usize, match output_type {
+      OutputType::PushPull  => RegType::PushPull,
+      OutputType::OpenDrain => RegType::OpenDrain,
+    });
+    Ok(())
+  }
+
+  fn set_drive_strength(&self, _strength: DriveStrength)
+      -> Result<(), ConfigError> {
+    Err(ConfigError::UnsupportedDriveStrength)
+  }
+
+  /// Slow selects the lowest output speed and fast the highest.
+  fn set_slew_rate(&self, rate: SlewRate) -> Result<(), ConfigError> {
+    use self::reg::GPIO_ospeedr_ospeed as RegSpeed;
+    self.get_reg().ospeedr.set_ospeed(self.pin as usize, match rate {
+      SlewRate::Slow => RegSpeed::Low,
+      SlewRate::Fast => RegSpeed::High,
+    });
+    Ok(())
+  }
+}
+
 #[allow(dead_code)]
 mod reg {
   use core::ops::Drop;
@@ -0,0 +1,238 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Analog to digital converter for STM32L1.
+//!
+//! The ADC is always clocked from HSI, which `Adc::new` turns on if the system
+//! runs from another clock.
+//!
+//! Channels 16 and 17 are the temperature sensor and the internal reference,
+//! the others are ADC input pins.
+
+use core::option::Option::{Some, None};
+use core::result::Result::{self, Ok, Err};
+
+use hal::adc::{self, Error, Resolution};
+use hal::stm32l1::init;
+use hal::stm32l1::peripheral_clock as clock;
+
+#[path="../../util/wait_for.rs"]
+#[macro_use] mod wait_for;
+
+/// Regular conversion triggers, on the rising edge of the event.
+#[allow(missing_docs)]
+#[derive(Clone, Copy)]
+pub enum Trigger {
+  Tim9Cc2  = 0,
+  Tim9Trgo = 1,
+  Tim2Cc3  = 2,
+  Tim2Cc2  = 3,
+  Tim3Trgo = 4,
+  Tim4Cc4  = 5,
+  Tim2Trgo = 6,
+  Tim3Cc1  = 7,
+  Tim3Cc3  = 8,
+  Tim4Trgo = 9,
+  Tim6Trgo = 10,
+}
+
+/// Highest channel number.
+const MAX_CHANNEL: u8 = 29;
+
+/// Available sample times, in ADC cycles.
+const SAMPLE_TIMES: [u32; 8] = [4, 9, 16, 24, 48, 96, 192, 384];
+
+/// The ADC peripheral.
+#[derive(Clone, Copy)]
+pub struct Adc {
+  reg: &'static reg::ADC,
+}
+
+impl Adc {
+  /// Powers up the ADC, with a 12-bit resolution.
+  pub fn new() -> Adc {
+    let rcc = &init::reg::RCC;
+    rcc.cr.set_hsi_on(true);
+    wait_for!(rcc.cr.hsi_ready());
+    clock::Apb2(clock::BusApb2::Adc1).enable();
+
+    reg::ADC_COMMON.ccr
+      .set_prescaler(0b00)
+      .set_temp_vref(true);
+
+    let reg = &reg::ADC1;
+    reg.cr1.set_resolution(0b00);
+    reg.cr2.set_power_on(true);
+    wait_for!(reg.sr.ready());
+    Adc { reg: reg }
+  }
+
+  /// Starts a conversion of `channel` on each `trigger` event.
+  ///
+  /// Results are read with `latest`, until `stop` is called.
+  pub fn start_triggered(&self, channel: u8, trigger: Trigger)
+      -> Result<(), Error> {
+    try!(self.select(channel));
+    self.reg.cr2
+      .set_continuous(false)
+      .set_ext_select(trigger as u32)
+      .set_ext_enable(EXT_RISING);
+    Ok(())
+  }
+
+  fn select(&self, channel: u8) -> Result<(), Error> {
+    if channel > MAX_CHANNEL {
+      return Err(Error::InvalidChannel);
+    }
+    self.reg.sqr1.set_length(0);
+    self.reg.sqr5.set_sq(0, channel as u32);
+    Ok(())
+  }
+
+  fn start(&self) {
+    self.reg.sr.set_eoc(false).set_overrun(false);
+    self.reg.cr2.set_sw_start(true);
+  }
+}
+
+impl adc::Adc for Adc {
+  fn set_resolution(&self, resolution: Resolution) -> Result<(), Error> {
+    let res = match resolution {
+      Resolution::Bits12 => 0b00,
+      Resolution::Bits10 => 0b01,
+      Resolution::Bits8  => 0b10,
+      Resolution::Bits6  => 0b11,
+      _                  => return Err(Error::UnsupportedResolution),
+    };
+    self.reg.cr1.set_resolution(res);
+    Ok(())
+  }
+
+  fn resolution(&self) -> Resolution {
+    match self.reg.cr1.resolution() {
+      0b00 => Resolution::Bits12,
+      0b01 => Resolution::Bits10,
+      0b10 => Resolution::Bits8,
+      _    => Resolution::Bits6,
+    }
+  }
+
+  fn set_sample_time(&self, cycles: u32) -> Result<(), Error> {
+    let smp = match SAMPLE_TIMES.iter().position(|&t| t >= cycles) {
+      Some(smp) => smp as u32,
+      None      => return Err(Error::UnsupportedSampleTime),
+    };
+    for channel in 0..10 {
+      self.reg.smpr3.set_smp(channel, smp);
+      self.reg.smpr2.set_smp(channel, smp);
+      self.reg.smpr1.set_smp(channel, smp);
+    }
+    Ok(())
+  }
+
+  fn read(&self, channel: u8) -> Result<u16, Error> {
+    try!(self.select(channel));
+    self.reg.cr2
+      .set_continuous(false)
+      .set_ext_enable(EXT_DISABLED);
+    self.start();
+    wait_for!(self.reg.sr.eoc());
+    Ok(self.reg.dr.data() as u16)
+  }
+
+  fn start_continuous(&self, channel: u8) -> Result<(), Error> {
+    try!(self.select(channel));
+    self.reg.cr2
+      .set_continuous(true)
+      .set_ext_enable(EXT_DISABLED);
+    self.start();
+    Ok(())
+  }
+
+  fn stop(&self) {
+    self.reg.cr2
+      .set_continuous(false)
+      .set_ext_enable(EXT_DISABLED);
+  }
+
+  fn latest(&self) -> u16 {
+    self.reg.dr.data() as u16
+  }
+}
+
+const EXT_DISABLED: u32 = 0b00;
+const EXT_RISING: u32 = 0b01;
+
+mod reg {
+  use volatile_cell::VolatileCell;
+  use core::ops::Drop;
+
+  ioregs!(ADC = {
+    0x00 => reg32 sr {      // status
+      0 => analog_watchdog : rw,
+      1 => eoc : rw,        // end of conversion, cleared by reading dr
+      2 => injected_eoc : rw,
+      3 => injected_start : rw,
+      4 => start : rw,
+      5 => overrun : rw,
+      6 => ready : ro,      // ADC powered up and ready to convert
+    },
+    0x04 => reg32 cr1 {     // control 1
+      8 => scan : rw,
+      25..24 => resolution : rw,
+      26 => overrun_interrupt : rw,
+    },
+    0x08 => reg32 cr2 {     // control 2
+      0 => power_on : rw,
+      1 => continuous : rw,
+      8 => dma : rw,
+      10 => eoc_selection : rw,
+      11 => left_align : rw,
+      27..24 => ext_select : rw,
+      29..28 => ext_enable : rw,
+      30 => sw_start : rw,
+    },
+    0x0C => reg32 smpr1 {   // sample time of channels 20 to 29
+      29..0 => smp[10] : rw,
+    },
+    0x10 => reg32 smpr2 {   // sample time of channels 10 to 19
+      29..0 => smp[10] : rw,
+    },
+    0x14 => reg32 smpr3 {   // sample time of channels 0 to 9
+      29..0 => smp[10] : rw,
+    },
+    0x30 => reg32 sqr1 {    // regular sequence 1
+      24..20 => length : rw,
+    },
+    0x40 => reg32 sqr5 {    // regular sequence 5, conversions 1 to 6
+      29..0 => sq[6] : rw,
+    },
+    0x58 => reg32 dr {      // regular data
+      15..0 => data : ro,
+    },
+  });
+
+  ioregs!(COMMON = {
+    0x04 => reg32 ccr {     // common control
+      17..16 => prescaler : rw,
+      23 => temp_vref : rw,
+    },
+  });
+
+  extern {
+    #[link_name="stm32l1_iomem_ADC1"] pub static ADC1: ADC;
+    #[link_name="stm32l1_iomem_ADC_COMMON"] pub static ADC_COMMON: COMMON;
+  }
+}
@@ -0,0 +1,85 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! DMA controllers for STM32L1.
+//!
+//! The controller and channel code is shared with the other STM32 families,
+//! see `hal::stm32_common::dma`.
+//!
+//! Callbacks are run from `isr_handler`, which must be called from the
+//! `isr_dmaN_channelM` interrupt of the channel.
+
+use core::intrinsics::abort;
+use core::option::Option;
+
+use hal::cortex_m3::nvic::Interrupt;
+use hal::stm32_common::dma as common;
+use hal::stm32l1::irq::Irq;
+use hal::stm32l1::peripheral_clock as clock;
+
+pub use hal::stm32_common::dma::{Channel, Controller, Request, MAX_TRANSFER};
+
+impl Channel {
+  /// Claims the channel serving `request`.
+  ///
+  /// Returns `None` if the channel is already in use.
+  pub fn claim(request: Request) -> Option<Channel> {
+    let (controller, number) = request.channel();
+    clock::Ahb(match controller {
+      Controller::Dma1 => clock::BusAhb::Dma1,
+      Controller::Dma2 => clock::BusAhb::Dma2,
+    }).enable();
+    Channel::claim_id(channel_id(controller, number))
+  }
+}
+
+fn channel_id(controller: Controller, number: usize) -> common::ChannelId {
+  let irq = match (controller, number) {
+    (Controller::Dma1, 1) => Irq::Dma1Channel1,
+    (Controller::Dma1, 2) => Irq::Dma1Channel2,
+    (Controller::Dma1, 3) => Irq::Dma1Channel3,
+    (Controller::Dma1, 4) => Irq::Dma1Channel4,
+    (Controller::Dma1, 5) => Irq::Dma1Channel5,
+    (Controller::Dma1, 6) => Irq::Dma1Channel6,
+    (Controller::Dma1, 7) => Irq::Dma1Channel7,
+    (Controller::Dma2, 1) => Irq::Dma2Channel1,
+    (Controller::Dma2, 2) => Irq::Dma2Channel2,
+    (Controller::Dma2, 3) => Irq::Dma2Channel3,
+    (Controller::Dma2, 4) => Irq::Dma2Channel4,
+    (Controller::Dma2, 5) => Irq::Dma2Channel5,
+    _ => unsafe { abort() },
+  };
+  let dma = match controller {
+    Controller::Dma1 => &reg::DMA1,
+    Controller::Dma2 => &reg::DMA2,
+  };
+  common::ChannelId::new(controller, number, dma, irq.number())
+}
+
+/// Services the interrupt of a DMA channel, running its callback.
+///
+/// Interrupts shared by several channels must call it for each of them.
+pub fn isr_handler(controller: Controller, number: usize) {
+  common::isr_handler(channel_id(controller, number));
+}
+
+mod reg {
+  use hal::stm32_common::dma::reg::DMA;
+
+  extern {
+    #[link_name="stm32l1_iomem_DMA1"] pub static DMA1: DMA;
+    #[link_name="stm32l1_iomem_DMA2"] pub static DMA2: DMA;
+  }
+}
@@ -385,10 +385,24 @@ pub mod reg {
 
   ioregs!(PWR = {
     0x0 => reg32 cr {   // power control
-      31..0 => control : rw,
+      0 => low_power_deepsleep : rw,
+      1 => power_down_deepsleep : rw,
+      2 => clear_wakeup_flag : rw,
+      3 => clear_standby_flag : rw,
+      8 => backup_write_enable : rw,
+      9 => ultra_low_power : rw,
+      10 => fast_wakeup : rw,
+      12..11 => voltage_scaling : rw,
+      14 => low_power_run : rw,
     },
     0x4 => reg32 csr {  // power control/status
-      31..0 => status : rw,
+      0 => wakeup_flag : ro,
+      1 => standby_flag : ro,
+      2 => pvd_output : ro,
+      3 => vrefint_ready : ro,
+      4 => voltage_scaling_flag : ro,
+      5 => regulator_low_power_flag : ro,
+      10..8 => wakeup_pin_enable[3] : rw,
     },
   });
 
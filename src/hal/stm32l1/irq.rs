@@ -0,0 +1,88 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Interrupt lines and NVIC vector table for stm32l1.
+
+use hal::cortex_m3::nvic::Interrupt;
+
+#[path="../irq_list.rs"]
+#[macro_use] mod irq_list;
+
+irqs! {
+  // s.a. RM0038, table 48
+  Wwdg         => isr_wwdg,
+  Pvd          => isr_pvd,
+  TamperStamp  => isr_tamper_stamp,
+  RtcWkup      => isr_rtc_wkup,
+  Flash        => isr_flash,
+  Rcc          => isr_rcc,
+  Exti0        => isr_exti0,
+  Exti1        => isr_exti1,
+  Exti2        => isr_exti2,
+  Exti3        => isr_exti3,
+  Exti4        => isr_exti4,
+  Dma1Channel1 => isr_dma1_channel1,
+  Dma1Channel2 => isr_dma1_channel2,
+  Dma1Channel3 => isr_dma1_channel3,
+  Dma1Channel4 => isr_dma1_channel4,
+  Dma1Channel5 => isr_dma1_channel5,
+  Dma1Channel6 => isr_dma1_channel6,
+  Dma1Channel7 => isr_dma1_channel7,
+  Adc1         => isr_adc1,
+  UsbHp        => isr_usb_hp,
+  UsbLp        => isr_usb_lp,
+  Dac          => isr_dac,
+  Comp         => isr_comp,
+  Exti9To5     => isr_exti9_5,
+  Lcd          => isr_lcd,
+  Tim9         => isr_tim9,
+  Tim10        => isr_tim10,
+  Tim11        => isr_tim11,
+  Tim2         => isr_tim2,
+  Tim3         => isr_tim3,
+  Tim4         => isr_tim4,
+  I2c1Ev       => isr_i2c1_ev,
+  I2c1Er       => isr_i2c1_er,
+  I2c2Ev       => isr_i2c2_ev,
+  I2c2Er       => isr_i2c2_er,
+  Spi1         => isr_spi1,
+  Spi2         => isr_spi2,
+  Usart1       => isr_usart1,
+  Usart2       => isr_usart2,
+  Usart3       => isr_usart3,
+  Exti15To10   => isr_exti15_10,
+  RtcAlarm     => isr_rtc_alarm,
+  UsbFsWkup    => isr_usb_fs_wkup,
+  Tim6         => isr_tim6,
+  Tim7         => isr_tim7,
+  Sdio         => isr_sdio,
+  Tim5         => isr_tim5,
+  Spi3         => isr_spi3,
+  Uart4        => isr_uart4,
+  Uart5        => isr_uart5,
+  Dma2Channel1 => isr_dma2_channel1,
+  Dma2Channel2 => isr_dma2_channel2,
+  Dma2Channel3 => isr_dma2_channel3,
+  Dma2Channel4 => isr_dma2_channel4,
+  Dma2Channel5 => isr_dma2_channel5,
+  Aes          => isr_aes,
+  CompAcq      => isr_comp_acq,
+}
+
+impl Interrupt for Irq {
+  fn number(&self) -> usize {
+    *self as usize
+  }
+}
@@ -15,9 +15,17 @@
 
 //! HAL for STM32L1.
 
+pub mod adc;
+pub mod dma;
+pub mod exti;
+pub mod i2c;
+pub mod irq;
 pub mod init;
 pub mod peripheral_clock;
 pub mod pin;
+pub mod power;
+pub mod rtc;
 pub mod spi;
 pub mod timer;
 pub mod usart;
+pub mod watchdog;
@@ -17,9 +17,15 @@
 //!
 //! Some pins that could be configured here may be missing from actual MCU
 //! depending on the package.
+//!
+//! Edge interrupts are served by the EXTI lines, see `stm32l1::exti`.
 
+use super::exti;
 use super::peripheral_clock;
 use core::intrinsics::abort;
+use core::option::Option;
+use core::result::Result::{self, Ok, Err};
+use hal::pin::{ConfigError, GpioEdge, GpioHandler};
 use self::Port::*;
 
 /// Available port names.
@@ -109,6 +115,8 @@ pub enum Mode {
 pub struct Pin {
   /// Pin index.
   pub index: u8,
+  /// Port the pin belongs to
+  port: Port,
   /// GPIO register
   reg: &'static reg::GPIO,
 }
@@ -182,6 +190,7 @@ impl Pin {
 
     Pin {
       index: pin_index,
+      port: port,
       reg: reg,
     }
   }
@@ -213,6 +222,77 @@ impl ::hal::pin::Gpio for Pin {
   }
 }
 
+/// The L1 GPIO has no drive strength setting, the output speed is used as
+/// slew rate.
+impl ::hal::pin::Electrical for Pin {
+  fn set_pull(&self, pull: ::hal::pin::Pull) -> Result<(), ConfigError> {
+    use hal::pin::Pull;
+    let pull_type = match pull {
+      Pull::Floating => PullType::PullNone,
+      Pull::Up       => PullType::PullUp,
+      Pull::Down     => PullType::PullDown,
+    };
+    let offset = self.index as usize * 2;
+    let v = self.reg.pupdr.mode() & !(0b11 << offset);
+    self.reg.pupdr.set_mode(v | ((pull_type as u32) << offset));
+    Ok(())
+  }
+
+  fn set_output_type(&self, output_type: ::hal::pin::OutputType)
+      -> Result<(), ConfigError> {
+    let otype = match output_type {
+      ::hal::pin::OutputType::PushPull  => OutputType::OutPushPull,
+      ::hal::pin::OutputType::OpenDrain => OutputType::OutOpenDrain,
+    };
+    let offset = self.index as usize;
+    let v = self.reg.otyper.otype() & !(0b1 << offset);
+    self.reg.otyper.set_otype(v | ((otype as u16) << offset));
+    Ok(())
+  }
+
+  fn set_drive_strength(&self, _strength: ::hal::pin::DriveStrength)
+      -> Result<(), ConfigError> {
+    Err(ConfigError::UnsupportedDriveStrength)
+  }
+
+  /// Slow is 400 KHz and fast is 40 MHz.
+  fn set_slew_rate(&self, rate: ::hal::pin::SlewRate)
+      -> Result<(), ConfigError> {
+    let speed = match rate {
+      ::hal::pin::SlewRate::Slow => Speed::VeryLow,
+      ::hal::pin::SlewRate::Fast => Speed::High,
+    };
+    let offset = self.index as usize * 2;
+    let v = self.reg.ospeedr.speed() & !(0b11 << offset);
+    self.reg.ospeedr.set_speed(v | ((speed as u32) << offset));
+    Ok(())
+  }
+}
+
+impl ::hal::pin::GpioInterrupt for Pin {
+  fn set_handler(&self, handler: Option<GpioHandler>) {
+    exti::set_handler(self.index as usize, handler);
+  }
+
+  fn enable_interrupt(&self, edge: GpioEdge) {
+    let port = match self.port {
+      PortH => 5,
+      PortF => 6,
+      PortG => 7,
+      port  => port as u8,
+    };
+    exti::enable(self.index as usize, port, edge);
+  }
+
+  fn disable_interrupt(&self) {
+    exti::disable(self.index as usize);
+  }
+
+  fn clear_interrupt(&self) {
+    exti::clear(self.index as usize);
+  }
+}
+
 mod reg {
   use volatile_cell::VolatileCell;
   use core::ops::Drop;
@@ -0,0 +1,112 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Power modes for STM32L1.
+//!
+//! `DeepSleep` is the Stop mode, with the regulator in low power mode. Any
+//! EXTI line, including the RTC alarm on line 17, wakes the core up, which
+//! then runs from the MSI: `ClockConfig::setup` must be called again to
+//! restore the clocks. `Standby` is left through a reset, triggered by NRST,
+//! the IWDG, an RTC event or a rising edge on an enabled WKUP pin.
+//!
+//! The L1 also has low-power run and low-power sleep modes, where the
+//! regulator is in low power mode while the core runs or sleeps. They're only
+//! available with the system clock at or below 131kHz (MSI range 1) and the
+//! core voltage in range 2 or 3.
+
+use core::intrinsics::abort;
+
+use hal::cortex_m3::scb;
+use hal::power::Mode;
+use hal::stm32l1::init::reg::PWR;
+use hal::stm32l1::peripheral_clock as clock;
+use util::support::wfi;
+
+#[path="../../util/wait_for.rs"]
+#[macro_use] mod wait_for;
+
+/// Enters `mode` until a wake-up event.
+pub fn enter(mode: Mode) {
+  clock::Apb1(clock::BusApb1::Pwr).enable();
+  let low_power_regulator = PWR.cr.low_power_deepsleep();
+  match mode {
+    Mode::Sleep => scb::set_sleepdeep(false),
+    Mode::DeepSleep => {
+      PWR.cr
+        .set_power_down_deepsleep(false)
+        .set_low_power_deepsleep(true);
+      scb::set_sleepdeep(true);
+    },
+    Mode::Standby => {
+      PWR.cr
+        .set_power_down_deepsleep(true)
+        .set_clear_wakeup_flag(true);
+      scb::set_sleepdeep(true);
+    },
+  }
+  wfi();
+  scb::set_sleepdeep(false);
+  PWR.cr.set_low_power_deepsleep(low_power_regulator);
+}
+
+/// Enters low-power sleep until an interrupt.
+pub fn enter_low_power_sleep() {
+  clock::Apb1(clock::BusApb1::Pwr).enable();
+  let low_power_regulator = PWR.cr.low_power_deepsleep();
+  PWR.cr.set_low_power_deepsleep(true);
+  scb::set_sleepdeep(false);
+  wfi();
+  PWR.cr.set_low_power_deepsleep(low_power_regulator);
+}
+
+/// Switches low-power run on or off.
+///
+/// The system clock must not be raised above 131kHz until this returns after
+/// switching low-power run off, as the regulator takes a while to leave its
+/// low power mode.
+pub fn set_low_power_run(enable: bool) {
+  clock::Apb1(clock::BusApb1::Pwr).enable();
+  if enable {
+    PWR.cr.set_low_power_deepsleep(true);
+    PWR.cr.set_low_power_run(true);
+  } else {
+    PWR.cr.set_low_power_run(false);
+    wait_for!(!PWR.csr.regulator_low_power_flag());
+    PWR.cr.set_low_power_deepsleep(false);
+  }
+}
+
+/// Sets whether a rising edge on WKUP pin `pin` (1 to 3) wakes the MCU up
+/// from `Standby`.
+pub fn set_wakeup_pin(pin: usize, enable: bool) {
+  if pin < 1 || pin > 3 {
+    unsafe { abort() };
+  }
+  clock::Apb1(clock::BusApb1::Pwr).enable();
+  PWR.csr.set_wakeup_pin_enable(pin - 1, enable);
+}
+
+/// Returns true if the MCU was reset by waking up from `Standby`.
+///
+/// The flag is kept until the next `Standby`, or until it's cleared by
+/// `clear_standby_flag`.
+pub fn woke_from_standby() -> bool {
+  PWR.csr.standby_flag()
+}
+
+/// Clears the flag returned by `woke_from_standby`.
+pub fn clear_standby_flag() {
+  PWR.cr.set_clear_standby_flag(true);
+}
@@ -19,6 +19,9 @@ use core::result::Result;
 use core::result::Result::{Ok, Err};
 use core::marker::Copy;
 
+use hal::dma::Channel;
+use hal::stm32l1::dma;
+
 #[path="../../util/wait_for.rs"]
 #[macro_use] mod wait_for;
 
@@ -191,6 +194,44 @@ impl Spi {
     }
     r
   }
+
+  /// Writes `data` through a DMA channel, blocking until it's sent.
+  ///
+  /// `channel` must be claimed for the TX request of this SPI. Received data
+  /// is dropped, as with `Spi::write_all`.
+  pub fn write_dma(&self, channel: &dma::Channel, data: &[u8])
+      -> Result<(), ::hal::dma::Error> {
+    for chunk in data.chunks(dma::MAX_TRANSFER) {
+      unsafe { self.start_write_dma(channel, chunk) };
+      try!(self.finish_write_dma(channel));
+    }
+    Ok(())
+  }
+
+  /// Starts writing `data` through a DMA channel and returns immediately.
+  ///
+  /// `channel` must be claimed for the TX request of this SPI. The write must
+  /// be completed with `finish_write_dma` before the SPI is used again.
+  ///
+  /// This is unsafe as `data` must stay valid until the transfer completes.
+  pub unsafe fn start_write_dma(&self, channel: &dma::Channel, data: &[u8]) {
+    channel.memory_to_peripheral(data.as_ptr(), true, data.len(),
+        &self.reg.dr as *const reg::SPI_dr as u32);
+    self.reg.cr2.set_tx_dma_enable(true);
+  }
+
+  /// Waits for a write started with `start_write_dma` to complete.
+  pub fn finish_write_dma(&self, channel: &dma::Channel)
+      -> Result<(), ::hal::dma::Error> {
+    let result = channel.wait();
+    wait_for!(self.reg.sr.transmit_buffer_empty());
+    wait_for!(!self.reg.sr.busy_flag());
+    self.reg.cr2.set_tx_dma_enable(false);
+    // Reading DR then SR clears both RXNE and the overrun flag.
+    self.reg.dr.data();
+    self.reg.sr.overrun_flag();
+    result
+  }
 }
 
 impl ::hal::spi::Spi for Spi {
@@ -203,6 +244,54 @@ impl ::hal::spi::Spi for Spi {
     wait_for!(self.reg.sr.receive_buffer_not_empty());
     self.reg.dr.data() as u8
   }
+
+  fn transfer_in_place(&self, buffer: &mut [u8]) {
+    let len = buffer.len();
+    if len == 0 {
+      return;
+    }
+
+    // TX is double-buffered: queue the next byte while the current one is
+    // still shifting out, then collect the byte received for it.
+    wait_for!(self.reg.sr.transmit_buffer_empty());
+    self.reg.dr.set_data(buffer[0] as u16);
+    for i in 1..len {
+      wait_for!(self.reg.sr.transmit_buffer_empty());
+      self.reg.dr.set_data(buffer[i] as u16);
+      wait_for!(self.reg.sr.receive_buffer_not_empty());
+      buffer[i - 1] = self.reg.dr.data() as u8;
+    }
+    wait_for!(self.reg.sr.receive_buffer_not_empty());
+    buffer[len - 1] = self.reg.dr.data() as u8;
+  }
+
+  fn write_all(&self, data: &[u8]) {
+    for b in data.iter() {
+      wait_for!(self.reg.sr.transmit_buffer_empty());
+      self.reg.dr.set_data(*b as u16);
+    }
+    wait_for!(self.reg.sr.transmit_buffer_empty());
+    wait_for!(!self.reg.sr.busy_flag());
+    // Reading DR then SR clears both RXNE and the overrun flag.
+    self.reg.dr.data();
+    self.reg.sr.overrun_flag();
+  }
+
+  fn read_into(&self, buffer: &mut [u8]) {
+    for b in buffer.iter_mut() {
+      *b = 0;
+    }
+    self.transfer_in_place(buffer);
+  }
+}
+
+impl ::hal::spi::SpiDma for Spi {
+  type Channel = dma::Channel;
+
+  fn write_dma(&self, channel: &dma::Channel, data: &[u8])
+      -> Result<(), ::hal::dma::Error> {
+    Spi::write_dma(self, channel, data)
+  }
 }
 
 mod reg {
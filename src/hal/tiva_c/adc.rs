@@ -0,0 +1,254 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! ADC configuration
+//!
+//! Each of the two ADC modules converts through sample sequencer 3, which
+//! takes one sample at a time. Results are always 12-bit.
+//!
+//! Channels 0 to 11 are the AIN pins, channel 12 selects the temperature
+//! sensor.
+
+use core::result::Result::{self, Ok, Err};
+
+use hal::adc::{self, Error, Resolution};
+use hal::cortex_m4::irq::NoInterrupts;
+use hal::tiva_c::sysctl;
+use util::shared::Shared;
+use util::support::get_reg_ref;
+
+#[path="../../util/wait_for.rs"]
+#[macro_use] mod wait_for;
+
+/// There are 2 ADC modules sharing the same inputs
+#[allow(missing_docs)]
+#[derive(Clone, Copy)]
+pub enum AdcId {
+  Adc0,
+  Adc1,
+}
+
+/// Conversion triggers
+#[derive(Clone, Copy)]
+pub enum Trigger {
+  /// Analog comparator 0
+  Comparator0 = 0x1,
+  /// Analog comparator 1
+  Comparator1 = 0x2,
+  /// GPIO pin selected as ADC trigger
+  Gpio        = 0x4,
+  /// Any timer with its ADC trigger output enabled, see
+  /// `timer::Timer::set_adc_trigger`
+  Timer       = 0x5,
+  /// PWM generator 0
+  Pwm0        = 0x6,
+  /// PWM generator 1
+  Pwm1        = 0x7,
+  /// PWM generator 2
+  Pwm2        = 0x8,
+  /// PWM generator 3
+  Pwm3        = 0x9,
+}
+
+/// Channel number of the temperature sensor
+pub const TEMPERATURE_CHANNEL: u8 = 12;
+
+/// Sample sequencer used for all conversions
+const SEQUENCER: usize = 3;
+
+/// Trigger selections for the sequencer
+const EM_PROCESSOR: u32 = 0x0;
+const EM_ALWAYS:    u32 = 0xF;
+
+/// Last result read from each module, see `latest`
+static LATEST: Shared<[u16; 2]> = Shared::new([0; 2]);
+
+/// Structure describing a single ADC module
+#[derive(Clone, Copy)]
+pub struct Adc {
+  /// Module index
+  index: usize,
+  /// ADC registers
+  regs: &'static reg::Adc,
+}
+
+impl Adc {
+  /// Create and setup an ADC module
+  pub fn new(id: AdcId) -> Adc {
+    let (periph, regs, index) = match id {
+      AdcId::Adc0 => (sysctl::periph::adc::ADC_0, reg::ADC_0, 0),
+      AdcId::Adc1 => (sysctl::periph::adc::ADC_1, reg::ADC_1, 1),
+    };
+
+    periph.ensure_enabled();
+
+    Adc { index: index, regs: get_reg_ref(regs) }
+  }
+
+  /// Start a conversion of `channel` on each `trigger` event
+  ///
+  /// Results are read with `latest`, until `stop` is called.
+  pub fn start_triggered(&self, channel: u8, trigger: Trigger)
+      -> Result<(), Error> {
+    self.configure(channel, trigger as u32)
+  }
+
+  /// Program the sequencer for a single sample of `channel`
+  fn configure(&self, channel: u8, event: u32) -> Result<(), Error> {
+    if channel > TEMPERATURE_CHANNEL {
+      return Err(Error::InvalidChannel);
+    }
+
+    // The sequencer must be disabled while it's being programmed
+    self.regs.actss.set_asen(SEQUENCER, false);
+
+    self.regs.emux.set_em(SEQUENCER, event);
+    if channel == TEMPERATURE_CHANNEL {
+      self.regs.ssctl3.ignoring_state()
+        .set_ts0(true)
+        .set_ie0(true)
+        .set_end0(true);
+    } else {
+      self.regs.ssmux3.set_mux0(channel as u32);
+      self.regs.ssctl3.ignoring_state()
+        .set_ie0(true)
+        .set_end0(true);
+    }
+
+    // Drop any result left from a previous conversion
+    while !self.regs.ssfstat3.empty() {
+      self.regs.ssfifo3.data();
+    }
+    self.regs.ostat.ignoring_state().set_ov(SEQUENCER, true);
+    self.regs.isc.ignoring_state().set_intr(SEQUENCER, true);
+
+    self.regs.actss.set_asen(SEQUENCER, true);
+    Ok(())
+  }
+}
+
+impl adc::Adc for Adc {
+  fn set_resolution(&self, resolution: Resolution) -> Result<(), Error> {
+    match resolution {
+      Resolution::Bits12 => Ok(()),
+      _                  => Err(Error::UnsupportedResolution),
+    }
+  }
+
+  fn resolution(&self) -> Resolution {
+    Resolution::Bits12
+  }
+
+  /// The sample time is fixed on this part.
+  fn set_sample_time(&self, _cycles: u32) -> Result<(), Error> {
+    Err(Error::UnsupportedSampleTime)
+  }
+
+  fn read(&self, channel: u8) -> Result<u16, Error> {
+    try!(self.configure(channel, EM_PROCESSOR));
+
+    self.regs.pssi.ignoring_state().set_ss(SEQUENCER, true);
+    wait_for!(self.regs.ris.inr(SEQUENCER));
+    self.regs.isc.ignoring_state().set_intr(SEQUENCER, true);
+
+    Ok(self.regs.ssfifo3.data() as u16)
+  }
+
+  fn start_continuous(&self, channel: u8) -> Result<(), Error> {
+    self.configure(channel, EM_ALWAYS)
+  }
+
+  fn stop(&self) {
+    self.regs.actss.set_asen(SEQUENCER, false);
+  }
+
+  /// The sequencer FIFO holds a single result and keeps it until it's read,
+  /// so the value returned may be one conversion old.
+  fn latest(&self) -> u16 {
+    let crit = NoInterrupts::new();
+    let mut latest = LATEST.borrow(&crit);
+    while !self.regs.ssfstat3.empty() {
+      latest[self.index] = self.regs.ssfifo3.data() as u16;
+    }
+    self.regs.ostat.ignoring_state().set_ov(SEQUENCER, true);
+    latest[self.index]
+  }
+}
+
+pub mod reg {
+  //! ADC registers definition
+  use volatile_cell::VolatileCell;
+  use core::ops::Drop;
+
+  ioregs!(Adc = {
+    0x000 => reg32 actss {
+      //! Active sample sequencer
+      0..3 => asen[4],
+      16   => busy: ro,
+    }
+
+    0x004 => reg32 ris {
+      //! Raw interrupt status
+      0..3 => inr[4]: ro,
+    }
+
+    0x00C => reg32 isc {
+      //! Interrupt status and clear, write 1 to clear
+      0..3 => intr[4],
+    }
+
+    0x010 => reg32 ostat {
+      //! Overflow status, write 1 to clear
+      0..3 => ov[4],
+    }
+
+    0x014 => reg32 emux {
+      //! Event multiplexer select
+      0..15 => em[4],
+    }
+
+    0x028 => reg32 pssi {
+      //! Processor sample sequence initiate
+      0..3 => ss[4]: wo,
+    }
+
+    0x0A0 => reg32 ssmux3 {
+      //! Sample sequence input multiplexer select 3
+      0..3 => mux0,
+    }
+
+    0x0A4 => reg32 ssctl3 {
+      //! Sample sequence control 3
+      0 => d0,          //= Differential input select
+      1 => end0,        //= End of sequence
+      2 => ie0,         //= Interrupt enable
+      3 => ts0,         //= Temperature sensor select
+    }
+
+    0x0A8 => reg32 ssfifo3 {
+      //! Sample sequence result FIFO 3
+      0..11 => data: ro,
+    }
+
+    0x0AC => reg32 ssfstat3 {
+      //! Sample sequence FIFO 3 status
+      8 => empty: ro,
+      12 => full: ro,
+    }
+  });
+
+  pub const ADC_0: *const Adc = 0x40038000 as *const Adc;
+  pub const ADC_1: *const Adc = 0x40039000 as *const Adc;
+}
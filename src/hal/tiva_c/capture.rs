@@ -0,0 +1,212 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Input capture on the general purpose timers.
+//!
+//! Each timer half captures the edges of its CCP pin in edge-time mode,
+//! counting up at the system clock frequency. Halves of 16/32-bit timers are
+//! 24-bit counters, so measured periods must be shorter than 2^24 system clock
+//! cycles. Wide timer halves are 32 bits.
+//!
+//! Capture mode splits the timer in two half-width counters, so the other half
+//! of a timer used for capture can't be used as a `timer::Timer`. Input pins
+//! must be configured for their CCP function, and `isr_handler` must be called
+//! from the interrupt of each timer half used for capture.
+
+use core::option::Option::{self, Some, None};
+
+use hal::capture::{self, Callback, EdgeTracker, Measurement};
+use hal::cortex_m4::irq::NoInterrupts;
+use hal::cortex_m4::nvic;
+use hal::tiva_c::irq::Irq;
+use hal::tiva_c::pwm::Half;
+use hal::tiva_c::sysctl;
+use hal::tiva_c::timer::{TimerId, reg};
+use util::shared::Shared;
+
+static TRACKERS: Shared<[EdgeTracker; 24]> =
+    Shared::new([EdgeTracker::new(0xffff_ffff); 24]);
+
+/// Structure describing a capture channel.
+#[derive(Clone, Copy)]
+pub struct Capture {
+  id: TimerId,
+  half: Half,
+}
+
+impl Capture {
+  /// Starts capturing edges on `half` of timer `id`.
+  pub fn new(id: TimerId, half: Half) -> Capture {
+    let (regs, wide) = id.enable();
+    regs.cfg.set_cfg(reg::Timer_cfg_cfg::HalfWidth);
+
+    // Counting up, the prescaler extends the counter.
+    let (load, prescale, mask) = if wide {
+      (0xffff_ffff, 0xffff, 0xffff_ffff)
+    } else {
+      (0xffff, 0xff, 0xff_ffff)
+    };
+
+    let capture = Capture {
+      id: id,
+      half: half,
+    };
+    {
+      let crit = NoInterrupts::new();
+      TRACKERS.borrow(&crit)[capture.index()] = EdgeTracker::new(mask);
+    }
+
+    match half {
+      Half::A => {
+        regs.ctl.set_taen(false);
+        regs.amr
+          .set_mr(reg::Timer_amr_mr::Capture)
+          .set_cmr(true)
+          .set_ams(false)
+          .set_cdir(reg::Timer_amr_cdir::Up);
+        regs.tailr.set_tailr(load);
+        regs.apr.set_psr(prescale);
+        regs.ctl.set_taevent(reg::Timer_ctl_taevent::PosEdge);
+        regs.icr.set_caecint(true);
+        regs.imr.set_caeim(true);
+        regs.ctl.set_taen(true);
+      },
+      Half::B => {
+        regs.ctl.set_tben(false);
+        regs.bmr
+          .set_mr(reg::Timer_bmr_mr::Capture)
+          .set_cmr(true)
+          .set_ams(false)
+          .set_cdir(reg::Timer_bmr_cdir::Up);
+        regs.tbilr.set_tbilr(load);
+        regs.bpr.set_psr(prescale);
+        regs.ctl.set_tbevent(reg::Timer_ctl_tbevent::PosEdge);
+        regs.icr.set_cbecint(true);
+        regs.imr.set_cbeim(true);
+        regs.ctl.set_tben(true);
+      },
+    }
+    nvic::enable_irq(irq(id, half));
+    capture
+  }
+
+  fn index(&self) -> usize {
+    self.id as usize * 2 + self.half as usize
+  }
+}
+
+impl capture::InputCapture for Capture {
+  fn tick_hz(&self) -> u32 {
+    sysctl::clock::sysclk_get() as u32
+  }
+
+  fn measurement(&self) -> Option<Measurement> {
+    let crit = NoInterrupts::new();
+    TRACKERS.borrow(&crit)[self.index()].measurement()
+  }
+
+  fn set_callback(&self, callback: Option<Callback>) {
+    let crit = NoInterrupts::new();
+    TRACKERS.borrow(&crit)[self.index()].set_callback(callback);
+  }
+}
+
+fn irq(id: TimerId, half: Half) -> Irq {
+  match (id, half) {
+    (TimerId::Timer0,  Half::A) => Irq::Timer0a,
+    (TimerId::Timer0,  Half::B) => Irq::Timer0b,
+    (TimerId::Timer1,  Half::A) => Irq::Timer1a,
+    (TimerId::Timer1,  Half::B) => Irq::Timer1b,
+    (TimerId::Timer2,  Half::A) => Irq::Timer2a,
+    (TimerId::Timer2,  Half::B) => Irq::Timer2b,
+    (TimerId::Timer3,  Half::A) => Irq::Timer3a,
+    (TimerId::Timer3,  Half::B) => Irq::Timer3b,
+    (TimerId::Timer4,  Half::A) => Irq::Timer4a,
+    (TimerId::Timer4,  Half::B) => Irq::Timer4b,
+    (TimerId::Timer5,  Half::A) => Irq::Timer5a,
+    (TimerId::Timer5,  Half::B) => Irq::Timer5b,
+    (TimerId::TimerW0, Half::A) => Irq::WideTimer0a,
+    (TimerId::TimerW0, Half::B) => Irq::WideTimer0b,
+    (TimerId::TimerW1, Half::A) => Irq::WideTimer1a,
+    (TimerId::TimerW1, Half::B) => Irq::WideTimer1b,
+    (TimerId::TimerW2, Half::A) => Irq::WideTimer2a,
+    (TimerId::TimerW2, Half::B) => Irq::WideTimer2b,
+    (TimerId::TimerW3, Half::A) => Irq::WideTimer3a,
+    (TimerId::TimerW3, Half::B) => Irq::WideTimer3b,
+    (TimerId::TimerW4, Half::A) => Irq::WideTimer4a,
+    (TimerId::TimerW4, Half::B) => Irq::WideTimer4b,
+    (TimerId::TimerW5, Half::A) => Irq::WideTimer5a,
+    (TimerId::TimerW5, Half::B) => Irq::WideTimer5b,
+  }
+}
+
+/// Records the captured edge of a timer half, must be called from its
+/// interrupt.
+///
+/// Capture alternates between rising and falling edges, so edges closer than
+/// the interrupt latency are missed.
+pub fn isr_handler(id: TimerId, half: Half) {
+  let (regs, _) = id.regs();
+  let (count, rising) = match half {
+    Half::A => {
+      if !regs.mis.caemis() {
+        return;
+      }
+      regs.icr.set_caecint(true);
+      let rising = match regs.ctl.taevent() {
+        reg::Timer_ctl_taevent::PosEdge => true,
+        _ => false,
+      };
+      regs.ctl.set_taevent(if rising {
+        reg::Timer_ctl_taevent::NegEdge
+      } else {
+        reg::Timer_ctl_taevent::PosEdge
+      });
+      (regs.tar.v(), rising)
+    },
+    Half::B => {
+      if !regs.mis.cbemis() {
+        return;
+      }
+      regs.icr.set_cbecint(true);
+      let rising = match regs.ctl.tbevent() {
+        reg::Timer_ctl_tbevent::PosEdge => true,
+        _ => false,
+      };
+      regs.ctl.set_tbevent(if rising {
+        reg::Timer_ctl_tbevent::NegEdge
+      } else {
+        reg::Timer_ctl_tbevent::PosEdge
+      });
+      (regs.tbr.v(), rising)
+    },
+  };
+
+  let completed = {
+    let crit = NoInterrupts::new();
+    let mut trackers = TRACKERS.borrow(&crit);
+    let tracker = &mut trackers[id as usize * 2 + half as usize];
+    if rising {
+      tracker.rising(count)
+    } else {
+      tracker.falling(count);
+      None
+    }
+  };
+  match completed {
+    Some((callback, measurement)) => callback(measurement),
+    None => (),
+  }
+}
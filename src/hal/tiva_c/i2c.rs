@@ -0,0 +1,261 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! I2C configuration
+//!
+//! Enables I2C master mode on any of the 4 I2C modules in TM4C
+//! microcontrollers.
+
+use core::result::Result;
+use core::result::Result::{Ok, Err};
+
+use hal::i2c;
+use hal::tiva_c::sysctl;
+use util::support::get_reg_ref;
+
+#[path="../../util/wait_for.rs"]
+#[macro_use] mod wait_for;
+
+/// There are 4 I2C instances
+/// See the TM4C123GH6PM datasheet page 1000 for detailed signal to pin mappings
+#[allow(missing_docs)]
+#[derive(Clone, Copy)]
+pub enum I2cId {
+  /// I2C0, uses pins PB2:3
+  I2c0,
+
+  /// I2C1, uses pins PA6:7
+  I2c1,
+
+  /// I2C2, uses pins PE4:5
+  I2c2,
+
+  /// I2C3, uses pins PD0:1
+  I2c3,
+}
+
+/**
+I2C configuration object
+
+Note: The SDA pin must be configured as open drain and both SDA and SCL pins
+must be set to the I2C function before using the interface.
+*/
+pub struct I2cConf {
+  /// Which I2C peripheral to use
+  pub peripheral: I2cId,
+
+  /// Bus frequency in Hz, typically 100kHz or 400kHz
+  pub frequency: u32,
+}
+
+/// Structure describing a single I2C master
+#[derive(Clone, Copy)]
+pub struct I2c {
+  /// I2C registers
+  regs: &'static reg::I2c,
+}
+
+impl I2c {
+  /// Create and setup an I2C master.
+  pub fn new(config: I2cConf) -> I2c {
+    let (periph, regs) = match config.peripheral {
+      I2cId::I2c0 => (sysctl::periph::i2c::I2C_0, reg::I2C_0),
+      I2cId::I2c1 => (sysctl::periph::i2c::I2C_1, reg::I2C_1),
+      I2cId::I2c2 => (sysctl::periph::i2c::I2C_2, reg::I2C_2),
+      I2cId::I2c3 => (sysctl::periph::i2c::I2C_3, reg::I2C_3),
+    };
+
+    let i2c = I2c { regs: get_reg_ref(regs) };
+
+    // Make sure peripheral clock gating is enabled
+    periph.ensure_enabled();
+
+    i2c.regs.mcr.set_mfe(true);
+    i2c.set_frequency(config.frequency);
+
+    i2c
+  }
+
+  /// Set I2C frequency
+  ///
+  /// The SCL period is `2 * (1 + TPR) * (SCL_LP + SCL_HP) / SysClk`, where
+  /// `SCL_LP` and `SCL_HP` are fixed at 6 and 4 respectively.
+  fn set_frequency(&self, freq: u32) {
+    let sysclk = sysctl::clock::sysclk_get() as u32;
+    let tpr = sysclk / (2 * (6 + 4) * freq);
+
+    self.regs.mtpr.set_tpr(if tpr > 0 { tpr - 1 } else { 0 });
+  }
+
+  /// Issue a command to the master state machine and wait for it to finish
+  fn command(&self, cmd: u32) -> Result<(), i2c::Error> {
+    self.regs.mcs.set_cs(cmd);
+    wait_for!(self.regs.mcs.cs() & MCS_BUSY == 0);
+
+    let status = self.regs.mcs.cs();
+    if status & MCS_ERROR == 0 {
+      return Ok(());
+    }
+
+    // Arbitration loss already released the bus, everything else needs an
+    // explicit STOP unless the failed command carried one.
+    if status & MCS_ARBLST != 0 {
+      return Err(i2c::Error::ArbitrationLost);
+    }
+    if cmd & MCS_STOP == 0 {
+      self.regs.mcs.set_cs(MCS_STOP);
+      wait_for!(self.regs.mcs.cs() & MCS_BUSY == 0);
+    }
+
+    Err(if status & MCS_ADRACK != 0 {
+      i2c::Error::AddressNack
+    } else if status & MCS_DATACK != 0 {
+      i2c::Error::DataNack
+    } else {
+      i2c::Error::Bus
+    })
+  }
+
+  fn write_bytes(&self, address: u8, data: &[u8], stop: bool)
+      -> Result<(), i2c::Error> {
+    self.regs.msa.set_sa(address as u32).set_rs(false);
+
+    let last = data.len() - 1;
+    for (i, &b) in data.iter().enumerate() {
+      self.regs.mdr.set_data(b as u32);
+
+      let mut cmd = MCS_RUN;
+      if i == 0 {
+        cmd |= MCS_START;
+      }
+      if i == last && stop {
+        cmd |= MCS_STOP;
+      }
+      try!(self.command(cmd));
+    }
+    Ok(())
+  }
+
+  fn read_bytes(&self, address: u8, buffer: &mut [u8])
+      -> Result<(), i2c::Error> {
+    self.regs.msa.set_sa(address as u32).set_rs(true);
+
+    let last = buffer.len() - 1;
+    for (i, b) in buffer.iter_mut().enumerate() {
+      let mut cmd = MCS_RUN;
+      if i == 0 {
+        cmd |= MCS_START;
+      }
+      // The last byte is not acknowledged
+      if i == last {
+        cmd |= MCS_STOP;
+      } else {
+        cmd |= MCS_ACK;
+      }
+      try!(self.command(cmd));
+
+      *b = self.regs.mdr.data() as u8;
+    }
+    Ok(())
+  }
+}
+
+impl ::hal::i2c::I2c for I2c {
+  fn write(&self, address: u8, data: &[u8]) -> Result<(), i2c::Error> {
+    if data.is_empty() {
+      return Ok(());
+    }
+    self.write_bytes(address, data, true)
+  }
+
+  fn read(&self, address: u8, buffer: &mut [u8]) -> Result<(), i2c::Error> {
+    if buffer.is_empty() {
+      return Ok(());
+    }
+    self.read_bytes(address, buffer)
+  }
+
+  fn write_read(&self, address: u8, data: &[u8], buffer: &mut [u8])
+      -> Result<(), i2c::Error> {
+    if data.is_empty() {
+      return i2c::I2c::read(self, address, buffer);
+    }
+    if buffer.is_empty() {
+      return i2c::I2c::write(self, address, data);
+    }
+    try!(self.write_bytes(address, data, false));
+    self.read_bytes(address, buffer)
+  }
+}
+
+// I2CMCS bits, read as status
+const MCS_BUSY:   u32 = 1 << 0;
+const MCS_ERROR:  u32 = 1 << 1;
+const MCS_ADRACK: u32 = 1 << 2;
+const MCS_DATACK: u32 = 1 << 3;
+const MCS_ARBLST: u32 = 1 << 4;
+
+// I2CMCS bits, written as command
+const MCS_RUN:    u32 = 1 << 0;
+const MCS_START:  u32 = 1 << 1;
+const MCS_STOP:   u32 = 1 << 2;
+const MCS_ACK:    u32 = 1 << 3;
+
+#[allow(missing_docs)]
+pub mod reg {
+  //! I2C registers definition
+  use volatile_cell::VolatileCell;
+  use core::ops::Drop;
+
+  ioregs!(I2c = {
+    /// I2C master slave address
+    0x000 => reg32 msa {
+      0    => rs: rw,     //= Receive/send, 0 = transmit 1 = receive
+      1..7 => sa: rw,     //= Slave address
+    },
+
+    /// I2C master control/status
+    ///
+    /// The register has different meanings when read (status) and when
+    /// written (command), so it's exposed as raw bits.
+    0x004 => reg32 mcs {
+      0..7 => cs: rw,
+    },
+
+    /// I2C master data
+    0x008 => reg32 mdr {
+      0..7 => data: rw,   //= Data transferred during transaction
+    },
+
+    /// I2C master timer period
+    0x00c => reg32 mtpr {
+      0..6 => tpr: rw,    //= SCL clock period, see `set_frequency`
+      7    => hs: rw,     //= High-speed enable
+    },
+
+    /// I2C master configuration
+    0x020 => reg32 mcr {
+      0 => lpbk: rw,      //= Loopback enable
+      4 => mfe: rw,       //= Master function enable
+      5 => sfe: rw,       //= Slave function enable
+      6 => gfe: rw,       //= Glitch filter enable
+    },
+  });
+
+  pub const I2C_0: *const I2c = 0x40020000 as *const I2c;
+  pub const I2C_1: *const I2c = 0x40021000 as *const I2c;
+  pub const I2C_2: *const I2c = 0x40022000 as *const I2c;
+  pub const I2C_3: *const I2c = 0x40023000 as *const I2c;
+}
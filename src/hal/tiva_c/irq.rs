@@ -0,0 +1,169 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Interrupt lines and NVIC vector table for tiva_c.
+
+use hal::cortex_m4::nvic::Interrupt;
+
+#[path="../irq_list.rs"]
+#[macro_use] mod irq_list;
+
+irqs! {
+  GpioPortA            => isr_gpio_port_a,
+  GpioPortB            => isr_gpio_port_b,
+  GpioPortC            => isr_gpio_port_c,
+  GpioPortD            => isr_gpio_port_d,
+  GpioPortE            => isr_gpio_port_e,
+  Uart0,
+  Uart1,
+  Ssi0,
+  I2c0,
+  PwmFault,
+  PwmGenerator0,
+  PwmGenerator1,
+  PwmGenerator2,
+  QuadratureEncoder0,
+  AdcSequence0,
+  AdcSequence1,
+  AdcSequence2,
+  AdcSequence3,
+  WatchdogTimer,
+  Timer0a              => isr_timer_0a,
+  Timer0b              => isr_timer_0b,
+  Timer1a              => isr_timer_1a,
+  Timer1b              => isr_timer_1b,
+  Timer2a              => isr_timer_2a,
+  Timer2b              => isr_timer_2b,
+  AnalogComparator0,
+  AnalogComparator1,
+  AnalogComparator2,
+  SystemControl,
+  FlashControl,
+  GpioPortF            => isr_gpio_port_f,
+  GpioPortG,
+  GpioPortH,
+  Uart2,
+  Ssi1,
+  Timer3a              => isr_timer_3a,
+  Timer3b              => isr_timer_3b,
+  I2c1,
+  QuadratureEncoder1,
+  Can0,
+  Can1,
+  _,
+  _,
+  Hibernate,
+  Usb0,
+  PwmGenerator3,
+  UdmaSoftwareTransfer,
+  UdmaError,
+  Adc1Sequence0,
+  Adc1Sequence1,
+  Adc1Sequence2,
+  Adc1Sequence3,
+  _,
+  _,
+  GpioPortJ,
+  GpioPortK,
+  GpioPortL,
+  Ssi2,
+  Ssi3,
+  Uart3,
+  Uart4,
+  Uart5,
+  Uart6,
+  Uart7,
+  _,
+  _,
+  _,
+  _,
+  I2c2,
+  I2c3,
+  Timer4a              => isr_timer_4a,
+  Timer4b              => isr_timer_4b,
+  _,
+  _,
+  _,
+  _,
+  _,
+  _,
+  _,
+  _,
+  _,
+  _,
+  _,
+  _,
+  _,
+  _,
+  _,
+  _,
+  _,
+  _,
+  _,
+  _,
+  Timer5a              => isr_timer_5a,
+  Timer5b              => isr_timer_5b,
+  WideTimer0a          => isr_wtimer_0a,
+  WideTimer0b          => isr_wtimer_0b,
+  WideTimer1a          => isr_wtimer_1a,
+  WideTimer1b          => isr_wtimer_1b,
+  WideTimer2a          => isr_wtimer_2a,
+  WideTimer2b          => isr_wtimer_2b,
+  WideTimer3a          => isr_wtimer_3a,
+  WideTimer3b          => isr_wtimer_3b,
+  WideTimer4a          => isr_wtimer_4a,
+  WideTimer4b          => isr_wtimer_4b,
+  WideTimer5a          => isr_wtimer_5a,
+  WideTimer5b          => isr_wtimer_5b,
+  Fpu,
+  _,
+  _,
+  I2c4,
+  I2c5,
+  GpioPortM,
+  GpioPortN,
+  QuadratureEncoder2,
+  _,
+  _,
+  GpioPortP,
+  GpioPortP1,
+  GpioPortP2,
+  GpioPortP3,
+  GpioPortP4,
+  GpioPortP5,
+  GpioPortP6,
+  GpioPortP7,
+  GpioPortQ,
+  GpioPortQ1,
+  GpioPortQ2,
+  GpioPortQ3,
+  GpioPortQ4,
+  GpioPortQ5,
+  GpioPortQ6,
+  GpioPortQ7,
+  GpioPortR,
+  GpioPortS,
+  Pwm1Generator0,
+  Pwm1Generator1,
+  Pwm1Generator2,
+  Pwm1Generator3,
+  Pwm1Fault,
+}
+
+impl Interrupt for Irq {
+  fn number(&self) -> usize {
+    *self as usize
+  }
+}
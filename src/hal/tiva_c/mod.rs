@@ -19,7 +19,13 @@
 pub mod sysctl;
 pub mod pin;
 pub mod timer;
+pub mod pwm;
+pub mod capture;
 pub mod uart;
 pub mod spi;
+pub mod i2c;
+pub mod irq;
+pub mod adc;
+pub mod watchdog;
 
 #[path="../../util/ioreg.rs"] mod util;
@@ -16,9 +16,22 @@
 //! Pin configuration
 //! Allows GPIO configuration
 //! Pin muxing not implemented yet.
+//!
+//! Each port has its own interrupt, `isr_handler` must be called from the
+//! `isr_gpio_port_*` ISR of the port.
 
+use core::option::Option::{self, Some, None};
+use core::result::Result::{self, Ok, Err};
+
+use hal::cortex_m4::irq::NoInterrupts;
+use hal::cortex_m4::nvic;
 use hal::pin::{Gpio, GpioDirection, In, Out, GpioLevel, High, Low};
+use hal::pin::{GpioEdge, GpioHandler, GpioInterrupt};
+use hal::pin::{ConfigError, Electrical, Pull, OutputType, DriveStrength};
+use hal::pin::SlewRate;
+use hal::tiva_c::irq::Irq;
 use hal::tiva_c::sysctl;
+use util::shared::Shared;
 use util::support::get_reg_ref;
 
 /// The pins are accessed through ports. Each port has 8 pins and are identified
@@ -37,6 +50,8 @@ pub enum PortId {
 /// Structure describing a single HW pin
 #[derive(Clone, Copy)]
 pub struct Pin {
+  /// Port the pin belongs to
+  port: PortId,
   /// Timer register interface
   regs: &'static reg::Port,
   /// Pin index in the port
@@ -51,18 +66,22 @@ impl Pin {
              function:  u8) -> Pin {
 
     // Retrieve GPIO port peripheral to enable it
-    let (periph, regs) = match pid {
-      PortId::PortA => (sysctl::periph::gpio::PORT_A, reg::PORT_A),
-      PortId::PortB => (sysctl::periph::gpio::PORT_B, reg::PORT_B),
-      PortId::PortC => (sysctl::periph::gpio::PORT_C, reg::PORT_C),
-      PortId::PortD => (sysctl::periph::gpio::PORT_D, reg::PORT_D),
-      PortId::PortE => (sysctl::periph::gpio::PORT_E, reg::PORT_E),
-      PortId::PortF => (sysctl::periph::gpio::PORT_F, reg::PORT_F),
+    let periph = match pid {
+      PortId::PortA => sysctl::periph::gpio::PORT_A,
+      PortId::PortB => sysctl::periph::gpio::PORT_B,
+      PortId::PortC => sysctl::periph::gpio::PORT_C,
+      PortId::PortD => sysctl::periph::gpio::PORT_D,
+      PortId::PortE => sysctl::periph::gpio::PORT_E,
+      PortId::PortF => sysctl::periph::gpio::PORT_F,
     };
 
     periph.ensure_enabled();
 
-    let pin = Pin { regs: get_reg_ref(regs), index: pin_index as usize };
+    let pin = Pin {
+      port: pid,
+      regs: port_regs(pid),
+      index: pin_index as usize,
+    };
 
     pin.configure(dir, function);
 
@@ -92,15 +111,12 @@ impl Pin {
       }
     }
 
-    // We can chose to drive each GPIO at either 2, 4 or 8mA. Default to 2mA for
-    // now.
-    // TODO(simias): make that configurable
+    // We can chose to drive each GPIO at either 2, 4 or 8mA. Default to 2mA,
+    // the electrical settings can be changed later through `Electrical`.
     self.regs.dr2r.set_dr2r(self.index, true);
     self.regs.dr4r.set_dr4r(self.index, false);
     self.regs.dr8r.set_dr8r(self.index, false);
 
-    // TODO(simias): configure open drain/pull up/pull down/slew rate if necessary
-
     self.regs.odr.set_odr(self.index, false);
     self.regs.pur.set_pur(self.index, false);
     self.regs.pdr.set_pdr(self.index, false);
@@ -143,6 +159,118 @@ impl Gpio for Pin {
   }
 }
 
+impl Electrical for Pin {
+  fn set_pull(&self, pull: Pull) -> Result<(), ConfigError> {
+    self.regs.pur.set_pur(self.index, pull == Pull::Up);
+    self.regs.pdr.set_pdr(self.index, pull == Pull::Down);
+    Ok(())
+  }
+
+  fn set_output_type(&self, output_type: OutputType)
+      -> Result<(), ConfigError> {
+    self.regs.odr.set_odr(self.index, output_type == OutputType::OpenDrain);
+    Ok(())
+  }
+
+  /// Low is 2mA, medium is 4mA and high is 8mA.
+  fn set_drive_strength(&self, strength: DriveStrength)
+      -> Result<(), ConfigError> {
+    // Setting one of the drive selection bits clears the other two.
+    match strength {
+      DriveStrength::Low    => { self.regs.dr2r.set_dr2r(self.index, true); },
+      DriveStrength::Medium => { self.regs.dr4r.set_dr4r(self.index, true); },
+      DriveStrength::High   => { self.regs.dr8r.set_dr8r(self.index, true); },
+    }
+    Ok(())
+  }
+
+  /// Slew rate control is only available with the 8mA drive strength.
+  fn set_slew_rate(&self, rate: SlewRate) -> Result<(), ConfigError> {
+    if rate == SlewRate::Slow && !self.regs.dr8r.dr8r(self.index) {
+      return Err(ConfigError::UnsupportedSlewRate);
+    }
+    self.regs.slr.set_slr(self.index, rate == SlewRate::Slow);
+    Ok(())
+  }
+}
+
+impl GpioInterrupt for Pin {
+  fn set_handler(&self, handler: Option<GpioHandler>) {
+    let index = self.port as usize * 8 + self.index;
+    let crit = NoInterrupts::new();
+    HANDLERS.borrow(&crit)[index] = handler;
+  }
+
+  fn enable_interrupt(&self, edge: GpioEdge) {
+    // Configure the edge with the interrupt masked, as changing the sense
+    // may trigger a spurious interrupt.
+    self.regs.im.set_im(self.index, false);
+    self.regs.is.set_is(self.index, reg::Port_is_is::EDGE);
+    self.regs.ibe.set_ibe(self.index, edge == GpioEdge::Both);
+    self.regs.iev.set_iev(self.index, match edge {
+      GpioEdge::Falling => reg::Port_iev_iev::FALLING,
+      _                 => reg::Port_iev_iev::RISING,
+    });
+    self.clear_interrupt();
+    self.regs.im.set_im(self.index, true);
+
+    nvic::enable_irq(port_irq(self.port));
+  }
+
+  fn disable_interrupt(&self) {
+    self.regs.im.set_im(self.index, false);
+  }
+
+  fn clear_interrupt(&self) {
+    self.regs.icr.set_ic(self.index, true);
+  }
+}
+
+static HANDLERS: Shared<[Option<GpioHandler>; 48]> = Shared::new([None; 48]);
+
+fn port_regs(pid: PortId) -> &'static reg::Port {
+  get_reg_ref(match pid {
+    PortId::PortA => reg::PORT_A,
+    PortId::PortB => reg::PORT_B,
+    PortId::PortC => reg::PORT_C,
+    PortId::PortD => reg::PORT_D,
+    PortId::PortE => reg::PORT_E,
+    PortId::PortF => reg::PORT_F,
+  })
+}
+
+fn port_irq(pid: PortId) -> Irq {
+  match pid {
+    PortId::PortA => Irq::GpioPortA,
+    PortId::PortB => Irq::GpioPortB,
+    PortId::PortC => Irq::GpioPortC,
+    PortId::PortD => Irq::GpioPortD,
+    PortId::PortE => Irq::GpioPortE,
+    PortId::PortF => Irq::GpioPortF,
+  }
+}
+
+/// Services the interrupt of a port, calling handlers of the pins that
+/// triggered.
+pub fn isr_handler(pid: PortId) {
+  let regs = port_regs(pid);
+  for index in 0..8 {
+    if !regs.mis.mis(index) {
+      continue;
+    }
+    regs.icr.set_ic(index, true);
+
+    let handler = {
+      let crit = NoInterrupts::new();
+      HANDLERS.borrow(&crit)[pid as usize * 8 + index]
+    };
+    match handler {
+      Some(handler) => handler(),
+      None => (),
+    }
+  }
+}
+
 pub mod reg {
   //! Pin registers definition
   use volatile_cell::VolatileCell;
@@ -162,6 +290,47 @@ pub mod reg {
       }
     }
 
+    0x404 => reg32 is {
+      //! Interrupt sense
+      0..7 => is[8] {
+        0 => EDGE,
+        1 => LEVEL,
+      }
+    }
+
+    0x408 => reg32 ibe {
+      //! Interrupt on both edges
+      0..7 => ibe[8]
+    }
+
+    0x40C => reg32 iev {
+      //! Interrupt event
+      0..7 => iev[8] {
+        0 => FALLING,
+        1 => RISING,
+      }
+    }
+
+    0x410 => reg32 im {
+      //! Interrupt mask, set to enable
+      0..7 => im[8]
+    }
+
+    0x414 => reg32 ris {
+      //! Raw interrupt status
+      0..7 => ris[8]: ro
+    }
+
+    0x418 => reg32 mis {
+      //! Masked interrupt status
+      0..7 => mis[8]: ro
+    }
+
+    0x41C => reg32 icr {
+      //! Interrupt clear
+      0..7 => ic[8]: wo
+    }
+
     0x420 => reg32 afsel {
       //! Pin alternate function
       0..7 => afsel[8] {
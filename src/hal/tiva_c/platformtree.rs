@@ -21,8 +21,10 @@ use node;
 
 mod clock_pt;
 mod pin_pt;
+mod pwm_pt;
 mod timer_pt;
 mod uart_pt;
+mod watchdog_pt;
 
 pub fn attach(builder: &mut Builder, cx: &mut ExtCtxt, node: Rc<node::Node>) {
   node.materializer.set(Some(verify as fn(&mut Builder, &mut ExtCtxt, Rc<node::Node>)));
@@ -32,8 +34,10 @@ pub fn attach(builder: &mut Builder, cx: &mut ExtCtxt, node: Rc<node::Node>) {
     match sub.path.as_str() {
       "clock" => clock_pt::attach(builder, cx, sub.clone()),
       "gpio"  => pin_pt  ::attach(builder, cx, sub.clone()),
+      "pwm"   => pwm_pt  ::attach(builder, cx, sub.clone()),
       "timer" => timer_pt::attach(builder, cx, sub.clone()),
       "uart"  => uart_pt ::attach(builder, cx, sub.clone()),
+      "watchdog" => watchdog_pt::attach(builder, cx, sub.clone()),
       _       => (),
     }
   }
@@ -41,7 +45,8 @@ pub fn attach(builder: &mut Builder, cx: &mut ExtCtxt, node: Rc<node::Node>) {
 
 fn verify(_: &mut Builder, cx: &mut ExtCtxt, node: Rc<node::Node>) {
   node.expect_no_attributes(cx);
-  node.expect_subnodes(cx, &["clock", "gpio", "timer", "uart"]);
+  node.expect_subnodes(cx, &["clock", "gpio", "pwm", "timer", "uart",
+      "watchdog"]);
 }
 
 pub fn add_node_dependency_on_clock(builder: &mut Builder,
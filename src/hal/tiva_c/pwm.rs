@@ -0,0 +1,145 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! PWM outputs on the general purpose timers.
+//!
+//! Each timer has two independent PWM outputs, driven by its A and B halves on
+//! the CCP0 and CCP1 pins. The halves of 16/32-bit timers are 24-bit counters,
+//! which limits their period to 2^24 system clock cycles.
+//!
+//! PWM mode splits the timer in two half-width counters, so the other half of
+//! a timer used for PWM can't be used as a `timer::Timer`.
+
+use core::cmp::min;
+
+use hal::pwm::PWMOutput;
+use hal::tiva_c::sysctl;
+use hal::tiva_c::timer::{TimerId, reg};
+
+/// Halves of a timer.
+#[allow(missing_docs)]
+#[derive(Clone, Copy)]
+pub enum Half {
+  A,
+  B,
+}
+
+/// Structure describing a PWM output.
+#[derive(Clone, Copy)]
+pub struct PWM {
+  regs: &'static reg::Timer,
+  wide: bool,
+  half: Half,
+  period_us: u32,
+  pulsewidth_us: u32,
+}
+
+impl PWM {
+  /// Creates a PWM output on `half` of timer `id`, initially low.
+  pub fn new(id: TimerId, half: Half, period_us: u32) -> PWM {
+    let (regs, wide) = id.enable();
+    regs.cfg.set_cfg(reg::Timer_cfg_cfg::HalfWidth);
+
+    let pwm = PWM {
+      regs: regs,
+      wide: wide,
+      half: half,
+      period_us: period_us,
+      pulsewidth_us: 0,
+    };
+
+    match half {
+      Half::A => {
+        regs.ctl.set_taen(false);
+        regs.amr
+          .set_mr(reg::Timer_amr_mr::Periodic)
+          .set_cmr(false)
+          .set_ams(true)
+          .set_cdir(reg::Timer_amr_cdir::Down);
+        regs.ctl.set_tapwml(false);
+        pwm.update();
+        regs.ctl.set_taen(true);
+      },
+      Half::B => {
+        regs.ctl.set_tben(false);
+        regs.bmr
+          .set_mr(reg::Timer_bmr_mr::Periodic)
+          .set_cmr(false)
+          .set_ams(true)
+          .set_cdir(reg::Timer_bmr_cdir::Down);
+        regs.ctl.set_tbpwml(false);
+        pwm.update();
+        regs.ctl.set_tben(true);
+      },
+    }
+    pwm
+  }
+
+  /// Splits a counter value between the interval or match register and the
+  /// prescaler, which extends the counter in PWM mode.
+  fn split(&self, value: u32) -> (u32, u32) {
+    if self.wide {
+      (value, 0)
+    } else {
+      (value & 0xffff, value >> 16)
+    }
+  }
+
+  fn update(&self) {
+    let ticks_per_us = sysctl::clock::sysclk_get() as u32 / 1_000_000;
+    let load = self.period_us * ticks_per_us;
+    let width = min(self.pulsewidth_us, self.period_us) * ticks_per_us;
+
+    // The output is high from the load value until the counter reaches the
+    // match value.
+    let (load_low, load_high) = self.split(load);
+    let (match_low, match_high) = self.split(load - width);
+
+    match self.half {
+      Half::A => {
+        self.regs.apr.set_psr(load_high);
+        self.regs.tailr.set_tailr(load_low);
+        self.regs.apmr.set_psmr(match_high);
+        self.regs.tamatchr.set_tamr(match_low);
+      },
+      Half::B => {
+        self.regs.bpr.set_psr(load_high);
+        self.regs.tbilr.set_tbilr(load_low);
+        self.regs.bpmr.set_psmr(match_high);
+        self.regs.tbmatchr.set_tbmr(match_low);
+      },
+    }
+  }
+}
+
+impl PWMOutput for PWM {
+  fn set_period_us(&mut self, period_us: u32) {
+    self.period_us = period_us;
+    self.update();
+  }
+
+  fn get_period_us(&self) -> u32 {
+    self.period_us
+  }
+
+  fn set_pulsewidth_us(&mut self, pulsewidth_us: u32) {
+    self.pulsewidth_us = pulsewidth_us;
+    self.update();
+  }
+
+  fn get_pulsewidth_us(&self) -> u32 {
+    self.pulsewidth_us
+  }
+}
@@ -0,0 +1,81 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::rc::Rc;
+use syntax::ext::base::ExtCtxt;
+use regex::Regex;
+
+use builder::{Builder, TokenString, add_node_dependency};
+use node;
+
+pub fn attach(builder: &mut Builder, _: &mut ExtCtxt, node: Rc<node::Node>) {
+  node.materializer.set(Some(verify as fn(&mut Builder, &mut ExtCtxt, Rc<node::Node>)));
+  for pwm_node in node.subnodes().iter() {
+    pwm_node.materializer.set(Some(build_pwm as fn(&mut Builder, &mut ExtCtxt, Rc<node::Node>)));
+    add_node_dependency(&node, pwm_node);
+    super::add_node_dependency_on_clock(builder, pwm_node);
+  }
+}
+
+pub fn verify(_: &mut Builder, cx: &mut ExtCtxt, node: Rc<node::Node>) {
+  node.expect_no_attributes(cx);
+}
+
+fn build_pwm(builder: &mut Builder, cx: &mut ExtCtxt, node: Rc<node::Node>) {
+  if !node.expect_attributes(cx, &[("period_us", node::IntAttribute)]) {
+    return
+  }
+
+  if node.name.is_none() {
+    cx.parse_sess().span_diagnostic.span_err(node.name_span,
+        "pwm node must have a name");
+    return
+  }
+
+  let name = TokenString(node.name.clone().unwrap());
+  let period_us = node.get_int_attr("period_us").unwrap() as u32;
+
+  // PWM path is in the form "w?[0-5][AB]", the timer path followed by the
+  // half of the timer driving the output.
+  let (wide_timer, id, half) =
+    match Regex::new(r"^(w?)([0-5])([AB])$").unwrap()
+        .captures(node.path.as_str()) {
+      Some(c) => (c.at(1) != Some(""), c.at(2).unwrap(), c.at(3).unwrap()),
+      None => {
+        cx.parse_sess().span_diagnostic.span_err(node.path_span,
+            format!("invalid pwm index `{}`, it should match `w?[0-5][AB]`",
+                    node.path).as_str());
+        return;
+      }
+    };
+
+  let timer_name = TokenString(
+    format!("zinc::hal::tiva_c::timer::TimerId::{}{}",
+            if wide_timer {
+              "TimerW"
+            } else {
+              "Timer"
+            }, id));
+  let half_name = TokenString(
+    format!("zinc::hal::tiva_c::pwm::Half::{}", half));
+
+  node.set_type_name("zinc::hal::tiva_c::pwm::PWM".to_string());
+
+  let st = quote_stmt!(&*cx,
+      let $name = zinc::hal::tiva_c::pwm::PWM::new(
+          $timer_name, $half_name, $period_us);
+  );
+  builder.add_main_statement(st.unwrap());
+}
@@ -252,8 +252,52 @@ impl ::hal::spi::Spi for Spi {
 
     self.regs.ssidr.data() as u8
   }
+
+  fn transfer_in_place(&self, buffer: &mut [u8]) {
+    let len = buffer.len();
+    let mut tx: usize = 0;
+    let mut rx: usize = 0;
+
+    // Keep the TX FIFO fed, but never have more frames in flight than the RX
+    // FIFO can hold, or received data would be lost.
+    while rx < len {
+      if tx < len && tx - rx < FIFO_DEPTH && self.regs.ssisr.tnf() {
+        self.regs.ssidr.set_data(buffer[tx] as u16);
+        tx += 1;
+      }
+      if self.regs.ssisr.rne() {
+        buffer[rx] = self.regs.ssidr.data() as u8;
+        rx += 1;
+      }
+    }
+  }
+
+  fn write_all(&self, data: &[u8]) {
+    for b in data.iter() {
+      wait_for!(self.regs.ssisr.tnf());
+      self.regs.ssidr.set_data(*b as u16);
+      // Drop received frames as we go, so the RX FIFO never overruns
+      while self.regs.ssisr.rne() {
+        self.regs.ssidr.data();
+      }
+    }
+    wait_for!(!self.regs.ssisr.bsy());
+    while self.regs.ssisr.rne() {
+      self.regs.ssidr.data();
+    }
+  }
+
+  fn read_into(&self, buffer: &mut [u8]) {
+    for b in buffer.iter_mut() {
+      *b = 0;
+    }
+    self.transfer_in_place(buffer);
+  }
 }
 
+/// Depth of the SSI transmit and receive FIFOs, in frames
+const FIFO_DEPTH: usize = 8;
+
 #[allow(missing_docs)]
 pub mod reg {
   //! SSI registers definition
@@ -339,6 +339,17 @@ pub mod periph {
     }
   }
 
+  #[allow(missing_docs)]
+  pub mod watchdog {
+    //! Watchdog peripherals instances
+    const CLASS: u8 = 0x0 / 4;
+
+    pub const WATCHDOG_0: super::PeripheralClock =
+      super::PeripheralClock { class: CLASS, id: 0 };
+    pub const WATCHDOG_1: super::PeripheralClock =
+      super::PeripheralClock { class: CLASS, id: 1 };
+  }
+
   #[allow(missing_docs)]
   pub mod gpio {
     //! GPIO system control peripherals. Split into ports of 8 GPIO each.
@@ -394,6 +405,17 @@ pub mod periph {
       super::PeripheralClock { class: TIMER_W_CLASS, id: 5 };
   }
 
+  #[allow(missing_docs)]
+  pub mod adc {
+    //! ADC peripherals instances
+    const CLASS: u8 = 0x38 / 4;
+
+    pub const ADC_0: super::PeripheralClock =
+      super::PeripheralClock { class: CLASS, id: 0 };
+    pub const ADC_1: super::PeripheralClock =
+      super::PeripheralClock { class: CLASS, id: 1 };
+  }
+
   #[allow(missing_docs)]
   pub mod uart {
     //! UART peripherals instances
@@ -431,6 +453,21 @@ pub mod periph {
     pub const SSI_3: super::PeripheralClock =
       super::PeripheralClock { class: CLASS, id: 3 };
   }
+
+  #[allow(missing_docs)]
+  pub mod i2c {
+    //! I2C peripherals instances
+    const CLASS: u8 = 0x20 / 4;
+
+    pub const I2C_0: super::PeripheralClock =
+      super::PeripheralClock { class: CLASS, id: 0 };
+    pub const I2C_1: super::PeripheralClock =
+      super::PeripheralClock { class: CLASS, id: 1 };
+    pub const I2C_2: super::PeripheralClock =
+      super::PeripheralClock { class: CLASS, id: 2 };
+    pub const I2C_3: super::PeripheralClock =
+      super::PeripheralClock { class: CLASS, id: 3 };
+  }
 }
 
 pub mod reg {
@@ -448,6 +485,15 @@ pub mod reg {
       10 => vddaris:    ro,  //= VDDA Power OK event raw interrupt status
       11 => bor0ris:    ro,  //= VDD under BOR0 raw interrupt status
     }
+    0x05C => reg32 resc {
+      0  => ext,             //= External reset
+      1  => por,             //= Power-on reset
+      2  => bor,             //= Brown-out reset
+      3  => wdt0,            //= Watchdog timer 0 reset
+      4  => sw,              //= Software reset
+      5  => wdt1,            //= Watchdog timer 1 reset
+      16 => mofr,            //= Main oscillator failure reset
+    }
     0x060 => reg32 rcc {
       0      => mosdis,      //= Main oscillator disable
       4..5   => oscsrc,      //= Oscillator source
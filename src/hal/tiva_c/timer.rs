@@ -38,6 +38,54 @@ pub enum TimerId {
   TimerW5,
 }
 
+impl TimerId {
+  /// Powers up the timer, returning its registers and whether it's wide.
+  pub fn enable(self) -> (&'static reg::Timer, bool) {
+    self.periph().ensure_enabled();
+    self.regs()
+  }
+
+  /// Returns the registers of the timer and whether it's wide.
+  pub fn regs(self) -> (&'static reg::Timer, bool) {
+    let (_, regs, wide) = self.info();
+    (get_reg_ref(regs), wide)
+  }
+
+  fn periph(self) -> sysctl::periph::PeripheralClock {
+    let (periph, _, _) = self.info();
+    periph
+  }
+
+  fn info(self) -> (sysctl::periph::PeripheralClock, *const reg::Timer, bool) {
+    match self {
+      TimerId::Timer0  =>
+        (sysctl::periph::timer::TIMER_0,   reg::TIMER_0,   false),
+      TimerId::Timer1  =>
+        (sysctl::periph::timer::TIMER_1,   reg::TIMER_1,   false),
+      TimerId::Timer2  =>
+        (sysctl::periph::timer::TIMER_2,   reg::TIMER_2,   false),
+      TimerId::Timer3  =>
+        (sysctl::periph::timer::TIMER_3,   reg::TIMER_3,   false),
+      TimerId::Timer4  =>
+        (sysctl::periph::timer::TIMER_4,   reg::TIMER_4,   false),
+      TimerId::Timer5  =>
+        (sysctl::periph::timer::TIMER_5,   reg::TIMER_5,   false),
+      TimerId::TimerW0 =>
+        (sysctl::periph::timer::TIMER_W_0, reg::TIMER_W_0, true),
+      TimerId::TimerW1 =>
+        (sysctl::periph::timer::TIMER_W_1, reg::TIMER_W_1, true),
+      TimerId::TimerW2 =>
+        (sysctl::periph::timer::TIMER_W_2, reg::TIMER_W_2, true),
+      TimerId::TimerW3 =>
+        (sysctl::periph::timer::TIMER_W_3, reg::TIMER_W_3, true),
+      TimerId::TimerW4 =>
+        (sysctl::periph::timer::TIMER_W_4, reg::TIMER_W_4, true),
+      TimerId::TimerW5 =>
+        (sysctl::periph::timer::TIMER_W_5, reg::TIMER_W_5, true),
+    }
+  }
+}
+
 /// Timer modes
 #[derive(Clone, Copy)]
 pub enum Mode {
@@ -74,36 +122,9 @@ impl Timer {
   pub fn new(id:      TimerId,
              mode:     Mode,
              prescale: u32) -> Timer {
-    let (periph, regs, wide) = match id {
-      TimerId::Timer0  =>
-        (sysctl::periph::timer::TIMER_0,   reg::TIMER_0,   false),
-      TimerId::Timer1  =>
-        (sysctl::periph::timer::TIMER_1,   reg::TIMER_1,   false),
-      TimerId::Timer2  =>
-        (sysctl::periph::timer::TIMER_2,   reg::TIMER_2,   false),
-      TimerId::Timer3  =>
-        (sysctl::periph::timer::TIMER_3,   reg::TIMER_3,   false),
-      TimerId::Timer4  =>
-        (sysctl::periph::timer::TIMER_4,   reg::TIMER_4,   false),
-      TimerId::Timer5  =>
-        (sysctl::periph::timer::TIMER_5,   reg::TIMER_5,   false),
-      TimerId::TimerW0 =>
-        (sysctl::periph::timer::TIMER_W_0, reg::TIMER_W_0, true),
-      TimerId::TimerW1 =>
-        (sysctl::periph::timer::TIMER_W_1, reg::TIMER_W_1, true),
-      TimerId::TimerW2 =>
-        (sysctl::periph::timer::TIMER_W_2, reg::TIMER_W_2, true),
-      TimerId::TimerW3 =>
-        (sysctl::periph::timer::TIMER_W_3, reg::TIMER_W_3, true),
-      TimerId::TimerW4 =>
-        (sysctl::periph::timer::TIMER_W_4, reg::TIMER_W_4, true),
-      TimerId::TimerW5 =>
-        (sysctl::periph::timer::TIMER_W_5, reg::TIMER_W_5, true),
-    };
-
-    periph.ensure_enabled();
+    let (regs, wide) = id.enable();
 
-    let timer = Timer { regs: get_reg_ref(regs), wide: wide, mode: mode};
+    let timer = Timer { regs: regs, wide: wide, mode: mode};
 
     timer.configure(prescale);
 
@@ -144,6 +165,11 @@ impl Timer {
     // Timer is now configured, we can enable it
     self.regs.ctl.set_taen(true);
   }
+
+  /// Enable or disable ADC triggers on each timeout
+  pub fn set_adc_trigger(&self, enable: bool) {
+    self.regs.ctl.set_taote(enable);
+  }
 }
 
 impl timer::Timer for Timer {
@@ -191,6 +217,27 @@ pub mod reg {
       10      => rsu,      //= match register update
       11      => plo,      //= PWM legacy operation
     }
+    0x08 => reg32 bmr {
+      //! Timer B mode
+      0..1    => mr {      //! mode
+        1 => OneShot,
+        2 => Periodic,
+        3 => Capture,
+      },
+      2       => cmr,      //= capture mode
+      3       => ams,      //= alternate mode select
+      4       => cdir {    //! Count direction
+        0 => Down,
+        1 => Up,
+      },
+      5       => mie,      //= match interrupt enable
+      6       => wot,      //= wait on trigger
+      7       => snaps,    //= snap-shot mode
+      8       => ild,      //= interval load write
+      9       => pwmie,    //= PWM interrupt enable
+      10      => rsu,      //= match register update
+      11      => plo,      //= PWM legacy operation
+    }
     0x0C => reg32 ctl {
       0      => taen,      //= Timer A enable
       1      => tastall,   //= Timer A stall enable
@@ -205,17 +252,57 @@ pub mod reg {
 
       8      => tben,      //= Timer B enable
       9      => tbstall,   //= Timer B stall enable
-      10..11 => tbevent,   //= Timer B event mode
+      10..11 => tbevent {  //! Timer B event mode
+        0 => PosEdge,
+        1 => NegEdge,
+        3 => AnyEdge,
+      },
       13     => tbote,     //= Timer B output trigger enable
       14     => tbpwml,    //= Timer B PWM output level
     }
+    0x18 => reg32 imr {
+      2      => caeim,     //= Timer A capture event interrupt mask
+      10     => cbeim,     //= Timer B capture event interrupt mask
+    }
+    0x20 => reg32 mis {
+      2      => caemis: ro, //= Timer A capture event masked interrupt
+      10     => cbemis: ro, //= Timer B capture event masked interrupt
+    }
+    0x24 => reg32 icr {
+      2      => caecint,   //= Timer A capture event interrupt clear
+      10     => cbecint,   //= Timer B capture event interrupt clear
+    }
     0x28 => reg32 tailr {
       0..31 => tailr,      //= Timer A interval load
     }
+    0x2C => reg32 tbilr {
+      0..31 => tbilr,      //= Timer B interval load
+    }
+    0x30 => reg32 tamatchr {
+      0..31 => tamr,       //= Timer A match value
+    }
+    0x34 => reg32 tbmatchr {
+      0..31 => tbmr,       //= Timer B match value
+    }
     0x38 => reg32 apr {
       0..15 => psr,        //= Timer A prescale value
                            //= Only 8bit for 16/32bit timers
     }
+    0x3C => reg32 bpr {
+      0..15 => psr,        //= Timer B prescale value
+    }
+    0x40 => reg32 apmr {
+      0..15 => psmr,       //= Timer A prescale match value
+    }
+    0x44 => reg32 bpmr {
+      0..15 => psmr,       //= Timer B prescale match value
+    }
+    0x48 => reg32 tar {
+      0..31 => v: ro,      //= Timer A captured value
+    }
+    0x4C => reg32 tbr {
+      0..31 => v: ro,      //= Timer B captured value
+    }
     0x50 => reg32 tav {
       0..31 => v,          // Timer A counter value
     }
@@ -15,10 +15,13 @@
 
 //! UART configuration
 
+use core::option::Option::{Some, None};
+use core::result::Result::{self, Ok, Err};
+
 use hal::tiva_c::sysctl;
 use util::support::get_reg_ref;
 
-use drivers::chario::CharIO;
+use drivers::chario::{CharIO, CharInput, ReadError};
 use hal::uart;
 
 #[path="../../util/ioreg.rs"]
@@ -91,8 +94,8 @@ impl Uart {
       .set_uarten(false)
       // Enable TX
       .set_txe(true)
-      // Enable TX
-      .set_rxe(false)
+      // Enable RX
+      .set_rxe(true)
       // Disable High-Speed
       .set_hse(false);
 
@@ -136,6 +139,27 @@ impl CharIO for Uart {
   }
 }
 
+impl CharInput for Uart {
+  fn try_getc(&self) -> Result<Option<char>, ReadError> {
+    if self.regs.fr.rxfe() {
+      return Ok(None);
+    }
+
+    // Error bits are attached to the character they apply to, so the FIFO
+    // entry must be read exactly once.
+    let data = self.regs.data.get();
+    if data.fe() {
+      Err(ReadError::Framing)
+    } else if data.pe() {
+      Err(ReadError::Parity)
+    } else if data.oe() {
+      Err(ReadError::Overrun)
+    } else {
+      Ok(Some(data.data() as u8 as char))
+    }
+  }
+}
+
 pub mod reg {
   //! Uart registers definition
   use volatile_cell::VolatileCell;
@@ -143,7 +167,11 @@ pub mod reg {
 
   ioregs!(Uart = {
     0x00 => reg32 data {
-      0..11 => data,     //= RX/TX fifo data
+      0..7  => data,     //= RX/TX fifo data
+      8     => fe:   ro, //= UART framing error
+      9     => pe:   ro, //= UART parity error
+      10    => be:   ro, //= UART break error
+      11    => oe:   ro, //= UART overrun error
     }
     0x18 => reg32 fr {
       0     => ctx:  ro, //= clear-to-send signal is asserted
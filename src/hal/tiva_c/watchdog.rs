@@ -0,0 +1,167 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Watchdog timers.
+//!
+//! Watchdog 0 is clocked by the system clock and watchdog 1 by the 16MHz
+//! precision internal oscillator. A watchdog raises an interrupt on its first
+//! timeout and resets the MCU on the second one, so the counter is loaded with
+//! half of the requested timeout. Once started, a watchdog can't be stopped
+//! until the next reset.
+
+use core::intrinsics::abort;
+use core::option::Option::Some;
+
+use hal::tiva_c::sysctl;
+use hal::watchdog;
+use util::support::get_reg_ref;
+
+#[path="../../util/wait_for.rs"]
+#[macro_use] mod wait_for;
+
+/// Precision internal oscillator frequency, in ticks per millisecond.
+const PIOSC_TICKS_PER_MS: u32 = 16_000;
+
+/// Key unlocking the watchdog registers, any other value locks them.
+const UNLOCK_KEY: u32 = 0x1ACCE551;
+const LOCK_KEY: u32 = 0;
+
+/// There are 2 watchdog timers
+#[allow(missing_docs)]
+#[derive(Clone, Copy)]
+pub enum WatchdogId {
+  Watchdog0,
+  Watchdog1,
+}
+
+/// Structure describing a watchdog timer.
+#[derive(Clone, Copy)]
+pub struct Watchdog {
+  id: WatchdogId,
+  regs: &'static reg::Watchdog,
+}
+
+impl Watchdog {
+  /// Powers up watchdog `id`, which is left stopped if it wasn't running.
+  pub fn new(id: WatchdogId) -> Watchdog {
+    let (periph, regs) = match id {
+      WatchdogId::Watchdog0 =>
+        (sysctl::periph::watchdog::WATCHDOG_0, reg::WATCHDOG_0),
+      WatchdogId::Watchdog1 =>
+        (sysctl::periph::watchdog::WATCHDOG_1, reg::WATCHDOG_1),
+    };
+    periph.ensure_enabled();
+
+    Watchdog { id: id, regs: get_reg_ref(regs) }
+  }
+
+  /// Runs `f` with the registers unlocked.
+  fn unlocked<F: Fn()>(&self, f: F) {
+    self.sync();
+    self.regs.lock.set_key(UNLOCK_KEY);
+    f();
+    self.sync();
+    self.regs.lock.set_key(LOCK_KEY);
+  }
+
+  /// Waits for the previous write to complete. Writes to watchdog 1 cross
+  /// into its clock domain, and must be spaced.
+  fn sync(&self) {
+    match self.id {
+      WatchdogId::Watchdog0 => (),
+      WatchdogId::Watchdog1 => wait_for!(self.regs.ctl.wrc()),
+    }
+  }
+}
+
+impl watchdog::Watchdog for Watchdog {
+  fn start(&self, timeout_ms: u32) {
+    let ticks_per_ms = match self.id {
+      WatchdogId::Watchdog0 => sysctl::clock::sysclk_get() as u32 / 1000,
+      WatchdogId::Watchdog1 => PIOSC_TICKS_PER_MS,
+    };
+    let load = match timeout_ms.checked_mul(ticks_per_ms / 2) {
+      Some(load) if load > 0 => load,
+      _ => unsafe { abort() },
+    };
+
+    self.unlocked(|| {
+      self.regs.load.set_load(load);
+      self.sync();
+      self.regs.ctl.set_resen(true);
+      self.sync();
+      // Enabling the interrupt starts the counter.
+      self.regs.ctl.set_inten(true);
+    });
+  }
+
+  fn feed(&self) {
+    self.unlocked(|| {
+      // Clearing the interrupt reloads the counter.
+      self.regs.icr.set_clear(0);
+    });
+  }
+
+  fn caused_reset(&self) -> bool {
+    let sysctl = get_reg_ref(sysctl::reg::SYSCTL);
+    match self.id {
+      WatchdogId::Watchdog0 => sysctl.resc.wdt0(),
+      WatchdogId::Watchdog1 => sysctl.resc.wdt1(),
+    }
+  }
+
+  fn clear_reset_flags(&self) {
+    get_reg_ref(sysctl::reg::SYSCTL).resc.ignoring_state().set_wdt0(false);
+  }
+}
+
+pub mod reg {
+  //! Watchdog registers definition
+  use volatile_cell::VolatileCell;
+  use core::ops::Drop;
+
+  ioregs!(Watchdog = {
+    0x000 => reg32 load {
+      0..31 => load,       //= Load value
+    }
+    0x004 => reg32 value {
+      0..31 => value: ro,  //= Current value
+    }
+    0x008 => reg32 ctl {
+      0     => inten,      //= Interrupt enable, starts the counter
+      1     => resen,      //= Reset enable
+      2     => inttype,    //= Non-maskable interrupt
+      31    => wrc: ro,    //= Write complete
+    }
+    0x00C => reg32 icr {
+      0..31 => clear: wo,  //= Any write clears the interrupt and reloads
+    }
+    0x010 => reg32 ris {
+      0     => wdtris: ro, //= Raw interrupt status
+    }
+    0xC00 => reg32 lock {
+      0..31 => key,        //= Lock key
+    }
+  });
+
+  #[allow(missing_docs)]
+  mod instances {
+    use super::*;
+
+    pub const WATCHDOG_0: *const Watchdog = 0x40000000 as *const Watchdog;
+    pub const WATCHDOG_1: *const Watchdog = 0x40001000 as *const Watchdog;
+  }
+  pub use self::instances::*;
+}
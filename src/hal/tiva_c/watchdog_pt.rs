@@ -0,0 +1,64 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::rc::Rc;
+use syntax::ext::base::ExtCtxt;
+
+use builder::{Builder, TokenString};
+use node;
+
+pub fn attach(builder: &mut Builder, _: &mut ExtCtxt, node: Rc<node::Node>) {
+  node.materializer.set(Some(build_watchdog as fn(&mut Builder, &mut ExtCtxt, Rc<node::Node>)));
+  // Watchdog 0 counts system clock cycles.
+  super::add_node_dependency_on_clock(builder, &node);
+}
+
+fn build_watchdog(builder: &mut Builder, cx: &mut ExtCtxt,
+    node: Rc<node::Node>) {
+  if !node.expect_no_subnodes(cx) ||
+      !node.expect_attributes(cx, &[("timeout_ms", node::IntAttribute)]) {
+    return
+  }
+
+  let timeout_ms = node.get_int_attr("timeout_ms").unwrap() as u32;
+
+  // A named watchdog can be passed to tasks, which have to feed it.
+  match node.name {
+    Some(ref name) => {
+      let name = TokenString(name.clone());
+      node.set_type_name(
+          "zinc::hal::tiva_c::watchdog::Watchdog".to_string());
+
+      let st = quote_stmt!(&*cx,
+          let $name = zinc::hal::tiva_c::watchdog::Watchdog::new(
+              zinc::hal::tiva_c::watchdog::WatchdogId::Watchdog0);
+      ).unwrap();
+      builder.add_main_statement(st);
+      let st = quote_stmt!(&*cx,
+          zinc::hal::watchdog::Watchdog::start(&$name, $timeout_ms);
+      ).unwrap();
+      builder.add_main_statement(st);
+    },
+    None => {
+      let st = quote_stmt!(&*cx,
+          zinc::hal::watchdog::Watchdog::start(
+              &zinc::hal::tiva_c::watchdog::Watchdog::new(
+                  zinc::hal::tiva_c::watchdog::WatchdogId::Watchdog0),
+              $timeout_ms);
+      ).unwrap();
+      builder.add_main_statement(st);
+    },
+  }
+}
@@ -19,8 +19,16 @@ UART interface.
 UARTConf is a MCU-specific struct.
 
 UART objects implement CharIO trait to perform actual data transmission.
+
+Interrupt-driven UARTs keep their data in `Buffers`, which the driver shares
+with its ISR through `util::shared::Shared`.
 */
 
+use core::option::Option::{self, None};
+
+use drivers::chario::ReadError;
+use util::ring_buffer::RingBuffer;
+
 /// UART parity mode.
 #[derive(Clone, Copy)]
 pub enum Parity {
@@ -35,3 +43,24 @@ pub enum Parity {
   /// Partity bit forced to 0.
   Forced0,
 }
+
+/// Transmit and receive queues of an interrupt-driven UART.
+pub struct Buffers {
+  /// Characters waiting to be sent.
+  pub tx: RingBuffer,
+  /// Characters received but not yet read.
+  pub rx: RingBuffer,
+  /// Receive error to report on the next read.
+  pub error: Option<ReadError>,
+}
+
+impl Buffers {
+  /// Creates empty buffers.
+  pub const fn new() -> Buffers {
+    Buffers {
+      tx: RingBuffer::new(),
+      rx: RingBuffer::new(),
+      error: None,
+    }
+  }
+}
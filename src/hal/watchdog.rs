@@ -0,0 +1,40 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/*!
+Watchdog interface.
+
+Watchdogs are MCU-specific structs that implement the `Watchdog` trait. Once
+started, a watchdog resets the MCU unless it's fed before the timeout expires.
+Most watchdogs can't be stopped until the next reset.
+*/
+
+/// Watchdog trait.
+pub trait Watchdog {
+  /// Starts the watchdog, resetting the MCU if it isn't fed for `timeout_ms`.
+  ///
+  /// Aborts if the timeout isn't reachable by the hardware.
+  fn start(&self, timeout_ms: u32);
+
+  /// Restarts the timeout.
+  fn feed(&self);
+
+  /// Returns true if the watchdog caused a reset since the reset flags were
+  /// last cleared.
+  fn caused_reset(&self) -> bool;
+
+  /// Clears the reset flags, so that the cause of the next reset can be told.
+  fn clear_reset_flags(&self);
+}
@@ -19,6 +19,7 @@
 #![feature(const_fn)]
 #![deny(missing_docs)]
 #![no_std]
+#![recursion_limit="192"]
 
 /*!
 Zinc is an embedded stack for rust.
@@ -0,0 +1,291 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Message queues
+//!
+//! A `Channel` passes `Copy` messages from any number of senders, including
+//! ISRs, to a single receiving task. Channels have a fixed capacity and can be
+//! allocated statically:
+//!
+//! ```ignore
+//! static EVENTS: Channel<u8> = Channel::new();
+//! ```
+
+pub use os::channel::internal::Channel;
+
+use core::option::Option::{self, Some, None};
+use core::result::Result::{self, Ok, Err};
+
+/// Number of messages a `Channel` can hold.
+pub const CAPACITY: usize = 16;
+
+/// Message storage shared by both channel implementations.
+struct Buffer<T> {
+  items: [Option<T>; CAPACITY],
+  head: usize,
+  len: usize,
+}
+
+impl<T: Copy> Buffer<T> {
+  const fn new() -> Buffer<T> {
+    Buffer {
+      items: [None; CAPACITY],
+      head: 0,
+      len: 0,
+    }
+  }
+}
+
+impl<T> Buffer<T> {
+  fn push(&mut self, msg: T) -> Result<(), T> {
+    if self.len == CAPACITY {
+      return Err(msg);
+    }
+    self.items[(self.head + self.len) % CAPACITY] = Some(msg);
+    self.len += 1;
+    Ok(())
+  }
+
+  fn pop(&mut self) -> Option<T> {
+    if self.len == 0 {
+      return None;
+    }
+    let msg = self.items[self.head].take();
+    self.head = (self.head + 1) % CAPACITY;
+    self.len -= 1;
+    msg
+  }
+}
+
+#[cfg(feature = "multitasking")]
+mod internal {
+  use core::cell::UnsafeCell;
+  use core::marker::{Sync, Send};
+  use core::option::Option::{self, Some, None};
+  use core::result::Result::{self, Ok, Err};
+
+  use hal::cortex_m3::irq::NoInterrupts;
+  use os::task::{TaskDescriptor, Tasks};
+  use util::queue::{Queue, Node};
+  use super::Buffer;
+
+  /// A multiple producer, single consumer message queue
+  pub struct Channel<T> {
+    buffer: UnsafeCell<Buffer<T>>,
+    receiver: UnsafeCell<Option<*mut TaskDescriptor>>,
+    senders: Queue<*mut TaskDescriptor>,
+  }
+
+  impl<T: Copy> Channel<T> {
+    /// Create a new, empty channel
+    pub const fn new() -> Channel<T> {
+      Channel {
+        buffer: UnsafeCell::new(Buffer::new()),
+        receiver: UnsafeCell::new(None),
+        senders: Queue::new(),
+      }
+    }
+  }
+
+  impl<T> Channel<T> {
+    /// Send a message, blocking while the channel is full
+    pub fn send(&self, msg: T) {
+      let mut msg = msg;
+      loop {
+        /*
+         * A sender that finds the channel full queues itself and blocks.
+         * The receiver pops it off the queue when it takes a message out, so
+         * there's nothing to clean up once we are woken up.
+         */
+        unsafe {
+          let crit = NoInterrupts::new();
+          match self.push(msg, &crit) {
+            Ok(()) => return,
+            Err(m) => msg = m,
+          }
+          let mut waiting = Node::new(Tasks.current_task() as *mut TaskDescriptor);
+          self.senders.push(&mut waiting, &crit);
+          Tasks.current_task().block(crit);
+        }
+      }
+    }
+
+    /// Try to send a message, handing it back if the channel is full
+    ///
+    /// This never blocks and can be called from interrupt context.
+    pub fn try_send(&self, msg: T) -> Result<(), T> {
+      let crit = NoInterrupts::new();
+      unsafe { self.push(msg, &crit) }
+    }
+
+    /// Receive a message, blocking while the channel is empty
+    ///
+    /// Only one task may receive from a channel.
+    pub fn recv(&self) -> T {
+      loop {
+        unsafe {
+          let crit = NoInterrupts::new();
+          match self.pop(&crit) {
+            Some(msg) => return msg,
+            None => (),
+          }
+          *self.receiver.get() = Some(Tasks.current_task() as *mut TaskDescriptor);
+          Tasks.current_task().block(crit);
+        }
+      }
+    }
+
+    /// Receive a message if there is one
+    pub fn try_recv(&self) -> Option<T> {
+      let crit = NoInterrupts::new();
+      unsafe { self.pop(&crit) }
+    }
+
+    unsafe fn push(&self, msg: T, crit: &NoInterrupts) -> Result<(), T> {
+      try!((*self.buffer.get()).push(msg));
+      match (*self.receiver.get()).take() {
+        Some(task) => (*task).unblock(crit),
+        None => (),
+      }
+      Ok(())
+    }
+
+    unsafe fn pop(&self, crit: &NoInterrupts) -> Option<T> {
+      let msg = (*self.buffer.get()).pop();
+      if msg.is_some() {
+        match self.senders.pop(crit) {
+          Some(task) => (*(*task).data).unblock(crit),
+          None => (),
+        }
+      }
+      msg
+    }
+  }
+
+  unsafe impl<T: Send> Sync for Channel<T> {}
+}
+
+#[cfg(not(feature = "multitasking"))]
+mod internal {
+  use core::marker::{Sync, Send};
+  use core::option::Option::{self, Some, None};
+  use core::result::Result::{self, Ok, Err};
+
+  use util::shared::{Shared, NoInterrupts};
+  use util::support::wfi;
+  use super::Buffer;
+
+  /// A multiple producer, single consumer message queue
+  ///
+  /// Without multitasking, blocking calls sleep until an interrupt changes
+  /// the state of the channel.
+  pub struct Channel<T> {
+    buffer: Shared<Buffer<T>>,
+  }
+
+  impl<T: Copy> Channel<T> {
+    /// Create a new, empty channel
+    pub const fn new() -> Channel<T> {
+      Channel {
+        buffer: Shared::new(Buffer::new()),
+      }
+    }
+  }
+
+  impl<T> Channel<T> {
+    /// Send a message, blocking while the channel is full
+    pub fn send(&self, msg: T) {
+      let mut msg = msg;
+      loop {
+        match self.try_send(msg) {
+          Ok(()) => return,
+          Err(m) => msg = m,
+        }
+        wfi();
+      }
+    }
+
+    /// Try to send a message, handing it back if the channel is full
+    ///
+    /// This never blocks and can be called from interrupt context.
+    pub fn try_send(&self, msg: T) -> Result<(), T> {
+      let crit = NoInterrupts::new();
+      self.buffer.borrow(&crit).push(msg)
+    }
+
+    /// Receive a message, blocking while the channel is empty
+    pub fn recv(&self) -> T {
+      loop {
+        match self.try_recv() {
+          Some(msg) => return msg,
+          None => wfi(),
+        }
+      }
+    }
+
+    /// Receive a message if there is one
+    pub fn try_recv(&self) -> Option<T> {
+      let crit = NoInterrupts::new();
+      self.buffer.borrow(&crit).pop()
+    }
+  }
+
+  unsafe impl<T: Send> Sync for Channel<T> {}
+}
+
+#[cfg(test)]
+mod test {
+  use core::option::Option::{Some, None};
+  use core::result::Result::{Ok, Err};
+  use super::{Buffer, CAPACITY};
+
+  #[test]
+  fn pops_in_push_order() {
+    let mut buffer = Buffer::new();
+    assert!(buffer.push(1u8) == Ok(()));
+    assert!(buffer.push(2) == Ok(()));
+    assert!(buffer.pop() == Some(1));
+    assert!(buffer.pop() == Some(2));
+    assert!(buffer.pop() == None);
+  }
+
+  #[test]
+  fn hands_message_back_when_full() {
+    let mut buffer = Buffer::new();
+    for i in 0..CAPACITY {
+      assert!(buffer.push(i as u8) == Ok(()));
+    }
+    assert!(buffer.push(0xff) == Err(0xff));
+    assert!(buffer.pop() == Some(0));
+    assert!(buffer.push(0xff) == Ok(()));
+  }
+
+  #[test]
+  fn wraps_around() {
+    let mut buffer = Buffer::new();
+    // Moves the head off the start of the storage.
+    for i in 0..CAPACITY / 2 + 1 {
+      assert!(buffer.push(i) == Ok(()));
+      assert!(buffer.pop() == Some(i));
+    }
+    for i in 0..CAPACITY {
+      assert!(buffer.push(i) == Ok(()));
+    }
+    for i in 0..CAPACITY {
+      assert!(buffer.pop() == Some(i));
+    }
+    assert!(buffer.pop() == None);
+  }
+}
@@ -0,0 +1,225 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Event flag groups
+//!
+//! A group holds 32 flags. Tasks wait for any or all of a set of flags to be
+//! raised; flags stay raised until they are explicitly cleared.
+
+pub use os::event_flags::internal::{EventFlags, EVENT_FLAGS_INIT};
+
+#[cfg(feature = "multitasking")]
+mod internal {
+  use core::cell::UnsafeCell;
+  use core::marker::Sync;
+  use core::option::Option::{Some, None};
+
+  use hal::cortex_m3::irq::NoInterrupts;
+  use os::task::{TaskDescriptor, Tasks};
+  use util::queue::{Queue, Node};
+
+  struct Waiter {
+    task: *mut TaskDescriptor,
+    mask: u32,
+    all: bool,
+    /// Flags that satisfied the wait, filled in by `set`.
+    matched: u32,
+  }
+
+  /// A group of event flags
+  pub struct EventFlags {
+    flags: UnsafeCell<u32>,
+    waiting: Queue<Waiter>,
+  }
+
+  /// Static initializer, all flags are cleared
+  pub const EVENT_FLAGS_INIT: EventFlags = EventFlags::new();
+
+  impl EventFlags {
+    /// Create a new group with all flags cleared
+    pub const fn new() -> EventFlags {
+      EventFlags {
+        flags: UnsafeCell::new(0),
+        waiting: Queue::new(),
+      }
+    }
+
+    /// Wait until any flag in `mask` is raised, returns the raised ones
+    pub fn wait_any(&self, mask: u32) -> u32 {
+      self.wait(mask, false)
+    }
+
+    /// Wait until all flags in `mask` are raised, returns `mask`
+    pub fn wait_all(&self, mask: u32) -> u32 {
+      self.wait(mask, true)
+    }
+
+    fn wait(&self, mask: u32, all: bool) -> u32 {
+      unsafe {
+        let crit = NoInterrupts::new();
+        let current = *self.flags.get();
+        if satisfied(current, mask, all) {
+          return current & mask;
+        }
+        let mut waiting = Node::new(Waiter {
+          task: Tasks.current_task() as *mut TaskDescriptor,
+          mask: mask,
+          all: all,
+          matched: 0,
+        });
+        self.waiting.push(&mut waiting, &crit);
+        Tasks.current_task().block(crit);
+
+        // `set` removed us from the waiting list before waking us up.
+        waiting.data.matched
+      }
+    }
+
+    /// Raise `bits`, waking up every task whose wait is now satisfied
+    ///
+    /// This can be called from interrupt context.
+    pub fn set(&self, bits: u32) {
+      unsafe {
+        let crit = NoInterrupts::new();
+        *self.flags.get() |= bits;
+        let current = *self.flags.get();
+
+        // Take every waiter off the list and put back the ones that still
+        // have to wait, which keeps them in order.
+        let mut remaining = Queue::new();
+        loop {
+          match self.waiting.pop(&crit) {
+            None => break,
+            Some(node) => {
+              let waiter = &mut (*node).data;
+              if satisfied(current, waiter.mask, waiter.all) {
+                waiter.matched = current & waiter.mask;
+                (*waiter.task).unblock(&crit);
+              } else {
+                remaining.push(node, &crit);
+              }
+            }
+          }
+        }
+        loop {
+          match remaining.pop(&crit) {
+            None => break,
+            Some(node) => self.waiting.push(node, &crit),
+          }
+        }
+      }
+    }
+
+    /// Lower `bits`
+    pub fn clear(&self, bits: u32) {
+      unsafe {
+        let _crit = NoInterrupts::new();
+        *self.flags.get() &= !bits;
+      }
+    }
+
+    /// Currently raised flags
+    pub fn get(&self) -> u32 {
+      unsafe { *self.flags.get() }
+    }
+  }
+
+  fn satisfied(flags: u32, mask: u32, all: bool) -> bool {
+    if all {
+      flags & mask == mask
+    } else {
+      flags & mask != 0
+    }
+  }
+
+  unsafe impl Sync for EventFlags {}
+}
+
+#[cfg(not(feature = "multitasking"))]
+mod internal {
+  use core::marker::Sync;
+
+  use util::shared::{Shared, NoInterrupts};
+  use util::support::wfi;
+
+  /// A group of event flags
+  pub struct EventFlags {
+    flags: Shared<u32>,
+  }
+
+  /// Static initializer, all flags are cleared
+  pub const EVENT_FLAGS_INIT: EventFlags = EventFlags::new();
+
+  impl EventFlags {
+    /// Create a new group with all flags cleared
+    pub const fn new() -> EventFlags {
+      EventFlags { flags: Shared::new(0) }
+    }
+
+    /// Wait until any flag in `mask` is raised, returns the raised ones
+    pub fn wait_any(&self, mask: u32) -> u32 {
+      self.wait(mask, false)
+    }
+
+    /// Wait until all flags in `mask` are raised, returns `mask`
+    pub fn wait_all(&self, mask: u32) -> u32 {
+      self.wait(mask, true)
+    }
+
+    fn wait(&self, mask: u32, all: bool) -> u32 {
+      loop {
+        // WFI wakes up on pending interrupts even while they are masked, so
+        // a `set` can't slip in between the check and the sleep.
+        let crit = NoInterrupts::new();
+        let current = *self.flags.borrow(&crit);
+        if satisfied(current, mask, all) {
+          return current & mask;
+        }
+        wfi();
+      }
+    }
+
+    /// Raise `bits`
+    ///
+    /// This can be called from interrupt context.
+    pub fn set(&self, bits: u32) {
+      let crit = NoInterrupts::new();
+      *self.flags.borrow(&crit) |= bits;
+    }
+
+    /// Lower `bits`
+    pub fn clear(&self, bits: u32) {
+      let crit = NoInterrupts::new();
+      *self.flags.borrow(&crit) &= !bits;
+    }
+
+    /// Currently raised flags
+    pub fn get(&self) -> u32 {
+      let crit = NoInterrupts::new();
+      let flags = *self.flags.borrow(&crit);
+      flags
+    }
+  }
+
+  fn satisfied(flags: u32, mask: u32, all: bool) -> bool {
+    if all {
+      flags & mask == mask
+    } else {
+      flags & mask != 0
+    }
+  }
+
+  unsafe impl Sync for EventFlags {}
+}
@@ -25,4 +25,7 @@ pub mod syscall;
 #[cfg(feature = "multitasking")] pub mod task;
 pub mod mutex;
 pub mod cond_var;
+pub mod channel;
+pub mod semaphore;
+pub mod event_flags;
 pub mod debug;
@@ -17,31 +17,38 @@
 
 pub use os::mutex::internal::{MUTEX_INIT, Mutex, Guard};
 
-#[cfg(multitasking)]
+#[cfg(feature = "multitasking")]
 mod internal {
-  use core::marker;
-  use core::ty::Unsafe;
-  use core::kinds::Sync;
-  use core::option::Option::{None, Some};
+  use core::cell::UnsafeCell;
+  use core::marker::Sync;
+  use core::option::Option::{self, None, Some};
   use core::ops::Drop;
+  use core::intrinsics::abort;
 
   use hal::cortex_m3::irq::NoInterrupts;
-  use os::task::{TaskDescriptor, Tasks};
+  use os::task::{self, TaskDescriptor, Tasks, Priority};
   use util::queue::{Queue, Node};
 
   /// A mutex
+  ///
+  /// A task waiting on the mutex lends its priority to the owner, if it's
+  /// higher, until the mutex is unlocked. The owner runs at the highest
+  /// priority of its own and of the tasks waiting on any mutex it holds.
+  ///
+  /// An unlocked mutex is handed over to the highest priority waiting task,
+  /// in the order they started waiting if several have the same priority.
   pub struct Mutex {
     owner: UnsafeCell<Option<*mut TaskDescriptor>>,
+    /// Next mutex held by the same owner.
+    next_held: UnsafeCell<*const Mutex>,
     waiting: Queue<*mut TaskDescriptor>
   }
 
   /// Static initializer
   pub const MUTEX_INIT: Mutex = Mutex {
     owner: UnsafeCell::new(None),
-    waiting: Queue {
-      head: UnsafeCell::new(0 as *mut Node<*mut TaskDescriptor>),
-      tail: UnsafeCell::new(0 as *mut Node<*mut TaskDescriptor>),
-    }
+    next_held: UnsafeCell::new(0 as *const Mutex),
+    waiting: Queue::new(),
   };
 
   /// A mutex lock
@@ -53,56 +60,29 @@ mod internal {
   impl Mutex {
     /// Create a new mutex
     pub fn new() -> Mutex {
-      Mutex {
-        owner: UnsafeCell::new(None),
-        waiting: Queue::new(),
-      }
+      MUTEX_INIT
     }
 
     /// Take a mutex lock
     pub fn lock<'a>(&'a self) -> Guard<'a> {
-      /*
-       * This is a bit subtle: We need to add ourselves to the mutex's
-       * waiting list. To do this we allocate a list item on the local
-       * stack, append it to the waiting list, and block. When the task
-       * before us unlocks the mutex, they will wake us up. Finally, when
-       * we are executing again we remove our entry from the list.
-       */
-      unsafe {
-        // we need the critical section until the end of this function
-        let _crit = match *self.owner.get() {
-          None    => NoInterrupts::new(),
-          Some(_) => {
-            let crit = NoInterrupts::new();
-            let mut waiting = Node::new(Tasks.current_task() as *mut TaskDescriptor);
-            self.waiting.push(&mut waiting, &crit);
-            Tasks.current_task().block(crit); // drops crit
-
-            /*
-             * Note that there is a small window here between being
-             * awoken by the unlocking thread and reentering a critical
-             * section. An interrupt could fire within this window but
-             * since the unlocking thread retains ownership of the mutex
-             * there is no risk of a third-party sneaking in.
-             */
-            let crit = NoInterrupts::new();
-            self.waiting.pop(&crit);
-            crit
-          }
-        };
-
-        *self.owner.get() = Some(Tasks.current_task() as *mut TaskDescriptor);
-        Guard { mutex: self }
+      match self.acquire(None) {
+        Some(guard) => guard,
+        None => unsafe { abort() },
       }
     }
 
+    /// Take a mutex lock, waiting at most `ms` milliseconds for it
+    pub fn lock_timeout<'a>(&'a self, ms: u32) -> Option<Guard<'a>> {
+      self.acquire(Some(task::ticks().wrapping_add(ms)))
+    }
+
     /// Try to take a mutex lock, returning `None` is unsuccessful
     pub fn try_lock<'a>(&'a self) -> Option<Guard<'a>> {
       unsafe {
+        let _crit = NoInterrupts::new();
         match *self.owner.get() {
           None => {
-            let _crit = NoInterrupts::new();
-            *self.owner.get() = Some(Tasks.current_task() as *mut TaskDescriptor);
+            self.set_owner(Tasks.current_task() as *mut TaskDescriptor);
             Some(Guard { mutex: self })
           }
           _ => None
@@ -111,24 +91,143 @@ mod internal {
     }
 
     /*
-     * Here we release ownership of the mutex only if there is no one
-     * waiting on it. Otherwise we retain to ensure there is no race
-     * between waking up the waiting thread and it claiming ownership.
+     * This is a bit subtle: We need to add ourselves to the mutex's
+     * waiting list. To do this we allocate a list item on the local
+     * stack, append it to the waiting list, and block. When the task
+     * before us unlocks the mutex, it takes us off the list and makes us the
+     * owner before waking us up. If we wake up without owning the mutex, our
+     * deadline has passed and we take ourselves off the list.
+     */
+    fn acquire<'a>(&'a self, deadline: Option<u32>) -> Option<Guard<'a>> {
+      unsafe {
+        let crit = NoInterrupts::new();
+        let current = Tasks.current_task() as *mut TaskDescriptor;
+        if (*self.owner.get()).is_none() {
+          self.set_owner(current);
+          return Some(Guard { mutex: self });
+        }
+
+        let mut waiting = Node::new(current);
+        self.waiting.push(&mut waiting, &crit);
+        self.inherit_priority(&crit);
+        match deadline {
+          None => (*current).block(crit), // drops crit
+          Some(deadline) => (*current).sleep(deadline, crit),
+        }
+
+        let crit = NoInterrupts::new();
+        if *self.owner.get() == Some(current) {
+          Some(Guard { mutex: self })
+        } else {
+          self.waiting.remove(&mut waiting, &crit);
+          self.inherit_priority(&crit);
+          None
+        }
+      }
+    }
+
+    /// Makes `task` the owner, adding the mutex to the ones it holds.
+    unsafe fn set_owner(&self, task: *mut TaskDescriptor) {
+      *self.owner.get() = Some(task);
+      *self.next_held.get() = (*task).held_mutexes;
+      (*task).held_mutexes = self as *const Mutex;
+    }
+
+    /// Removes the mutex from the ones held by `task`.
+    unsafe fn release(&self, task: *mut TaskDescriptor) {
+      let this = self as *const Mutex;
+      let mut link: *mut *const Mutex = &mut (*task).held_mutexes;
+      while !(*link).is_null() {
+        if *link == this {
+          *link = *self.next_held.get();
+          break;
+        }
+        link = (**link).next_held.get();
+      }
+      *self.next_held.get() = 0 as *const Mutex;
+    }
+
+    /// Returns the highest priority of the waiting tasks, if any.
+    unsafe fn waiting_priority(&self) -> Option<Priority> {
+      let mut priority = None;
+      let mut node = *self.waiting.head.get();
+      while !node.is_null() {
+        let waiter = (*node).data;
+        priority = match priority {
+          Some(p) if p >= (*waiter).priority => Some(p),
+          _ => Some((*waiter).priority),
+        };
+        node = *(*node).next.get();
+      }
+      priority
+    }
+
+    /// Returns the first waiting task with the highest priority.
+    unsafe fn highest_waiter(&self) -> Option<*mut Node<*mut TaskDescriptor>> {
+      let mut highest: Option<*mut Node<*mut TaskDescriptor>> = None;
+      let mut node = *self.waiting.head.get();
+      while !node.is_null() {
+        highest = match highest {
+          Some(h) if (*(*h).data).priority >= (*(*node).data).priority =>
+              Some(h),
+          _ => Some(node),
+        };
+        node = *(*node).next.get();
+      }
+      highest
+    }
+
+    /// Updates the owner priority after the waiting tasks changed.
+    unsafe fn inherit_priority(&self, crit: &NoInterrupts) {
+      match *self.owner.get() {
+        Some(owner) => update_priority(owner, crit),
+        None => (),
+      }
+    }
+
+    /*
+     * Ownership is handed over to the waiting task directly, so there is no
+     * race between waking it up and it claiming the mutex.
      */
     fn unlock(&self) {
       unsafe {
         let crit = NoInterrupts::new();
-        match self.waiting.peek() {
+        let owner = match *self.owner.get() {
+          Some(owner) => owner,
+          None => return,
+        };
+        self.release(owner);
+        update_priority(owner, &crit);
+
+        match self.highest_waiter() {
           None => *self.owner.get() = None,
-          Some(nextTask) => {
-            let mut task = *(*nextTask).data;
-            task.unblock(&crit);
+          Some(node) => {
+            self.waiting.remove(node, &crit);
+            let next = (*node).data;
+            self.set_owner(next);
+            update_priority(next, &crit);
+            (*next).unblock(&crit);
           }
         }
       }
     }
   }
 
+  /// Sets the priority of `task` to the highest of its base priority and of
+  /// the tasks waiting on the mutexes it holds.
+  unsafe fn update_priority(task: *mut TaskDescriptor, crit: &NoInterrupts) {
+    let mut priority = (*task).base_priority;
+    let mut mutex = (*task).held_mutexes;
+    while !mutex.is_null() {
+      match (*mutex).waiting_priority() {
+        Some(p) if p > priority => priority = p,
+        _ => (),
+      }
+      mutex = *(*mutex).next_held.get();
+    }
+    (*task).set_priority(priority, crit);
+  }
+
   impl<'a> Drop for Guard<'a> {
     #[inline]
     fn drop(&mut self) {
@@ -136,10 +235,10 @@ mod internal {
     }
   }
 
-  impl Sync for Mutex { }
+  unsafe impl Sync for Mutex { }
 }
 
-#[cfg(not(multitasking))]
+#[cfg(not(feature = "multitasking"))]
 mod internal {
   use core::marker::Sync;
   use core::option::Option::{self, None, Some};
@@ -180,6 +279,14 @@ mod internal {
       }
     }
 
+    /// Take a mutex lock, waiting at most `ms` milliseconds for it
+    ///
+    /// Without multitasking nothing can unlock the mutex while we wait, so
+    /// this is the same as `try_lock`.
+    pub fn lock_timeout<'a>(&'a self, _ms: u32) -> Option<Guard<'a>> {
+      self.try_lock()
+    }
+
     /// Try to take a mutex lock, returning `None` is unsuccessful
     pub fn try_lock<'a>(&'a self) -> Option<Guard<'a>> {
       unsafe {
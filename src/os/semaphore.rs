@@ -0,0 +1,169 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Counting semaphores
+
+pub use os::semaphore::internal::{Semaphore, SEMAPHORE_INIT};
+
+#[cfg(feature = "multitasking")]
+mod internal {
+  use core::cell::UnsafeCell;
+  use core::marker::Sync;
+  use core::option::Option::{Some, None};
+
+  use hal::cortex_m3::irq::NoInterrupts;
+  use os::task::{TaskDescriptor, Tasks};
+  use util::queue::{Queue, Node};
+
+  /// A counting semaphore
+  pub struct Semaphore {
+    count: UnsafeCell<usize>,
+    waiting: Queue<*mut TaskDescriptor>,
+  }
+
+  /// Static initializer, the semaphore has no units available
+  pub const SEMAPHORE_INIT: Semaphore = Semaphore::new(0);
+
+  impl Semaphore {
+    /// Create a new semaphore with `count` available units
+    pub const fn new(count: usize) -> Semaphore {
+      Semaphore {
+        count: UnsafeCell::new(count),
+        waiting: Queue::new(),
+      }
+    }
+
+    /// Take a unit, blocking until one is available
+    pub fn take(&self) {
+      /*
+       * `give` hands its unit directly to the first waiting task and pops it
+       * off the waiting list, so there is nothing left to do once we are
+       * woken up.
+       */
+      unsafe {
+        let crit = NoInterrupts::new();
+        if *self.count.get() > 0 {
+          *self.count.get() -= 1;
+          return;
+        }
+        let mut waiting = Node::new(Tasks.current_task() as *mut TaskDescriptor);
+        self.waiting.push(&mut waiting, &crit);
+        Tasks.current_task().block(crit);
+      }
+    }
+
+    /// Try to take a unit, returning false if none is available
+    pub fn try_take(&self) -> bool {
+      unsafe {
+        let _crit = NoInterrupts::new();
+        if *self.count.get() > 0 {
+          *self.count.get() -= 1;
+          true
+        } else {
+          false
+        }
+      }
+    }
+
+    /// Return a unit, waking up a waiting task if there is one
+    ///
+    /// This can be called from interrupt context.
+    pub fn give(&self) {
+      unsafe {
+        let crit = NoInterrupts::new();
+        match self.waiting.pop(&crit) {
+          Some(task) => (*(*task).data).unblock(&crit),
+          None => *self.count.get() += 1,
+        }
+      }
+    }
+
+    /// Number of available units
+    pub fn count(&self) -> usize {
+      unsafe { *self.count.get() }
+    }
+  }
+
+  unsafe impl Sync for Semaphore {}
+}
+
+#[cfg(not(feature = "multitasking"))]
+mod internal {
+  use core::marker::Sync;
+
+  use util::shared::{Shared, NoInterrupts};
+  use util::support::wfi;
+
+  /// A counting semaphore
+  pub struct Semaphore {
+    count: Shared<usize>,
+  }
+
+  /// Static initializer, the semaphore has no units available
+  pub const SEMAPHORE_INIT: Semaphore = Semaphore::new(0);
+
+  impl Semaphore {
+    /// Create a new semaphore with `count` available units
+    pub const fn new(count: usize) -> Semaphore {
+      Semaphore { count: Shared::new(count) }
+    }
+
+    /// Take a unit, sleeping until an interrupt gives one
+    pub fn take(&self) {
+      loop {
+        // WFI wakes up on pending interrupts even while they are masked, so
+        // a `give` can't slip in between the check and the sleep.
+        let crit = NoInterrupts::new();
+        if self.take_locked(&crit) {
+          return;
+        }
+        wfi();
+      }
+    }
+
+    /// Try to take a unit, returning false if none is available
+    pub fn try_take(&self) -> bool {
+      let crit = NoInterrupts::new();
+      self.take_locked(&crit)
+    }
+
+    /// Return a unit
+    ///
+    /// This can be called from interrupt context.
+    pub fn give(&self) {
+      let crit = NoInterrupts::new();
+      *self.count.borrow(&crit) += 1;
+    }
+
+    /// Number of available units
+    pub fn count(&self) -> usize {
+      let crit = NoInterrupts::new();
+      let count = *self.count.borrow(&crit);
+      count
+    }
+
+    fn take_locked(&self, crit: &NoInterrupts) -> bool {
+      let mut count = self.count.borrow(crit);
+      if *count > 0 {
+        *count -= 1;
+        true
+      } else {
+        false
+      }
+    }
+  }
+
+  unsafe impl Sync for Semaphore {}
+}
@@ -14,18 +14,88 @@
 // limitations under the License.
 
 //! Basic multitasking interface.
+//!
+//! Tasks live in a fixed table of `TaskDescriptor` slots. The table defaults
+//! to `DEFAULT_MAX_TASKS` slots and can be replaced by a bigger static array
+//! with `init`. One slot is always taken by the idle task.
+//!
+//! The scheduler runs the highest priority runnable task, tasks of the same
+//! priority are switched round-robin every `TIME_SLICE_TICKS` ticks. Defining
+//! or unblocking a task with a higher priority than the running one preempts
+//! it via PendSV.
+//!
+//! A task that returns from its function is killed and its slot can be reused
+//! by a later `define_task`, the task stack is not reclaimed though.
+//!
+//! SysTick fires every millisecond and counts `ticks`; tasks of equal priority
+//! are switched every `TIME_SLICE_TICKS` ticks. Tasks can sleep with
+//! `sleep_ms` and `sleep_until`, sleeping tasks are skipped by the scheduler
+//! until the tick handler finds their deadline passed. The task table is only
+//! scanned for them once the earliest deadline is reached.
+//!
+//! On cores with an MPU, a no-access guard region is programmed below the
+//! stack of the running task on every context switch, so that a stack
+//! overflow faults instead of corrupting the stack of the next task.
 
 use core::mem::size_of;
 use core::intrinsics::abort;
+use core::option::Option::{self, Some, None};
+use core::slice;
 
-use hal::cortex_m3::{sched, systick};
-use hal::cortex_m3::sched::NoInterrupts;
+use hal::cortex_m3::{mpu, sched, systick};
+use hal::cortex_m3::irq::NoInterrupts;
+use os::mutex::Mutex;
 use os::syscall::syscall;
 use hal::stack;
+use util::support::wfi;
 
 /// Task takes one argument, which is u32.
 pub type Task = fn(u32);
 
+/// Task priority, tasks with higher values preempt lower ones.
+pub type Priority = u8;
+
+/// Priority of the idle task, which runs when nothing else is runnable.
+pub const IDLE_PRIORITY: Priority = 0;
+
+/// Priority of the task started by `setup`.
+pub const DEFAULT_PRIORITY: Priority = 1;
+
+/// Number of task slots available if `init` is not called.
+pub const DEFAULT_MAX_TASKS: usize = 4;
+
+/// Ticks a task runs before the scheduler switches to the next task of the
+/// same priority.
+pub const TIME_SLICE_TICKS: u32 = 10;
+
+/// An unused task slot, to initialize task tables with.
+pub const TASK_DESCRIPTOR_INIT: TaskDescriptor = TaskDescriptor {
+  stack_start: 0,
+  stack_end: 0,
+  status: Status::Runnable,
+  priority: IDLE_PRIORITY,
+  base_priority: IDLE_PRIORITY,
+  wake_at: 0,
+  held_mutexes: 0 as *const Mutex,
+};
+
+/// Stack size of the idle task.
+const IdleStackSize: u32 = 64;
+
+/// SysTick reload value for 1ms used if the core has no 10ms calibration
+/// value.
+const DefaultTickReload: u32 = 48000;
+
+/// Size of registers saved by PendSV in addition to the hardware frame.
+const SoftwareSavedSize: u32 = 8*4;
+
+/// Bytes below `stack_end` left for the `__morestack` failure path, which
+/// must stay accessible.
+const MorestackReserve: u32 = 3*8*4;
+
+/// Size of the stack guard region, the smallest the MPU supports.
+const StackGuardSize: u32 = 32;
+
 mod current_stack_offset {
   /// Currently allocated stack memory, growing down, starting at __STACK_BASE.
   static mut CurrentStackOffset: u32 = 0;
@@ -39,174 +109,431 @@ mod current_stack_offset {
   }
 }
 
-/// Bytes to reserve in privileged stack based on stack size at the time of task::setup() call.
+/// Bytes to reserve in privileged stack based on stack size at the time the
+/// first task is defined.
 static ReservedPivilegedStackSize: u32 = 256;
 
-/// Maximum number of tasks.
-static MaxTasksCount: usize = 4;
+/// SysTick ticks since the scheduler was started.
+static mut Ticks: u32 = 0;
 
-mod defined_tasks_count {
-  use core::intrinsics::abort;
+/// Ticks since the running task was switched to.
+static mut SliceTicks: u32 = 0;
 
-  /// Total defined tasks count.
-  static mut DefinedTasksCount: usize = 0;
+/// Earliest deadline of the sleeping tasks, if any.
+static mut NextWakeup: Option<u32> = None;
 
-  pub fn get() -> usize {
-    unsafe { DefinedTasksCount }
-  }
-
-  pub fn increase() {
-    unsafe {
-      DefinedTasksCount += 1;
-      if DefinedTasksCount > super::MaxTasksCount {
-        abort();
-      }
-    }
-  }
-}
+static mut DefaultTasksTable: [TaskDescriptor; DEFAULT_MAX_TASKS] =
+    [TASK_DESCRIPTOR_INIT; DEFAULT_MAX_TASKS];
 
+/// Task scheduling status.
+#[derive(Clone, Copy, PartialEq)]
 pub enum Status {
+  /// Task can be scheduled.
   Runnable,
-  Blocked
+  /// Task waits for a wake-up from another task or an ISR.
+  Blocked,
+  /// Task waits until the tick count reaches `wake_at`.
+  Sleeping,
 }
 
 /// Task descriptor, provides task stack pointer.
+#[derive(Clone, Copy)]
 pub struct TaskDescriptor {
+  /// Saved stack pointer of the task.
   pub stack_start: u32,
+  /// Lowest stack address the task may use, zero for unused slots.
   pub stack_end: u32,
-  pub status: Status
+  /// Scheduling status.
+  pub status: Status,
+  /// Task priority, raised above `base_priority` while the task holds a
+  /// mutex a higher priority task waits for.
+  pub priority: Priority,
+  /// Priority the task was defined with.
+  pub base_priority: Priority,
+  /// Tick to wake up at if the task is sleeping.
+  pub wake_at: u32,
+  /// Mutexes held by the task, linked through the mutexes.
+  pub held_mutexes: *const Mutex,
 }
 
 impl TaskDescriptor {
+  /// Blocks the task and switches to the next runnable one once the critical
+  /// section is over.
   pub fn block(&mut self, _: NoInterrupts) {
-    self.status = Blocked;
+    self.status = Status::Blocked;
+    sched::switch_context();
+  }
+
+  /// Makes the task runnable again, preempting the current task if the
+  /// unblocked one has a higher priority.
+  pub fn unblock(&mut self, _: &NoInterrupts) {
+    self.status = Status::Runnable;
+    unsafe { Tasks.preempt_for(self.priority) };
+  }
+
+  /// Changes the task priority, rescheduling if another task should run
+  /// because of it.
+  pub fn set_priority(&mut self, priority: Priority, _: &NoInterrupts) {
+    let lowered = priority < self.priority;
+    self.priority = priority;
+    unsafe {
+      if lowered {
+        Tasks.reschedule();
+      } else {
+        Tasks.preempt_for(priority);
+      }
+    }
+  }
+
+  /// Puts the task to sleep until tick `deadline`, switching to the next
+  /// runnable task once the critical section is over.
+  pub fn sleep(&mut self, deadline: u32, _: NoInterrupts) {
+    self.wake_at = deadline;
+    self.status = Status::Sleeping;
+    unsafe {
+      NextWakeup = match NextWakeup {
+        Some(next) if deadline_passed(deadline, next) => Some(next),
+        _ => Some(deadline),
+      };
+    }
     sched::switch_context();
   }
-  pub fn unblock(&mut self, _: &NoInterrupts) { self.status = Runnable; }
 }
 
-struct TasksCollection {
-  pub current_task: usize,
-  pub tasks: [TaskDescriptor; MaxTasksCount],
+/// The task table.
+pub struct TasksCollection {
+  current_task: usize,
+  tasks: *mut TaskDescriptor,
+  capacity: usize,
+  running: bool,
 }
 
+/// Tasks known to the scheduler.
 pub static mut Tasks: TasksCollection = TasksCollection {
   current_task: 0,
-  tasks: [TaskDescriptor { stack_start: 0, stack_end: 0, status: Runnable }; MaxTasksCount]
+  tasks: 0 as *mut TaskDescriptor,
+  capacity: 0,
+  running: false,
 };
 
 impl TasksCollection {
+  fn tasks<'a>(&'a mut self) -> &'a mut [TaskDescriptor] {
+    unsafe { slice::from_raw_parts_mut(self.tasks, self.capacity) }
+  }
+
+  /// Returns the descriptor of the running task.
   pub fn current_task<'a>(&'a mut self) -> &'a mut TaskDescriptor {
-    &mut self.tasks[self.current_task]
+    let current = self.current_task;
+    &mut self.tasks()[current]
   }
 
+  /// Selects the highest priority runnable task. Tasks of the same priority
+  /// are picked in turns, starting after the current one.
   fn next_task(&mut self) {
-    loop {
-      self.current_task += 1;
-      if self.current_task == defined_tasks_count::get() {
-        self.current_task = 0;
+    let capacity = self.capacity;
+    let mut next = None;
+    let mut next_priority = IDLE_PRIORITY;
+    for offset in 1..capacity + 1 {
+      let index = (self.current_task + offset) % capacity;
+      let task = self.tasks()[index];
+      if !task.valid() || task.status != Status::Runnable {
+        continue;
       }
-      match self.current_task() {
-        &task if !task.valid()                 => {}
-        &TaskDescriptor {status: Runnable, ..} => break,
-        _                                      => {}
+      if next.is_none() || task.priority > next_priority {
+        next = Some(index);
+        next_priority = task.priority;
       }
     }
+
+    match next {
+      Some(index) => self.current_task = index,
+      // The idle task is always runnable.
+      None => unsafe { abort() },
+    }
+  }
+
+  /// Requests a context switch if a task of `priority` should preempt the
+  /// running one.
+  fn preempt_for(&mut self, priority: Priority) {
+    if self.running && priority > self.current_task().priority {
+      sched::switch_context();
+    }
+  }
+
+  /// Requests a context switch to let the scheduler pick the next task.
+  fn reschedule(&mut self) {
+    if self.running {
+      sched::switch_context();
+    }
   }
 
   fn add_task(&mut self, t: TaskDescriptor) {
-    self.tasks[defined_tasks_count::get()] = t;
-    defined_tasks_count::increase();
+    match self.tasks().iter_mut().find(|task| !task.valid()) {
+      Some(slot) => *slot = t,
+      None => unsafe { abort() },
+    }
+  }
+}
+
+/// Replaces the default task table.
+///
+/// Must be called before any task is defined.
+pub fn init(table: &'static mut [TaskDescriptor]) {
+  for task in table.iter_mut() {
+    *task = TASK_DESCRIPTOR_INIT;
+  }
+  unsafe {
+    Tasks.tasks = table.as_mut_ptr();
+    Tasks.capacity = table.len();
   }
 }
 
-/// Initialize and start task manager.
+/// Initialize and start task manager with a single task.
 ///
 /// This function keeps main stack intact. It starts the task scheduler and
 /// never returns.
 ///
 /// t should point to initial task.
 #[inline(never)]
-pub fn setup(t: Task, stack_size: u32) {
-  systick::setup(::hal::cortex_m3::systick::CALIBRATED, true);
-
-  let current_stack = sched::get_current_stack_pointer();
-  // User tasks start at this current stack size + reserved size aligned by 4
-  // bytes.
-  let task_stack_base: u32 = (current_stack as u32 - ReservedPivilegedStackSize) & !3;
-  current_stack_offset::set(task_stack_base);
-
-  let td = define_task(t, 0, stack_size, true);
+pub fn setup(t: Task, stack_size: u32) -> ! {
+  define_task(t, 0, stack_size, DEFAULT_PRIORITY);
+  start()
+}
 
-  td.load();
+/// Starts the task scheduler with the highest priority defined task.
+///
+/// This function keeps main stack intact and never returns.
+#[inline(never)]
+pub fn start() -> ! {
+  define_task(idle_task, 0, IdleStackSize, IDLE_PRIORITY);
+
+  unsafe {
+    Tasks.next_task();
+    let task = Tasks.current_task();
+    // PendSV stores the software saved registers of the "current" task on
+    // the first switch, make it store them right where the frame expects.
+    sched::set_task_stack_pointer(task.stack_start + SoftwareSavedSize);
+    stack::set_stack_limit(task.stack_end);
+    task.set_stack_guard();
+    Tasks.running = true;
+  }
+  if mpu::present() {
+    mpu::enable(true);
+  }
 
+  systick::setup(match systick::ten_ms() {
+    Some(ten_ms) => ten_ms / 10,
+    None => DefaultTickReload,
+  });
   systick::enable();
+  systick::enable_irq();
   sched::switch_context();
 
   unsafe { abort() };
 }
 
+/// Defines a new task running `t(arg)` with `stack_size` bytes of stack.
+///
+/// Tasks can be defined before `start` or by running tasks. In the latter
+/// case, a task with a higher priority than the caller runs immediately.
 #[inline(never)]
-pub fn define_task(t: Task, arg: u32, stack_size: u32, initial: bool) -> TaskDescriptor {
-  systick::disable_irq();
+pub fn define_task(t: Task, arg: u32, stack_size: u32, priority: Priority) {
+  let _crit = NoInterrupts::new();
+  unsafe {
+    if Tasks.capacity == 0 {
+      init(&mut DefaultTasksTable);
+    }
+  }
+  if current_stack_offset::get() == 0 {
+    // User tasks start at this current stack size + reserved size aligned by
+    // 8 bytes.
+    let current_stack = sched::get_current_stack_pointer();
+    current_stack_offset::set(
+        (current_stack - ReservedPivilegedStackSize) & !7);
+  }
+
   let task_base = current_stack_offset::get();
   let task_stack_size: u32 = (
     stack_size +
     8*4 +  // hw saved regs
     8*4 +  // sw saved regs
-    8*4    // scratch pad for __morestack failure. see note on morestack below.
+    8*4 +  // scratch pad for __morestack failure. see note on morestack below.
+    2*StackGuardSize +  // MPU guard region and its alignment.
+    0b1111
   ) & !0b1111;
   current_stack_offset::set(task_base - task_stack_size);
 
-  let td = TaskDescriptor::new(t, arg, task_base, stack_size, initial);
-  unsafe { Tasks.add_task(td) };
+  let td = TaskDescriptor::new(t, arg, task_base, stack_size, priority);
+  unsafe {
+    Tasks.add_task(td);
+    Tasks.preempt_for(priority);
+  }
+}
 
-  systick::enable_irq();
-  td
+/// Returns the number of milliseconds since the scheduler was started.
+///
+/// The counter wraps around after about 49 days.
+pub fn ticks() -> u32 {
+  unsafe { Ticks }
+}
+
+/// Blocks the current task for `ms` milliseconds.
+pub fn sleep_ms(ms: u32) {
+  sleep_until(ticks().wrapping_add(ms));
+}
+
+/// Blocks the current task until `ticks()` reaches `deadline`.
+///
+/// Returns immediately if the deadline has already passed.
+pub fn sleep_until(deadline: u32) {
+  let crit = NoInterrupts::new();
+  if deadline_passed(ticks(), deadline) {
+    return;
+  }
+  unsafe { Tasks.current_task().sleep(deadline, crit) };
+}
+
+/// Compares tick counts, accounting for the wrap-around.
+fn deadline_passed(now: u32, deadline: u32) -> bool {
+  now.wrapping_sub(deadline) as i32 >= 0
+}
+
+/// Terminates the current task, its slot is freed for new tasks.
+///
+/// Tasks returning from their function end up here as well.
+pub fn exit() -> ! {
+  unsafe { syscall(kill_current_task, 0) };
+  // PendSV switches away before the syscall returns.
+  loop {}
+}
+
+fn idle_task(_: u32) {
+  // Sleep until the next interrupt, which is at the latest the next SysTick.
+  loop {
+    wfi();
+  }
 }
 
 impl TaskDescriptor {
   /// Creates a new TaskDescriptor for given task, arg and stack base.
   ///
-  /// This function initializes task stack with hw saved registers.
+  /// This function initializes task stack with hw saved registers and leaves
+  /// room for the sw saved ones.
   #[inline(never)]
-  pub fn new(t: Task, arg: u32, stack_base: u32, stack_size: u32, initial: bool) -> TaskDescriptor {
+  pub fn new(t: Task, arg: u32, stack_base: u32, stack_size: u32,
+      priority: Priority) -> TaskDescriptor {
     let state = sched::SavedState::new(t, arg);
 
-    let mut stack_top: u32 = stack_base - size_of::<sched::SavedState>() as u32;
+    let stack_top: u32 = stack_base - size_of::<sched::SavedState>() as u32;
     unsafe { *(stack_top as *mut sched::SavedState) = state };
-    if !initial {
-      stack_top -= 8*4;
-    }
 
     TaskDescriptor {
-      stack_start: stack_top,
+      stack_start: stack_top - SoftwareSavedSize,
       stack_end: stack_base - stack_size,
-      status: Runnable,
+      status: Status::Runnable,
+      priority: priority,
+      base_priority: priority,
+      wake_at: 0,
+      held_mutexes: 0 as *const Mutex,
     }
   }
 
+  /// Makes this task's stack the current one.
   pub fn load(&self) {
     sched::set_task_stack_pointer(self.stack_start);
     stack::set_stack_limit(self.stack_end);
+    self.set_stack_guard();
   }
 
+  /// Programs the MPU guard region below the task stack, leaving room for
+  /// the `__morestack` reserve. Does nothing if there is no MPU.
+  ///
+  /// `define_task` allocates the guard and its alignment slack as part of
+  /// the task stack, so the guard never overlaps another task.
+  pub fn set_stack_guard(&self) {
+    if !mpu::present() {
+      return;
+    }
+    // The last region takes precedence over the ones set up by the
+    // application.
+    let region = mpu::regions() - 1;
+    let base = (self.stack_end - MorestackReserve - StackGuardSize) &
+        !(StackGuardSize - 1);
+    mpu::set_region(region, &mpu::Region {
+      base: base,
+      size: mpu::Size::Size32B,
+      access: mpu::Access::NoAccess,
+      memory: mpu::MemoryType::NonCacheable,
+      shareable: false,
+      executable: false,
+      disabled_subregions: 0,
+    });
+  }
+
+  /// Stores the current task stack pointer.
   pub fn save(&mut self) {
     self.stack_start = sched::get_task_stack_pointer();
   }
 
+  /// Returns true if the slot holds a live task.
   pub fn valid(&self) -> bool {
     self.stack_end != 0
   }
 
+  /// Frees the slot.
   pub fn invalidate(&mut self) {
     self.stack_end = 0;
   }
 }
 
+/// Counts a tick and wakes up tasks that are done sleeping, then switches to
+/// the next task if the time slice is over or a woken task preempts the
+/// running one. Called from the SysTick handler.
+#[inline(always)]
+pub unsafe fn task_tick() {
+  Ticks = Ticks.wrapping_add(1);
+  SliceTicks += 1;
+  let now = Ticks;
+  let preempt = match NextWakeup {
+    Some(next) if deadline_passed(now, next) => wake_sleepers(now),
+    _ => false,
+  };
+  if preempt || SliceTicks >= TIME_SLICE_TICKS {
+    task_scheduler();
+  }
+}
+
+/// Wakes up the tasks whose deadline passed and finds the next deadline.
+///
+/// Returns true if a woken task has a higher priority than the running one.
+unsafe fn wake_sleepers(now: u32) -> bool {
+  let current = Tasks.current_task().priority;
+  let mut preempt = false;
+  let mut next = None;
+  for task in Tasks.tasks().iter_mut() {
+    if !task.valid() || task.status != Status::Sleeping {
+      continue;
+    }
+    if deadline_passed(now, task.wake_at) {
+      task.status = Status::Runnable;
+      preempt = preempt || task.priority > current;
+    } else {
+      next = match next {
+        Some(next) if deadline_passed(task.wake_at, next) => Some(next),
+        _ => Some(task.wake_at),
+      };
+    }
+  }
+  NextWakeup = next;
+  preempt
+}
+
+/// Saves the current task and switches to the next one, called from the
+/// PendSV handler and `task_tick`.
 #[inline(always)]
 pub unsafe fn task_scheduler() {
-  stack::set_stack_limit(stack::stack_base() - ReservedPivilegedStackSize);
+  SliceTicks = 0;
+  stack::set_stack_limit(stack::stack_base() as u32 - ReservedPivilegedStackSize);
   Tasks.current_task().save();
   Tasks.next_task();
   Tasks.current_task().load();
@@ -215,6 +542,7 @@ pub unsafe fn task_scheduler() {
 // TODO(farcaller): this should not actually use stack!
 // At the time of the call of syscall(), the stack is overflown by 4, we still
 // have 12 bytes in reserve and 2*8*4 to save the frame in pendsv after kill.
+/// Kills the task that overflowed its stack.
 #[no_stack_check]
 pub fn morestack() {
   let psp = sched::get_task_stack_pointer();
@@ -226,6 +554,7 @@ pub fn morestack() {
   }
 }
 
+/// Frees the slot of the current task and switches to the next one.
 #[inline(never)]
 #[no_mangle]
 #[no_stack_check]
@@ -49,3 +49,10 @@ pub extern fn __morestack() {
 pub unsafe fn task_scheduler() {
   zinc::os::task::task_scheduler();
 }
+
+#[no_stack_check]
+#[no_mangle]
+#[cfg(feature = "multitasking")]
+pub unsafe fn task_tick() {
+  zinc::os::task::task_tick();
+}
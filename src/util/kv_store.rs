@@ -0,0 +1,591 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Log-structured key-value store on flash.
+//!
+//! The store keeps small blobs under `u16` keys in two flash sectors, one of
+//! which is active at a time. Writes append a record to the active sector, a
+//! later record for a key replacing the earlier ones, and deletions append a
+//! tombstone. When the active sector is full, the live records are copied to
+//! the other sector, which becomes the active one, so that the wear is spread
+//! over both sectors. The region returned by `hal::flash::user_data()` spans
+//! the last two sectors of flash so that it can hold a store.
+//!
+//! Records carry a CRC-32, records torn by a power loss are ignored. The
+//! sector a compaction copies to only becomes valid once the copy is
+//! committed, and the old sector is erased after that, so a power loss during
+//! compaction loses nothing.
+//!
+//! A sector starts with a header of a magic word, a sequence number and a
+//! commit word. Records follow, each made of a word with the key and the data
+//! length, the CRC of that word and of the data, then the data padded with
+//! ones to whole words.
+
+use core::cmp::min;
+use core::option::Option::{self, Some, None};
+use core::result::Result::{self, Ok, Err};
+
+use hal::flash::{self, Flash, Sector};
+
+/// First word of an initialized sector.
+const MAGIC: u32 = 0x4B56_5354;
+
+/// Last header word, written once the sector holds a complete copy.
+const COMMITTED: u32 = 0x434F_4D54;
+
+/// Value of erased flash words.
+const ERASED: u32 = 0xFFFF_FFFF;
+
+/// Size of the sector header.
+const HEADER_SIZE: u32 = 3 * 4;
+
+/// Size of the key and CRC words of a record.
+const RECORD_HEADER_SIZE: u32 = 2 * 4;
+
+/// Length of tombstone records.
+const DELETED: u32 = 0xFFFF;
+
+/// Key reserved so that record headers are never all ones.
+const RESERVED_KEY: u16 = 0xFFFF;
+
+/// Key-value store errors.
+#[derive(PartialEq, Clone, Copy)]
+pub enum Error {
+  /// The key has no value.
+  NotFound,
+  /// The buffer is too small for the value.
+  BufferTooSmall,
+  /// The key is reserved.
+  InvalidKey,
+  /// The value can't fit in a sector.
+  TooLarge,
+  /// There's no room left for the value, even after compaction.
+  Full,
+  /// A flash operation failed.
+  Flash(flash::Error),
+}
+
+/// A record found in a sector.
+#[derive(Clone, Copy)]
+struct Record {
+  addr: u32,
+  key: u16,
+  len: u32,
+  valid: bool,
+}
+
+impl Record {
+  fn is_tombstone(&self) -> bool {
+    self.len == DELETED
+  }
+
+  fn data_len(&self) -> u32 {
+    if self.is_tombstone() { 0 } else { self.len }
+  }
+
+  fn size(&self) -> u32 {
+    record_size(self.data_len())
+  }
+}
+
+/// A key-value store in two flash sectors.
+pub struct KvStore<'a, F: Flash + 'a> {
+  flash: &'a F,
+  sectors: [Sector; 2],
+  size: u32,
+  active: usize,
+  sequence: u32,
+  end: u32,
+}
+
+impl<'a, F: Flash> KvStore<'a, F> {
+  /// Opens the store kept in sectors `first` and `second` of `flash`,
+  /// formatting them if neither holds a store.
+  ///
+  /// Finishes a compaction interrupted by a power loss, if any.
+  pub fn new(flash: &'a F, first: usize, second: usize)
+      -> Result<KvStore<'a, F>, Error> {
+    let sectors = [flash.sector(first), flash.sector(second)];
+    let mut store = KvStore {
+      flash: flash,
+      sectors: sectors,
+      size: min(sectors[0].size, sectors[1].size),
+      active: 0,
+      sequence: 0,
+      end: 0,
+    };
+
+    match (store.committed_sequence(0), store.committed_sequence(1)) {
+      (Some(a), Some(b)) => {
+        // Power was lost before the compacted sector was erased.
+        let newer = if is_newer(b, a) { 1 } else { 0 };
+        try!(store.erase(1 - newer));
+        store.active = newer;
+        store.sequence = if newer == 1 { b } else { a };
+      },
+      (Some(a), None) => {
+        store.active = 0;
+        store.sequence = a;
+      },
+      (None, Some(b)) => {
+        store.active = 1;
+        store.sequence = b;
+      },
+      (None, None) => {
+        try!(store.format(0, 0));
+        try!(store.commit(0));
+        store.active = 0;
+        store.sequence = 0;
+      },
+    }
+    store.end = store.find_end();
+    Ok(store)
+  }
+
+  /// Reads the value of `key` into `buf`, returning its length.
+  pub fn get(&self, key: u16, buf: &mut [u8]) -> Result<usize, Error> {
+    let record = match self.find(key) {
+      Some(record) if !record.is_tombstone() => record,
+      _ => return Err(Error::NotFound),
+    };
+    let len = record.len as usize;
+    if buf.len() < len {
+      return Err(Error::BufferTooSmall);
+    }
+    for (i, byte) in buf[..len].iter_mut().enumerate() {
+      *byte = self.data_byte(&record, i as u32);
+    }
+    Ok(len)
+  }
+
+  /// Returns true if `key` has a value.
+  pub fn contains(&self, key: u16) -> bool {
+    match self.find(key) {
+      Some(record) => !record.is_tombstone(),
+      None => false,
+    }
+  }
+
+  /// Sets the value of `key`.
+  pub fn put(&mut self, key: u16, data: &[u8]) -> Result<(), Error> {
+    if key == RESERVED_KEY {
+      return Err(Error::InvalidKey);
+    }
+    if data.len() as u32 >= DELETED {
+      return Err(Error::TooLarge);
+    }
+    self.append(key, data.len() as u32, data)
+  }
+
+  /// Removes the value of `key`.
+  pub fn delete(&mut self, key: u16) -> Result<(), Error> {
+    if !self.contains(key) {
+      return Err(Error::NotFound);
+    }
+    self.append(key, DELETED, &[])
+  }
+
+  /// Copies the live records to the other sector, reclaiming the space of
+  /// replaced and deleted values.
+  pub fn compact(&mut self) -> Result<(), Error> {
+    let source = self.active;
+    let target = 1 - source;
+    let sequence = self.sequence.wrapping_add(1);
+    try!(self.format(target, sequence));
+
+    let mut dest = self.sectors[target].start + HEADER_SIZE;
+    let mut addr = self.sectors[source].start + HEADER_SIZE;
+    while let Some(record) = self.record_at(source, addr) {
+      if record.valid && !record.is_tombstone() && self.is_latest(&record) {
+        for offset in 0..record.size() / 4 {
+          let word = self.flash.read_word(record.addr + offset * 4);
+          try!(self.program(dest + offset * 4, word));
+        }
+        dest += record.size();
+      }
+      addr += record.size();
+    }
+
+    try!(self.commit(target));
+    self.active = target;
+    self.sequence = sequence;
+    self.end = dest;
+    self.erase(source)
+  }
+
+  fn append(&mut self, key: u16, len: u32, data: &[u8]) -> Result<(), Error> {
+    let size = record_size(if len == DELETED { 0 } else { len });
+    if size > self.size - HEADER_SIZE {
+      return Err(Error::TooLarge);
+    }
+    if self.end + size > self.sector_end(self.active) {
+      try!(self.compact());
+      if self.end + size > self.sector_end(self.active) {
+        return Err(Error::Full);
+      }
+    }
+
+    let addr = self.end;
+    // A failed write leaves a torn record behind, skip over it.
+    self.end += size;
+
+    let header = (key as u32) << 16 | len;
+    let mut crc = crc32_word(!0, header);
+    try!(self.program(addr, header));
+    for (i, chunk) in data.chunks(4).enumerate() {
+      let mut word = ERASED;
+      for (shift, &byte) in chunk.iter().enumerate() {
+        word &= !(0xFF << (shift * 8));
+        word |= (byte as u32) << (shift * 8);
+        crc = crc32_byte(crc, byte);
+      }
+      try!(self.program(addr + RECORD_HEADER_SIZE + i as u32 * 4, word));
+    }
+    self.program(addr + 4, !crc)
+  }
+
+  /// Returns the latest valid record of `key`.
+  fn find(&self, key: u16) -> Option<Record> {
+    let mut found = None;
+    let mut addr = self.sectors[self.active].start + HEADER_SIZE;
+    while let Some(record) = self.record_at(self.active, addr) {
+      if record.valid && record.key == key {
+        found = Some(record);
+      }
+      addr += record.size();
+    }
+    found
+  }
+
+  /// Returns true if no later valid record has the key of `record`.
+  fn is_latest(&self, record: &Record) -> bool {
+    let mut addr = record.addr + record.size();
+    while let Some(later) = self.record_at(self.active, addr) {
+      if later.valid && later.key == record.key {
+        return false;
+      }
+      addr += later.size();
+    }
+    true
+  }
+
+  /// Returns the address following the last record of the active sector.
+  fn find_end(&self) -> u32 {
+    let sector_end = self.sector_end(self.active);
+    let mut addr = self.sectors[self.active].start + HEADER_SIZE;
+    while let Some(record) = self.record_at(self.active, addr) {
+      addr += record.size();
+    }
+    if addr + 4 <= sector_end && self.flash.read_word(addr) != ERASED {
+      // A torn record header, the rest of the sector can't be used.
+      sector_end
+    } else {
+      addr
+    }
+  }
+
+  /// Returns the record at `addr` of `sector`, if there is a complete one.
+  fn record_at(&self, sector: usize, addr: u32) -> Option<Record> {
+    let sector_end = self.sector_end(sector);
+    if addr + RECORD_HEADER_SIZE > sector_end {
+      return None;
+    }
+    let header = self.flash.read_word(addr);
+    if header == ERASED {
+      return None;
+    }
+    let mut record = Record {
+      addr: addr,
+      key: (header >> 16) as u16,
+      len: header & 0xFFFF,
+      valid: false,
+    };
+    if addr + record.size() > sector_end {
+      return None;
+    }
+
+    let mut crc = crc32_word(!0, header);
+    for i in 0..record.data_len() {
+      crc = crc32_byte(crc, self.data_byte(&record, i));
+    }
+    record.valid = self.flash.read_word(addr + 4) == !crc;
+    Some(record)
+  }
+
+  fn data_byte(&self, record: &Record, index: u32) -> u8 {
+    let word = self.flash.read_word(
+        record.addr + RECORD_HEADER_SIZE + index / 4 * 4);
+    (word >> (index % 4 * 8)) as u8
+  }
+
+  /// Returns the sequence number of `sector` if it holds a committed store.
+  fn committed_sequence(&self, sector: usize) -> Option<u32> {
+    let start = self.sectors[sector].start;
+    if self.flash.read_word(start) == MAGIC &&
+        self.flash.read_word(start + 8) == COMMITTED {
+      Some(self.flash.read_word(start + 4))
+    } else {
+      None
+    }
+  }
+
+  fn sector_end(&self, sector: usize) -> u32 {
+    self.sectors[sector].start + self.size
+  }
+
+  /// Erases `sector` and writes an uncommitted header.
+  fn format(&self, sector: usize, sequence: u32) -> Result<(), Error> {
+    let start = self.sectors[sector].start;
+    try!(self.erase(sector));
+    try!(self.program(start, MAGIC));
+    self.program(start + 4, sequence)
+  }
+
+  fn commit(&self, sector: usize) -> Result<(), Error> {
+    self.program(self.sectors[sector].start + 8, COMMITTED)
+  }
+
+  fn erase(&self, sector: usize) -> Result<(), Error> {
+    let index = try!(self.flash.sector_at(self.sectors[sector].start)
+        .map_err(Error::Flash));
+    self.flash.erase(index).map_err(Error::Flash)
+  }
+
+  fn program(&self, addr: u32, word: u32) -> Result<(), Error> {
+    self.flash.program_word(addr, word).map_err(Error::Flash)
+  }
+}
+
+/// Returns the size of a record with `len` bytes of data.
+fn record_size(len: u32) -> u32 {
+  RECORD_HEADER_SIZE + (len + 3) / 4 * 4
+}
+
+/// Compares sequence numbers, accounting for the wrap-around.
+fn is_newer(a: u32, b: u32) -> bool {
+  a.wrapping_sub(b) as i32 > 0
+}
+
+fn crc32_byte(crc: u32, byte: u8) -> u32 {
+  let mut crc = crc ^ byte as u32;
+  for _ in 0..8 {
+    crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+  }
+  crc
+}
+
+fn crc32_word(crc: u32, word: u32) -> u32 {
+  let mut crc = crc;
+  for shift in 0..4 {
+    crc = crc32_byte(crc, (word >> (shift * 8)) as u8);
+  }
+  crc
+}
+
+#[cfg(test)]
+pub mod test {
+  use core::cell::{Cell, RefCell};
+  use core::result::Result::{self, Ok, Err};
+
+  use hal::flash::{self, Flash, Sector};
+  use super::{KvStore, Error};
+
+  const SECTOR_WORDS: usize = 64;
+  const SECTOR_SIZE: u32 = SECTOR_WORDS as u32 * 4;
+
+  /// Two sectors of flash in RAM, which can simulate a power loss by failing
+  /// all operations after a given number of them.
+  pub struct RamFlash {
+    words: RefCell<[u32; 2 * SECTOR_WORDS]>,
+    operations_left: Cell<usize>,
+  }
+
+  impl RamFlash {
+    pub fn new() -> RamFlash {
+      RamFlash {
+        words: RefCell::new([0xFFFF_FFFF; 2 * SECTOR_WORDS]),
+        operations_left: Cell::new(!0),
+      }
+    }
+
+    pub fn fail_after(&self, operations: usize) {
+      self.operations_left.set(operations);
+    }
+
+    fn operation(&self) -> Result<(), flash::Error> {
+      let left = self.operations_left.get();
+      if left == 0 {
+        return Err(flash::Error::Failed);
+      }
+      self.operations_left.set(left - 1);
+      Ok(())
+    }
+  }
+
+  impl Flash for RamFlash {
+    fn sector_count(&self) -> usize {
+      2
+    }
+
+    fn sector(&self, index: usize) -> Sector {
+      Sector {
+        start: index as u32 * SECTOR_SIZE,
+        size: SECTOR_SIZE,
+      }
+    }
+
+    fn erase(&self, index: usize) -> Result<(), flash::Error> {
+      try!(self.operation());
+      let mut words = self.words.borrow_mut();
+      for word in words[index * SECTOR_WORDS..][..SECTOR_WORDS].iter_mut() {
+        *word = 0xFFFF_FFFF;
+      }
+      Ok(())
+    }
+
+    fn program_word(&self, addr: u32, value: u32) -> Result<(), flash::Error> {
+      try!(self.operation());
+      // Programming can only clear bits.
+      self.words.borrow_mut()[addr as usize / 4] &= value;
+      Ok(())
+    }
+
+    fn program_half_word(&self, addr: u32, value: u16)
+        -> Result<(), flash::Error> {
+      let shift = (addr & 2) * 8;
+      self.program_word(addr & !3, !(0xFFFF << shift) | (value as u32) << shift)
+    }
+
+    fn read_word(&self, addr: u32) -> u32 {
+      self.words.borrow()[addr as usize / 4]
+    }
+  }
+
+  fn get(store: &KvStore<RamFlash>, key: u16)
+      -> Result<([u8; 16], usize), Error> {
+    let mut buf = [0; 16];
+    let len = try!(store.get(key, &mut buf));
+    Ok((buf, len))
+  }
+
+  fn assert_value(store: &KvStore<RamFlash>, key: u16, value: &[u8]) {
+    match get(store, key) {
+      Ok((buf, len)) => assert!(&buf[..len] == value),
+      Err(_) => panic!("key {} has no value", key),
+    }
+  }
+
+  #[test]
+  fn gets_the_latest_value() {
+    let flash = RamFlash::new();
+    let mut store = KvStore::new(&flash, 0, 1).ok().unwrap();
+    assert!(store.put(1, b"first").is_ok());
+    assert!(store.put(2, b"other").is_ok());
+    assert!(store.put(1, b"second").is_ok());
+    assert_value(&store, 1, b"second");
+    assert_value(&store, 2, b"other");
+    assert!(get(&store, 3).err() == Some(Error::NotFound));
+  }
+
+  #[test]
+  fn deletes_values() {
+    let flash = RamFlash::new();
+    let mut store = KvStore::new(&flash, 0, 1).ok().unwrap();
+    assert!(store.put(1, b"value").is_ok());
+    assert!(store.delete(1).is_ok());
+    assert!(!store.contains(1));
+    assert!(store.delete(1).err() == Some(Error::NotFound));
+  }
+
+  #[test]
+  fn rejects_small_buffers_and_reserved_keys() {
+    let flash = RamFlash::new();
+    let mut store = KvStore::new(&flash, 0, 1).ok().unwrap();
+    assert!(store.put(1, b"value").is_ok());
+    let mut buf = [0; 4];
+    assert!(store.get(1, &mut buf).err() == Some(Error::BufferTooSmall));
+    assert!(store.put(0xFFFF, b"value").err() == Some(Error::InvalidKey));
+  }
+
+  #[test]
+  fn keeps_values_across_reopening() {
+    let flash = RamFlash::new();
+    {
+      let mut store = KvStore::new(&flash, 0, 1).ok().unwrap();
+      assert!(store.put(1, b"one").is_ok());
+      assert!(store.put(2, b"two").is_ok());
+      assert!(store.delete(2).is_ok());
+    }
+    let store = KvStore::new(&flash, 0, 1).ok().unwrap();
+    assert_value(&store, 1, b"one");
+    assert!(!store.contains(2));
+  }
+
+  #[test]
+  fn compacts_when_full() {
+    let flash = RamFlash::new();
+    let mut store = KvStore::new(&flash, 0, 1).ok().unwrap();
+    assert!(store.put(1, b"kept").is_ok());
+    for i in 0..100u8 {
+      assert!(store.put(2, &[i, i, i, i]).is_ok());
+    }
+    assert_value(&store, 1, b"kept");
+    assert_value(&store, 2, &[99, 99, 99, 99]);
+
+    let store = KvStore::new(&flash, 0, 1).ok().unwrap();
+    assert_value(&store, 1, b"kept");
+    assert_value(&store, 2, &[99, 99, 99, 99]);
+  }
+
+  #[test]
+  fn survives_power_loss_during_compaction() {
+    let flash = RamFlash::new();
+    {
+      let mut store = KvStore::new(&flash, 0, 1).ok().unwrap();
+      assert!(store.put(1, b"kept").is_ok());
+      // Fills the sector: a 12 byte header and 20 records of 12 bytes.
+      for i in 0..19u8 {
+        assert!(store.put(2, &[i, i, i, i]).is_ok());
+      }
+      // Erases the other sector and writes the magic, then loses power.
+      flash.fail_after(2);
+      assert!(store.put(2, b"lost").err() ==
+          Some(Error::Flash(flash::Error::Failed)));
+    }
+    flash.fail_after(!0);
+    let mut store = KvStore::new(&flash, 0, 1).ok().unwrap();
+    assert_value(&store, 1, b"kept");
+    assert_value(&store, 2, &[18, 18, 18, 18]);
+    assert!(store.put(2, b"new").is_ok());
+    assert_value(&store, 2, b"new");
+  }
+
+  #[test]
+  fn ignores_torn_records() {
+    let flash = RamFlash::new();
+    {
+      let mut store = KvStore::new(&flash, 0, 1).ok().unwrap();
+      assert!(store.put(1, b"old").is_ok());
+      // Writes the record header and data, but not its CRC.
+      flash.fail_after(2);
+      assert!(store.put(1, b"new").is_err());
+    }
+    flash.fail_after(!0);
+    let mut store = KvStore::new(&flash, 0, 1).ok().unwrap();
+    assert_value(&store, 1, b"old");
+    assert!(store.put(1, b"newer").is_ok());
+    assert_value(&store, 1, b"newer");
+  }
+}
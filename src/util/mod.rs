@@ -18,6 +18,8 @@
 pub mod strconv;
 pub mod support;
 pub mod shared;
+pub mod ring_buffer;
+pub mod kv_store;
 #[cfg(feature = "multitasking")] pub mod queue;
 
 mod lang_items;
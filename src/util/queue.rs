@@ -6,31 +6,39 @@
 // | |--->| |--->| |--->| |--->| |
 //
 
-use core::ty::Unsafe;
+//! Intrusive queue, used for task wait lists.
+
+use core::cell::UnsafeCell;
 use core::cmp::Ord;
 use core::ops::Deref;
-use core::ptr::RawPtr;
 use core::option::Option::{self, Some, None};
 
-use hal::cortex_m3::sched::NoInterrupts;
+use hal::cortex_m3::irq::NoInterrupts;
 
+/// Queue entry, usually allocated on the stack of a waiting task.
 pub struct Node<T> {
-  pub next: Unsafe<*mut Node<T>>,
+  #[allow(missing_docs)]
+  pub next: UnsafeCell<*mut Node<T>>,
+  #[allow(missing_docs)]
   pub data: T
 }
 
+/// A queue of nodes owned by someone else.
 pub struct Queue<T> {
-  pub head: Unsafe<*mut Node<T>>,
-  pub tail: Unsafe<*mut Node<T>>
+  #[allow(missing_docs)]
+  pub head: UnsafeCell<*mut Node<T>>,
+  #[allow(missing_docs)]
+  pub tail: UnsafeCell<*mut Node<T>>
 }
 
-fn null_mut<T>() -> *mut T { 0 as *mut T }
+const fn null_mut<T>() -> *mut T { 0 as *mut T }
 
 impl<T> Queue<T> {
-  pub fn new() -> Queue<T> {
+  /// Creates an empty queue.
+  pub const fn new() -> Queue<T> {
     Queue {
-      head: Unsafe::new(null_mut()),
-      tail: Unsafe::new(null_mut())
+      head: UnsafeCell::new(null_mut()),
+      tail: UnsafeCell::new(null_mut())
     }
   }
 
@@ -63,8 +71,36 @@ impl<T> Queue<T> {
     if (*head).is_null() {
       None
     } else {
-      *head = *(**head).next.get();
-      Some(*head)
+      let node = *head;
+      *head = *(*node).next.get();
+      if (*head).is_null() {
+        *self.tail.get() = null_mut();
+      }
+      Some(node)
+    }
+  }
+}
+
+impl<T> Queue<T> {
+  /// Unlink a node from anywhere in the queue, if it's there.
+  pub unsafe fn remove(&self, node: *mut Node<T>, _: &NoInterrupts) {
+    let mut prev: *mut Node<T> = null_mut();
+    let mut i: *mut Node<T> = *self.head.get();
+    while !i.is_null() {
+      if i == node {
+        let next = *(*i).next.get();
+        if prev.is_null() {
+          *self.head.get() = next;
+        } else {
+          *(*prev).next.get() = next;
+        }
+        if *self.tail.get() == node {
+          *self.tail.get() = prev;
+        }
+        return;
+      }
+      prev = i;
+      i = *(*i).next.get();
     }
   }
 }
@@ -72,7 +108,7 @@ impl<T> Queue<T> {
 impl<T: Ord> Queue<T> {
   /// Priority insertion (higher ends up closer to head).
   pub unsafe fn insert(&self, node: *mut Node<T>, _: &NoInterrupts) {
-    let mut next: &Unsafe<*mut Node<T>> = &self.head;
+    let mut next: &UnsafeCell<*mut Node<T>> = &self.head;
     loop {
       let i: *mut Node<T> = *next.get();
       if i.is_null() {
@@ -92,11 +128,13 @@ impl<T: Ord> Queue<T> {
 }
 
 impl<T> Node<T> {
+  /// Creates an unlinked node.
   pub fn new(data: T) -> Node<T> {
-    Node { next: Unsafe::new(null_mut()), data: data }
+    Node { next: UnsafeCell::new(null_mut()), data: data }
   }
 }
 
-impl<T> Deref<T> for Node<T> {
+impl<T> Deref for Node<T> {
+  type Target = T;
   fn deref<'a>(&'a self) -> &'a T {&self.data}
 }
@@ -0,0 +1,128 @@
+// Zinc, the bare metal stack for rust.
+// Copyright 2015 zinc developers <http://zinc.rs>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Fixed size byte FIFO.
+//!
+//! The buffer has no synchronisation of its own, wrap it into
+//! `util::shared::Shared` if it is accessed from an ISR.
+
+use core::option::Option::{self, Some, None};
+
+/// Number of bytes a `RingBuffer` can hold.
+pub const CAPACITY: usize = 64;
+
+/// A FIFO of bytes backed by a static array.
+pub struct RingBuffer {
+  buf: [u8; CAPACITY],
+  head: usize,
+  len: usize,
+}
+
+impl RingBuffer {
+  /// Creates an empty buffer.
+  pub const fn new() -> RingBuffer {
+    RingBuffer {
+      buf: [0; CAPACITY],
+      head: 0,
+      len: 0,
+    }
+  }
+
+  /// Appends a byte, returns false if the buffer is full.
+  pub fn push(&mut self, value: u8) -> bool {
+    if self.is_full() {
+      return false;
+    }
+    self.buf[(self.head + self.len) % CAPACITY] = value;
+    self.len += 1;
+    true
+  }
+
+  /// Removes the oldest byte.
+  pub fn pop(&mut self) -> Option<u8> {
+    if self.is_empty() {
+      return None;
+    }
+    let value = self.buf[self.head];
+    self.head = (self.head + 1) % CAPACITY;
+    self.len -= 1;
+    Some(value)
+  }
+
+  /// Returns the number of bytes stored.
+  pub fn len(&self) -> usize {
+    self.len
+  }
+
+  /// Returns true if there is nothing to pop.
+  pub fn is_empty(&self) -> bool {
+    self.len == 0
+  }
+
+  /// Returns true if a push would fail.
+  pub fn is_full(&self) -> bool {
+    self.len == CAPACITY
+  }
+
+  /// Drops all stored bytes.
+  pub fn clear(&mut self) {
+    self.head = 0;
+    self.len = 0;
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use core::option::Option::{Some, None};
+  use super::{RingBuffer, CAPACITY};
+
+  #[test]
+  fn pops_in_push_order() {
+    let mut rb = RingBuffer::new();
+    assert!(rb.push(1));
+    assert!(rb.push(2));
+    assert!(rb.len() == 2);
+    assert!(rb.pop() == Some(1));
+    assert!(rb.pop() == Some(2));
+    assert!(rb.pop() == None);
+    assert!(rb.is_empty());
+  }
+
+  #[test]
+  fn rejects_push_when_full() {
+    let mut rb = RingBuffer::new();
+    for i in 0..CAPACITY {
+      assert!(rb.push(i as u8));
+    }
+    assert!(rb.is_full());
+    assert!(!rb.push(0xff));
+    assert!(rb.pop() == Some(0));
+    assert!(rb.push(0xff));
+  }
+
+  #[test]
+  fn wraps_around() {
+    let mut rb = RingBuffer::new();
+    for round in 0..3 {
+      for i in 0..CAPACITY - 1 {
+        assert!(rb.push((i + round) as u8));
+      }
+      for i in 0..CAPACITY - 1 {
+        assert!(rb.pop() == Some((i + round) as u8));
+      }
+    }
+    assert!(rb.is_empty());
+  }
+}
@@ -20,13 +20,13 @@ use core::ops::{Deref, DerefMut};
 use core::marker::{Sync, Send};
 
 #[cfg(feature = "cpu_cortex-m3")]
-use hal::cortex_m3::irq::NoInterrupts;
+pub use hal::cortex_m3::irq::NoInterrupts;
 #[cfg(feature = "cpu_cortex-m4")]
-use hal::cortex_m4::irq::NoInterrupts;
+pub use hal::cortex_m4::irq::NoInterrupts;
 // If cpu doesn't have nointerrupts provide dummy implementation
 #[cfg(not(any(feature = "cpu_cortex-m3",
               feature = "cpu_cortex-m4")))]
-use self::dummy_irq::NoInterrupts;
+pub use self::dummy_irq::NoInterrupts;
 
 #[allow(missing_docs)]
 mod dummy_irq {
@@ -56,7 +56,7 @@ pub struct SharedRef<'a, T: 'a> {
 
 impl<T> Shared<T> {
   /// Create a new `Shared` value
-  pub fn new(value: T) -> Shared<T> {
+  pub const fn new(value: T) -> Shared<T> {
     Shared {
       value: UnsafeCell::new(value),
     }